@@ -0,0 +1,308 @@
+//! Affine transformations of point coordinates.
+//!
+//! An `Affine` is a 4x4 matrix that moves, rotates, and scales xyz coordinates, e.g. for boresight
+//! corrections, registering a local survey into a global frame, or a simple datum shift. To apply
+//! one to a whole file, transform the header first so the output can hold the moved points, then
+//! transform each point:
+//!
+//! ```
+//! use las::{Affine, Read, Reader, Write, Writer};
+//!
+//! let mut reader = Reader::from_path("tests/data/autzen.las").unwrap();
+//! let affine = Affine::rotation_z(std::f64::consts::FRAC_PI_2).then(&Affine::translation(0., 0., 100.));
+//! let header = affine.transform_header(reader.header()).unwrap();
+//! let mut writer = Writer::new(std::io::Cursor::new(Vec::new()), header).unwrap();
+//! for point in reader.points() {
+//!     let mut point = point.unwrap();
+//!     affine.transform_point(&mut point);
+//!     writer.write(point).unwrap();
+//! }
+//! ```
+
+use crate::{Bounds, Builder, Header, Point, Result, Transform, Vector};
+
+/// A 4x4 affine transformation matrix.
+///
+/// The matrix is row-major and is applied to column vectors, so the translation lives in the last
+/// column.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Affine {
+    /// The matrix, `matrix[row][column]`.
+    pub matrix: [[f64; 4]; 4],
+}
+
+impl Affine {
+    /// Creates an affine transformation from a row-major matrix.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::{Affine, Vector};
+    /// let affine = Affine::new([
+    ///     [1., 0., 0., 10.],
+    ///     [0., 1., 0., 20.],
+    ///     [0., 0., 1., 30.],
+    ///     [0., 0., 0., 1.],
+    /// ]);
+    /// assert_eq!(Vector { x: 11., y: 22., z: 33. }, affine.transform(Vector { x: 1., y: 2., z: 3. }));
+    /// ```
+    pub fn new(matrix: [[f64; 4]; 4]) -> Affine {
+        Affine { matrix }
+    }
+
+    /// Returns the identity transformation.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::{Affine, Vector};
+    /// let vector = Vector { x: 1., y: 2., z: 3. };
+    /// assert_eq!(vector, Affine::identity().transform(vector));
+    /// ```
+    pub fn identity() -> Affine {
+        Affine::new([
+            [1., 0., 0., 0.],
+            [0., 1., 0., 0.],
+            [0., 0., 1., 0.],
+            [0., 0., 0., 1.],
+        ])
+    }
+
+    /// Returns a translation.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::{Affine, Vector};
+    /// let affine = Affine::translation(1., 2., 3.);
+    /// assert_eq!(Vector { x: 1., y: 2., z: 3. }, affine.transform(Vector::default()));
+    /// ```
+    pub fn translation(x: f64, y: f64, z: f64) -> Affine {
+        let mut affine = Affine::identity();
+        affine.matrix[0][3] = x;
+        affine.matrix[1][3] = y;
+        affine.matrix[2][3] = z;
+        affine
+    }
+
+    /// Returns a scaling about the origin.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::{Affine, Vector};
+    /// let affine = Affine::scaling(2., 2., 0.3048);
+    /// let vector = affine.transform(Vector { x: 1., y: 1., z: 1. });
+    /// assert_eq!(0.3048, vector.z);
+    /// ```
+    pub fn scaling(x: f64, y: f64, z: f64) -> Affine {
+        let mut affine = Affine::identity();
+        affine.matrix[0][0] = x;
+        affine.matrix[1][1] = y;
+        affine.matrix[2][2] = z;
+        affine
+    }
+
+    /// Returns a counter-clockwise rotation about the z axis, in radians.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::{Affine, Vector};
+    /// let affine = Affine::rotation_z(std::f64::consts::FRAC_PI_2);
+    /// let vector = affine.transform(Vector { x: 1., y: 0., z: 0. });
+    /// assert!(vector.x.abs() < 1e-12);
+    /// assert!((vector.y - 1.).abs() < 1e-12);
+    /// ```
+    pub fn rotation_z(radians: f64) -> Affine {
+        let (sin, cos) = radians.sin_cos();
+        let mut affine = Affine::identity();
+        affine.matrix[0][0] = cos;
+        affine.matrix[0][1] = -sin;
+        affine.matrix[1][0] = sin;
+        affine.matrix[1][1] = cos;
+        affine
+    }
+
+    /// Returns the transformation that applies this one, then the other.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::{Affine, Vector};
+    /// let affine = Affine::translation(1., 0., 0.).then(&Affine::scaling(2., 1., 1.));
+    /// assert_eq!(4., affine.transform(Vector { x: 1., y: 0., z: 0. }).x);
+    /// ```
+    pub fn then(&self, other: &Affine) -> Affine {
+        let mut matrix = [[0.; 4]; 4];
+        for (i, row) in matrix.iter_mut().enumerate() {
+            for (j, value) in row.iter_mut().enumerate() {
+                *value = (0..4).map(|k| other.matrix[i][k] * self.matrix[k][j]).sum();
+            }
+        }
+        Affine::new(matrix)
+    }
+
+    /// Transforms an xyz vector.
+    ///
+    /// If the last row of the matrix isn't `[0, 0, 0, 1]`, the result is divided by the
+    /// homogeneous coordinate.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::{Affine, Vector};
+    /// let vector = Affine::translation(0., 0., -1.).transform(Vector { x: 0., y: 0., z: 1. });
+    /// assert_eq!(0., vector.z);
+    /// ```
+    pub fn transform(&self, vector: Vector<f64>) -> Vector<f64> {
+        let m = &self.matrix;
+        let row = |i: usize| m[i][0] * vector.x + m[i][1] * vector.y + m[i][2] * vector.z + m[i][3];
+        let w = row(3);
+        Vector {
+            x: row(0) / w,
+            y: row(1) / w,
+            z: row(2) / w,
+        }
+    }
+
+    /// Transforms the coordinates of a point in place.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::{Affine, Point};
+    /// let mut point = Point { x: 1., y: 2., z: 3., ..Default::default() };
+    /// Affine::translation(1., 1., 1.).transform_point(&mut point);
+    /// assert_eq!((2., 3., 4.), (point.x, point.y, point.z));
+    /// ```
+    pub fn transform_point(&self, point: &mut Point) {
+        let vector = self.transform(Vector {
+            x: point.x,
+            y: point.y,
+            z: point.z,
+        });
+        point.x = vector.x;
+        point.y = vector.y;
+        point.z = vector.z;
+    }
+
+    /// Transforms bounds, returning the bounds of the transformed box.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::{Affine, Bounds, Vector};
+    /// let bounds = Bounds {
+    ///     min: Vector { x: 0., y: 0., z: 0. },
+    ///     max: Vector { x: 1., y: 2., z: 3. },
+    /// };
+    /// let bounds = Affine::scaling(-1., 1., 1.).transform_bounds(&bounds);
+    /// assert_eq!(-1., bounds.min.x);
+    /// assert_eq!(0., bounds.max.x);
+    /// ```
+    pub fn transform_bounds(&self, bounds: &Bounds) -> Bounds {
+        let mut transformed = Bounds::default();
+        for &x in &[bounds.min.x, bounds.max.x] {
+            for &y in &[bounds.min.y, bounds.max.y] {
+                for &z in &[bounds.min.z, bounds.max.z] {
+                    let corner = self.transform(Vector { x, y, z });
+                    transformed.grow(&Point {
+                        x: corner.x,
+                        y: corner.y,
+                        z: corner.z,
+                        ..Default::default()
+                    });
+                }
+            }
+        }
+        transformed
+    }
+
+    /// Returns a header for the transformed points.
+    ///
+    /// The bounds are transformed, and the offsets (and, if needed, the scales) are refit with
+    /// `Transform::fit` so that every transformed point can be written.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::{Affine, Read, Reader};
+    /// let reader = Reader::from_path("tests/data/autzen.las").unwrap();
+    /// let affine = Affine::translation(1e8, 0., 0.);
+    /// let header = affine.transform_header(reader.header()).unwrap();
+    /// assert!(header.transforms().x.inverse(header.bounds().max.x).is_ok());
+    /// ```
+    pub fn transform_header(&self, header: &Header) -> Result<Header> {
+        let bounds = self.transform_bounds(&header.bounds());
+        let mut builder = Builder::from(header.clone());
+        let transforms = header.transforms();
+        builder.transforms = Vector {
+            x: Transform::fit(bounds.min.x, bounds.max.x, transforms.x.scale),
+            y: Transform::fit(bounds.min.y, bounds.max.y, transforms.y.scale),
+            z: Transform::fit(bounds.min.z, bounds.max.z, transforms.z.scale),
+        };
+        builder.bounds = bounds;
+        builder.into_header()
+    }
+}
+
+impl Default for Affine {
+    fn default() -> Affine {
+        Affine::identity()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Read, Reader, Write, Writer};
+
+    #[test]
+    fn then_is_associative() {
+        let a = Affine::rotation_z(0.3);
+        let b = Affine::translation(1., 2., 3.);
+        let c = Affine::scaling(2., 3., 4.);
+        let vector = Vector {
+            x: 1.,
+            y: -1.,
+            z: 2.,
+        };
+        let left = a.then(&b).then(&c).transform(vector);
+        let right = a.then(&b.then(&c)).transform(vector);
+        let sequential = c.transform(b.transform(a.transform(vector)));
+        for other in &[right, sequential] {
+            assert!((left.x - other.x).abs() < 1e-12);
+            assert!((left.y - other.y).abs() < 1e-12);
+            assert!((left.z - other.z).abs() < 1e-12);
+        }
+    }
+
+    #[test]
+    fn far_translation_roundtrip() {
+        let mut reader = Reader::from_path("tests/data/autzen.las").unwrap();
+        let affine = Affine::translation(5e7, -5e7, 1e3);
+        let header = affine.transform_header(reader.header()).unwrap();
+        assert!(reader
+            .header()
+            .transforms()
+            .x
+            .inverse(header.bounds().max.x)
+            .is_err());
+        let mut writer = Writer::new(std::io::Cursor::new(Vec::new()), header).unwrap();
+        let mut expected = Vec::new();
+        for point in reader.points() {
+            let mut point = point.unwrap();
+            affine.transform_point(&mut point);
+            expected.push(point.clone());
+            writer.write(point).unwrap();
+        }
+        let mut reader = Reader::new(writer.into_inner().unwrap()).unwrap();
+        let scale = reader.header().transforms().x.scale;
+        for (point, expected) in reader.points().zip(expected) {
+            let point = point.unwrap();
+            assert!((point.x - expected.x).abs() <= scale);
+            assert!((point.y - expected.y).abs() <= scale);
+        }
+    }
+}
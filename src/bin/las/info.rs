@@ -0,0 +1,365 @@
+//! Summarize a las file.
+
+use byteorder::{LittleEndian, ReadBytesExt};
+use las::extra_bytes::{self, DataType};
+use las::stats::Stats;
+use las::{Header, Read, Reader, Vlr};
+use serde_json::{json, Map, Value};
+use std::error::Error;
+use std::io::Cursor;
+use std::path::PathBuf;
+
+const GEO_KEY_DIRECTORY_RECORD_ID: u16 = 34735;
+const GEO_DOUBLE_PARAMS_RECORD_ID: u16 = 34736;
+const GEO_ASCII_PARAMS_RECORD_ID: u16 = 34737;
+const WKT_RECORD_ID: u16 = 2112;
+const TEXT_AREA_DESCRIPTION_RECORD_ID: u16 = 3;
+
+/// Arguments to `las info`.
+#[derive(Debug, clap::Args)]
+pub struct Args {
+    /// The las or laz file to summarize.
+    path: PathBuf,
+
+    /// Read every point and report statistics for each attribute.
+    #[arg(long)]
+    stats: bool,
+
+    /// Print JSON instead of text.
+    #[arg(long)]
+    json: bool,
+}
+
+/// Runs `las info`.
+pub fn run(args: Args) -> Result<(), Box<dyn Error>> {
+    let mut reader = Reader::from_path(&args.path)?;
+    let mut info = Map::new();
+    info.insert("path".to_string(), json!(args.path.display().to_string()));
+    info.extend(header(reader.header()));
+    info.insert("vlrs".to_string(), vlrs(reader.header().vlrs()));
+    info.insert("evlrs".to_string(), vlrs(reader.header().evlrs()));
+    if args.stats {
+        let stats = Stats::from_reader(&mut reader)?;
+        info.insert("stats".to_string(), serde_json::to_value(stats)?);
+    }
+    let info = Value::Object(info);
+    if args.json {
+        println!("{}", serde_json::to_string_pretty(&info)?);
+    } else {
+        print_text(&info, 0);
+    }
+    Ok(())
+}
+
+fn header(header: &Header) -> Map<String, Value> {
+    let format = header.point_format();
+    let transforms = header.transforms();
+    let bounds = header.bounds();
+    let number_of_points_by_return = (1..=15)
+        .map(|n| header.number_of_points_by_return(n).unwrap_or(0))
+        .collect::<Vec<_>>();
+    let last = number_of_points_by_return
+        .iter()
+        .rposition(|&n| n > 0)
+        .map_or(0, |i| i + 1);
+    let value = json!({
+        "version": header.version().to_string(),
+        "point_format": format.to_u8().ok(),
+        "point_data_record_length": format.len(),
+        "extra_bytes": format.extra_bytes,
+        "is_compressed": format.is_compressed,
+        "number_of_points": header.number_of_points(),
+        "number_of_points_by_return": &number_of_points_by_return[..last],
+        "file_source_id": header.file_source_id(),
+        "gps_time_type": if header.gps_time_type().is_standard() { "standard" } else { "week" },
+        "has_synthetic_return_numbers": header.has_synthetic_return_numbers(),
+        "guid": header.guid().to_string(),
+        "system_identifier": header.system_identifier(),
+        "generating_software": header.generating_software(),
+        "date": header.date().map(|date| date.to_string()),
+        "bounds": {
+            "min": { "x": bounds.min.x, "y": bounds.min.y, "z": bounds.min.z },
+            "max": { "x": bounds.max.x, "y": bounds.max.y, "z": bounds.max.z },
+        },
+        "scale": {
+            "x": transforms.x.scale,
+            "y": transforms.y.scale,
+            "z": transforms.z.scale,
+        },
+        "offset": {
+            "x": transforms.x.offset,
+            "y": transforms.y.offset,
+            "z": transforms.z.offset,
+        },
+    });
+    match value {
+        Value::Object(map) => map,
+        _ => unreachable!(),
+    }
+}
+
+fn vlrs(vlrs: &[Vlr]) -> Value {
+    Value::Array(
+        vlrs.iter()
+            .map(|vlr| {
+                let mut map = Map::new();
+                map.insert("user_id".to_string(), json!(vlr.user_id));
+                map.insert("record_id".to_string(), json!(vlr.record_id));
+                map.insert("description".to_string(), json!(vlr.description));
+                map.insert("length".to_string(), json!(vlr.data.len()));
+                match decode(vlr, vlrs) {
+                    Ok(Some((kind, value))) => {
+                        map.insert("kind".to_string(), json!(kind));
+                        map.insert("content".to_string(), value);
+                    }
+                    Ok(None) => {}
+                    Err(err) => {
+                        map.insert("error".to_string(), json!(err.to_string()));
+                    }
+                }
+                Value::Object(map)
+            })
+            .collect(),
+    )
+}
+
+fn decode(vlr: &Vlr, vlrs: &[Vlr]) -> Result<Option<(&'static str, Value)>, Box<dyn Error>> {
+    let decoded = match (vlr.user_id.as_str(), vlr.record_id) {
+        ("LASF_Projection", GEO_KEY_DIRECTORY_RECORD_ID) => {
+            Some(("geotiff geo key directory", geo_key_directory(vlr, vlrs)?))
+        }
+        ("LASF_Projection", GEO_DOUBLE_PARAMS_RECORD_ID) => {
+            let mut cursor = Cursor::new(&vlr.data);
+            let mut params = Vec::new();
+            for _ in 0..vlr.data.len() / 8 {
+                params.push(cursor.read_f64::<LittleEndian>()?);
+            }
+            Some(("geotiff double params", json!(params)))
+        }
+        ("LASF_Projection", GEO_ASCII_PARAMS_RECORD_ID) => {
+            Some(("geotiff ascii params", json!(string(&vlr.data))))
+        }
+        ("LASF_Projection", WKT_RECORD_ID) | ("liblas", WKT_RECORD_ID) => {
+            Some(("wkt", json!(string(&vlr.data))))
+        }
+        (extra_bytes::USER_ID, extra_bytes::RECORD_ID) => Some(("extra bytes", extra_bytes(vlr)?)),
+        ("LASF_Spec", TEXT_AREA_DESCRIPTION_RECORD_ID) => {
+            Some(("text area description", json!(string(&vlr.data))))
+        }
+        _ if vlr.is_laszip() => Some(("laszip", laszip(vlr)?)),
+        _ => None,
+    };
+    Ok(decoded)
+}
+
+fn geo_key_directory(vlr: &Vlr, vlrs: &[Vlr]) -> Result<Value, Box<dyn Error>> {
+    let find = |record_id| {
+        vlrs.iter()
+            .find(|vlr| vlr.user_id == "LASF_Projection" && vlr.record_id == record_id)
+    };
+    let mut cursor = Cursor::new(&vlr.data);
+    let version = cursor.read_u16::<LittleEndian>()?;
+    let revision = cursor.read_u16::<LittleEndian>()?;
+    let minor_revision = cursor.read_u16::<LittleEndian>()?;
+    let number_of_keys = cursor.read_u16::<LittleEndian>()?;
+    let mut keys = Vec::new();
+    for _ in 0..number_of_keys {
+        let id = cursor.read_u16::<LittleEndian>()?;
+        let location = cursor.read_u16::<LittleEndian>()?;
+        let count = usize::from(cursor.read_u16::<LittleEndian>()?);
+        let offset = usize::from(cursor.read_u16::<LittleEndian>()?);
+        let value = match location {
+            0 => json!(offset),
+            GEO_DOUBLE_PARAMS_RECORD_ID => find(location)
+                .and_then(|vlr| {
+                    vlr.data
+                        .chunks_exact(8)
+                        .skip(offset)
+                        .take(count)
+                        .map(|bytes| bytes.try_into().ok().map(f64::from_le_bytes))
+                        .collect::<Option<Vec<_>>>()
+                })
+                .map_or(Value::Null, |params| json!(params)),
+            GEO_ASCII_PARAMS_RECORD_ID => find(location)
+                .and_then(|vlr| vlr.data.get(offset..offset + count))
+                .map_or(Value::Null, |bytes| {
+                    json!(string(bytes).trim_end_matches('|'))
+                }),
+            _ => Value::Null,
+        };
+        keys.push(json!({
+            "id": id,
+            "name": geo_key_name(id),
+            "value": value,
+        }));
+    }
+    Ok(json!({
+        "version": format!("{}.{}.{}", version, revision, minor_revision),
+        "keys": keys,
+    }))
+}
+
+fn geo_key_name(id: u16) -> Option<&'static str> {
+    let name = match id {
+        1024 => "GTModelTypeGeoKey",
+        1025 => "GTRasterTypeGeoKey",
+        1026 => "GTCitationGeoKey",
+        2048 => "GeographicTypeGeoKey",
+        2049 => "GeogCitationGeoKey",
+        2050 => "GeogGeodeticDatumGeoKey",
+        2052 => "GeogLinearUnitsGeoKey",
+        2054 => "GeogAngularUnitsGeoKey",
+        2056 => "GeogEllipsoidGeoKey",
+        3072 => "ProjectedCSTypeGeoKey",
+        3073 => "PCSCitationGeoKey",
+        3074 => "ProjectionGeoKey",
+        3075 => "ProjCoordTransGeoKey",
+        3076 => "ProjLinearUnitsGeoKey",
+        4096 => "VerticalCSTypeGeoKey",
+        4097 => "VerticalCitationGeoKey",
+        4098 => "VerticalDatumGeoKey",
+        4099 => "VerticalUnitsGeoKey",
+        _ => return None,
+    };
+    Some(name)
+}
+
+fn extra_bytes(vlr: &Vlr) -> Result<Value, Box<dyn Error>> {
+    let descriptors = extra_bytes::descriptors_from_vlr(vlr)?
+        .into_iter()
+        .map(|descriptor| {
+            let data_type = match descriptor.data_type {
+                DataType::Undocumented(_) => "undocumented".to_string(),
+                DataType::U8 => "u8".to_string(),
+                DataType::I8 => "i8".to_string(),
+                DataType::U16 => "u16".to_string(),
+                DataType::I16 => "i16".to_string(),
+                DataType::U32 => "u32".to_string(),
+                DataType::I32 => "i32".to_string(),
+                DataType::U64 => "u64".to_string(),
+                DataType::I64 => "i64".to_string(),
+                DataType::F32 => "f32".to_string(),
+                DataType::F64 => "f64".to_string(),
+                DataType::Deprecated(n) => format!("deprecated ({})", n),
+            };
+            let mut value = json!({
+                "name": descriptor.name,
+                "data_type": data_type,
+                "description": descriptor.description,
+            });
+            if let DataType::Undocumented(size) = descriptor.data_type {
+                value["size"] = json!(size);
+            }
+            if let Some(scale) = descriptor.scale {
+                value["scale"] = json!(scale);
+            }
+            if let Some(offset) = descriptor.offset {
+                value["offset"] = json!(offset);
+            }
+            value
+        })
+        .collect();
+    Ok(Value::Array(descriptors))
+}
+
+fn laszip(vlr: &Vlr) -> Result<Value, Box<dyn Error>> {
+    let mut cursor = Cursor::new(&vlr.data);
+    let compressor = match cursor.read_u16::<LittleEndian>()? {
+        0 => "none",
+        1 => "pointwise",
+        2 => "pointwise chunked",
+        3 => "layered chunked",
+        _ => "unknown",
+    };
+    let coder = cursor.read_u16::<LittleEndian>()?;
+    let major = cursor.read_u8()?;
+    let minor = cursor.read_u8()?;
+    let revision = cursor.read_u16::<LittleEndian>()?;
+    let options = cursor.read_u32::<LittleEndian>()?;
+    let chunk_size = cursor.read_u32::<LittleEndian>()?;
+    let _number_of_special_evlrs = cursor.read_i64::<LittleEndian>()?;
+    let _offset_to_special_evlrs = cursor.read_i64::<LittleEndian>()?;
+    let number_of_items = cursor.read_u16::<LittleEndian>()?;
+    let mut items = Vec::new();
+    for _ in 0..number_of_items {
+        let item_type = match cursor.read_u16::<LittleEndian>()? {
+            0 => "byte",
+            1 => "short",
+            2 => "integer",
+            3 => "long",
+            4 => "float",
+            5 => "double",
+            6 => "point10",
+            7 => "gps time 11",
+            8 => "rgb 12",
+            9 => "wavepacket 13",
+            10 => "point14",
+            11 => "rgb 14",
+            12 => "rgb nir 14",
+            13 => "wavepacket 14",
+            14 => "byte14",
+            _ => "unknown",
+        };
+        let size = cursor.read_u16::<LittleEndian>()?;
+        let version = cursor.read_u16::<LittleEndian>()?;
+        items.push(json!({ "type": item_type, "size": size, "version": version }));
+    }
+    Ok(json!({
+        "compressor": compressor,
+        "coder": coder,
+        "version": format!("{}.{}r{}", major, minor, revision),
+        "options": options,
+        "chunk_size": chunk_size,
+        "items": items,
+    }))
+}
+
+fn string(bytes: &[u8]) -> String {
+    String::from_utf8_lossy(bytes)
+        .trim_end_matches('\0')
+        .to_string()
+}
+
+fn print_text(value: &Value, indent: usize) {
+    match value {
+        Value::Object(map) => {
+            for (key, value) in map {
+                print_entry(key, value, indent);
+            }
+        }
+        Value::Array(values) => {
+            for (i, value) in values.iter().enumerate() {
+                print_entry(&format!("[{}]", i), value, indent);
+            }
+        }
+        _ => println!("{}{}", "  ".repeat(indent), scalar(value)),
+    }
+}
+
+fn print_entry(key: &str, value: &Value, indent: usize) {
+    let pad = "  ".repeat(indent);
+    let is_nested = match value {
+        Value::Object(map) => !map.is_empty(),
+        Value::Array(values) => values.iter().any(|v| v.is_object() || v.is_array()),
+        _ => false,
+    };
+    if is_nested {
+        println!("{}{}:", pad, key);
+        print_text(value, indent + 1);
+    } else {
+        println!("{}{}: {}", pad, key, scalar(value));
+    }
+}
+
+fn scalar(value: &Value) -> String {
+    match value {
+        Value::Null => "none".to_string(),
+        Value::String(s) => s.clone(),
+        Value::Array(values) => format!(
+            "[{}]",
+            values.iter().map(scalar).collect::<Vec<_>>().join(", ")
+        ),
+        Value::Object(_) => "{}".to_string(),
+        _ => value.to_string(),
+    }
+}
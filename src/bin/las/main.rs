@@ -0,0 +1,74 @@
+//! Command-line tools for las data.
+//!
+//! Built when the `cli` feature is enabled:
+//!
+//! ```text
+//! cargo install las --features cli
+//! las info tests/data/autzen.las
+//! ```
+
+#![deny(
+    missing_debug_implementations,
+    trivial_casts,
+    trivial_numeric_casts,
+    unsafe_code,
+    unused_import_braces,
+    unused_qualifications
+)]
+
+mod info;
+mod merge;
+mod split;
+mod translate;
+
+use clap::{Parser, Subcommand};
+use las::{Builder, Header};
+use std::error::Error;
+use std::process;
+
+/// Read, inspect, and convert las and laz point clouds.
+#[derive(Debug, Parser)]
+#[command(name = "las", version, about)]
+struct Cli {
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Debug, Subcommand)]
+enum Command {
+    /// Prints the header, the vlrs and evlrs, and optionally point statistics.
+    Info(info::Args),
+
+    /// Converts between las and laz, changes the version, point format, scale, and offset, and
+    /// filters points.
+    Translate(translate::Args),
+
+    /// Merges several files into one, checking that they are compatible.
+    Merge(merge::Args),
+
+    /// Splits a file by point count, by a tile grid, or by point source id.
+    Split(split::Args),
+}
+
+fn main() {
+    if let Err(err) = run(Cli::parse()) {
+        eprintln!("error: {}", err);
+        process::exit(1);
+    }
+}
+
+fn run(cli: Cli) -> Result<(), Box<dyn Error>> {
+    match cli.command {
+        Command::Info(args) => info::run(args),
+        Command::Translate(args) => translate::run(args),
+        Command::Merge(args) => merge::run(args),
+        Command::Split(args) => split::run(args),
+    }
+}
+
+/// Returns a builder for an output file, without the input's compression vlr.
+fn builder(header: Header) -> Builder {
+    let mut builder = Builder::from(header);
+    builder.vlrs.retain(|vlr| !vlr.is_laszip());
+    builder
+}
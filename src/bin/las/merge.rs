@@ -0,0 +1,42 @@
+//! Merge several las files into one.
+
+use las::{Read, Reader, Write, Writer};
+use std::error::Error;
+use std::path::PathBuf;
+
+/// Arguments to `las merge`.
+#[derive(Debug, clap::Args)]
+pub struct Args {
+    /// The las or laz files to merge.
+    #[arg(required = true)]
+    inputs: Vec<PathBuf>,
+
+    /// The merged file, compressed if its extension is `laz`.
+    #[arg(short, long)]
+    output: PathBuf,
+}
+
+/// Runs `las merge`.
+pub fn run(args: Args) -> Result<(), Box<dyn Error>> {
+    let mut readers = args
+        .inputs
+        .iter()
+        .map(Reader::from_path)
+        .collect::<las::Result<Vec<_>>>()?;
+    let mut header = readers[0].header().clone();
+    for (reader, path) in readers.iter().zip(&args.inputs).skip(1) {
+        header
+            .merge(reader.header())
+            .map_err(|err| format!("{}: {}", path.display(), err))?;
+    }
+    let mut builder = crate::builder(header);
+    builder.system_identifier = "MERGE".to_string();
+    let mut writer = Writer::from_path(&args.output, builder.into_header()?)?;
+    for reader in &mut readers {
+        for point in reader.points() {
+            writer.write(point?)?;
+        }
+    }
+    writer.close()?;
+    Ok(())
+}
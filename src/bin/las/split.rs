@@ -0,0 +1,107 @@
+//! Split a las file into several smaller ones.
+
+use clap::ArgGroup;
+use las::{Point, Read, Reader, TilingWriter, Write, Writer};
+use std::collections::HashMap;
+use std::error::Error;
+use std::fs::File;
+use std::io::BufWriter;
+use std::path::{Path, PathBuf};
+
+/// Arguments to `las split`.
+#[derive(Debug, clap::Args)]
+#[command(group(ArgGroup::new("by").required(true)))]
+pub struct Args {
+    /// The las or laz file to split.
+    input: PathBuf,
+
+    /// The directory for the output files, defaults to the input's directory.
+    #[arg(short, long)]
+    output_dir: Option<PathBuf>,
+
+    /// The extension of the output files (`las` or `laz`), defaults to the input's.
+    #[arg(long)]
+    extension: Option<String>,
+
+    /// Write at most this many points to each file.
+    #[arg(long, group = "by")]
+    points: Option<u64>,
+
+    /// Split into square tiles of this size, aligned to the origin and named by column and row.
+    #[arg(long, group = "by")]
+    tile_size: Option<f64>,
+
+    /// Write one file per point source id.
+    #[arg(long, group = "by")]
+    point_source_id: bool,
+}
+
+/// Runs `las split`.
+pub fn run(args: Args) -> Result<(), Box<dyn Error>> {
+    let mut reader = Reader::from_path(&args.input)?;
+    let stem = args
+        .input
+        .file_stem()
+        .ok_or("input has no file name")?
+        .to_string_lossy()
+        .into_owned();
+    let extension = args.extension.clone().unwrap_or_else(|| {
+        args.input
+            .extension()
+            .map_or("las".to_string(), |ext| ext.to_string_lossy().into_owned())
+    });
+    let dir = args.output_dir.clone().unwrap_or_else(|| {
+        args.input
+            .parent()
+            .map_or_else(PathBuf::new, Path::to_path_buf)
+    });
+    let mut builder = crate::builder(reader.header().clone());
+    let path = |name: String| dir.join(format!("{}_{}.{}", stem, name, extension));
+    if args.points == Some(0) {
+        return Err("--points must be greater than zero".into());
+    }
+    if let Some(tile_size) = args.tile_size {
+        builder.point_format.is_compressed = extension.eq_ignore_ascii_case("laz");
+        let mut writer = TilingWriter::new(&dir, builder.into_header()?, tile_size, (0., 0.))?;
+        writer.set_prefix(format!("{}_", stem));
+        for point in reader.points() {
+            writer.write(point?)?;
+        }
+        writer.close()?;
+        return Ok(());
+    }
+    let header = builder.into_header()?;
+    let mut writers: HashMap<String, Writer<BufWriter<File>>> = HashMap::new();
+    for (i, point) in reader.points().enumerate() {
+        let point = point?;
+        let name = key(&args, i as u64, &point);
+        if !writers.contains_key(&name) {
+            if args.points.is_some() {
+                close(&mut writers)?;
+            }
+            let writer = Writer::from_path(path(name.clone()), header.clone())?;
+            writers.insert(name.clone(), writer);
+        }
+        writers
+            .get_mut(&name)
+            .expect("writer was just inserted")
+            .write(point)?;
+    }
+    close(&mut writers)
+}
+
+/// Returns the part of the output file name that this point belongs to.
+fn key(args: &Args, i: u64, point: &Point) -> String {
+    if let Some(n) = args.points {
+        (i / n).to_string()
+    } else {
+        point.point_source_id.to_string()
+    }
+}
+
+fn close(writers: &mut HashMap<String, Writer<BufWriter<File>>>) -> Result<(), Box<dyn Error>> {
+    for (_, mut writer) in writers.drain() {
+        writer.close()?;
+    }
+    Ok(())
+}
@@ -0,0 +1,217 @@
+//! Convert a las file, optionally changing its layout and filtering its points.
+
+use las::point::{Classification, Format};
+use las::{Bounds, Filter, Point, Read, Reader, Vector, Version, Write, Writer};
+use std::error::Error;
+use std::path::PathBuf;
+
+/// Arguments to `las translate`.
+#[derive(Debug, clap::Args)]
+pub struct Args {
+    /// The input las or laz file.
+    input: PathBuf,
+
+    /// The output file, compressed if its extension is `laz`.
+    output: PathBuf,
+
+    /// The las version of the output file, e.g. `1.4`.
+    #[arg(long, value_parser = parse_version)]
+    las_version: Option<Version>,
+
+    /// The point format of the output file.
+    #[arg(long)]
+    point_format: Option<u8>,
+
+    /// The output scale factors, either one value for all axes or `x,y,z`.
+    #[arg(long, value_delimiter = ',', num_args = 1..=3)]
+    scale: Vec<f64>,
+
+    /// The output offsets, either one value for all axes or `x,y,z`.
+    #[arg(long, value_delimiter = ',', num_args = 1..=3, allow_negative_numbers = true)]
+    offset: Vec<f64>,
+
+    /// Only keep points with these classifications.
+    #[arg(long, value_delimiter = ',')]
+    keep_classification: Vec<u8>,
+
+    /// Drop points with these classifications.
+    #[arg(long, value_delimiter = ',')]
+    drop_classification: Vec<u8>,
+
+    /// Only keep first returns.
+    #[arg(long, conflicts_with = "last_only")]
+    first_only: bool,
+
+    /// Only keep last returns.
+    #[arg(long)]
+    last_only: bool,
+
+    /// Only keep points inside `minx,miny,maxx,maxy` or `minx,miny,minz,maxx,maxy,maxz`.
+    #[arg(long, value_parser = parse_bounds, allow_negative_numbers = true)]
+    bounds: Option<Bounds>,
+
+    /// Only keep every nth point (after the other filters).
+    #[arg(long, value_parser = clap::value_parser!(u64).range(1..))]
+    thin: Option<u64>,
+}
+
+/// Runs `las translate`.
+pub fn run(args: Args) -> Result<(), Box<dyn Error>> {
+    let mut reader = Reader::from_path(&args.input)?;
+    let mut builder = crate::builder(reader.header().clone());
+    if let Some(version) = args.las_version {
+        builder.version = version;
+    }
+    if let Some(n) = args.point_format {
+        let extra_bytes = builder.point_format.extra_bytes;
+        builder.point_format = Format::new(n)?;
+        builder.point_format.extra_bytes = extra_bytes;
+    }
+    if let Some(scale) = axes(&args.scale)? {
+        builder.transforms.x.scale = scale.x;
+        builder.transforms.y.scale = scale.y;
+        builder.transforms.z.scale = scale.z;
+    }
+    if let Some(offset) = axes(&args.offset)? {
+        builder.transforms.x.offset = offset.x;
+        builder.transforms.y.offset = offset.y;
+        builder.transforms.z.offset = offset.z;
+    }
+    let header = builder.into_header()?;
+    let format = *header.point_format();
+    let filter = filter(&args, &format)?;
+    let mut writer = Writer::from_path(&args.output, header)?;
+    for (i, point) in reader.filtered_points(filter).enumerate() {
+        let point = point?;
+        if args.thin.is_some_and(|n| !(i as u64).is_multiple_of(n)) {
+            continue;
+        }
+        writer.write(conform(point, &format))?;
+    }
+    writer.close()?;
+    Ok(())
+}
+
+/// Adds or removes optional attributes so the point can be written with this format.
+fn conform(mut point: Point, format: &Format) -> Point {
+    if format.has_gps_time {
+        point.gps_time = point.gps_time.or(Some(0.));
+    } else {
+        point.gps_time = None;
+    }
+    if format.has_color {
+        point.color = point.color.or_else(|| Some(Default::default()));
+    } else {
+        point.color = None;
+    }
+    if format.has_nir {
+        point.nir = point.nir.or(Some(0));
+    } else {
+        point.nir = None;
+    }
+    if format.has_waveform {
+        point.waveform = point.waveform.or_else(|| Some(Default::default()));
+    } else {
+        point.waveform = None;
+    }
+    if !format.is_extended {
+        point.scanner_channel = 0;
+    }
+    point.extra_bytes.resize(usize::from(format.extra_bytes), 0);
+    point
+}
+
+/// Combines the filtering arguments into one filter, reading classification codes for the output
+/// format.
+fn filter(args: &Args, format: &Format) -> Result<Filter, Box<dyn Error>> {
+    let mut filter = Filter::all();
+    if !args.keep_classification.is_empty() {
+        filter = filter.and(Filter::classification(classifications(
+            &args.keep_classification,
+            format,
+        )?));
+    }
+    if !args.drop_classification.is_empty() {
+        filter = filter.and(!Filter::classification(classifications(
+            &args.drop_classification,
+            format,
+        )?));
+    }
+    if args.first_only {
+        filter = filter.and(Filter::first_return());
+    }
+    if args.last_only {
+        filter = filter.and(Filter::last_return());
+    }
+    if let Some(bounds) = args.bounds {
+        filter = filter.and(Filter::bounds(bounds));
+    }
+    Ok(filter)
+}
+
+fn axes(values: &[f64]) -> Result<Option<Vector<f64>>, String> {
+    match *values {
+        [] => Ok(None),
+        [n] => Ok(Some(Vector { x: n, y: n, z: n })),
+        [x, y, z] => Ok(Some(Vector { x, y, z })),
+        _ => Err(format!(
+            "expected one or three values, got {}",
+            values.len()
+        )),
+    }
+}
+
+fn classifications(codes: &[u8], format: &Format) -> Result<Vec<Classification>, Box<dyn Error>> {
+    codes
+        .iter()
+        .map(|&n| Classification::for_format(n, format).map_err(Into::into))
+        .collect()
+}
+
+fn parse_version(s: &str) -> Result<Version, String> {
+    let (major, minor) = s
+        .split_once('.')
+        .ok_or_else(|| format!("expected a version like 1.4, got {}", s))?;
+    let major = major
+        .parse()
+        .map_err(|_| format!("invalid major version: {}", major))?;
+    let minor = minor
+        .parse()
+        .map_err(|_| format!("invalid minor version: {}", minor))?;
+    Ok(Version::new(major, minor))
+}
+
+fn parse_bounds(s: &str) -> Result<Bounds, String> {
+    let values = s
+        .split(',')
+        .map(|n| n.trim().parse::<f64>().map_err(|err| err.to_string()))
+        .collect::<Result<Vec<_>, _>>()?;
+    let (min, max) = match *values {
+        [minx, miny, maxx, maxy] => (
+            Vector {
+                x: minx,
+                y: miny,
+                z: f64::NEG_INFINITY,
+            },
+            Vector {
+                x: maxx,
+                y: maxy,
+                z: f64::INFINITY,
+            },
+        ),
+        [minx, miny, minz, maxx, maxy, maxz] => (
+            Vector {
+                x: minx,
+                y: miny,
+                z: minz,
+            },
+            Vector {
+                x: maxx,
+                y: maxy,
+                z: maxz,
+            },
+        ),
+        _ => return Err(format!("expected four or six values, got {}", values.len())),
+    };
+    Ok(Bounds { min, max })
+}
@@ -4,6 +4,7 @@ use crate::{Point, Result, Transform, Vector};
 
 /// Minimum and maximum bounds in three dimensions.
 #[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Bounds {
     /// The minimum values.
     pub min: Vector<f64>,
@@ -0,0 +1,461 @@
+//! Remap classification codes.
+//!
+//! A `ClassMap` is a 256-entry lookup table from one classification code to another, e.g. to
+//! convert a vendor's codes to the ASPRS ones. Maps can be parsed from text, with one `from to`
+//! pair per line, or, with the `serde` feature, deserialized from a map such as the JSON
+//! `{"7": 18, "64": 1}`. Codes that aren't listed keep their value.
+//!
+//! ```
+//! use las::class_map::ClassMap;
+//! use las::point::{Classification, Format};
+//! use las::Point;
+//!
+//! let mut class_map: ClassMap = "# noise\n7 18\n64 1".parse().unwrap();
+//! let mut point = Point { classification: Classification::LowPoint, ..Default::default() };
+//! class_map.apply(&mut point, &Format::new(6).unwrap()).unwrap();
+//! assert_eq!(Classification::HighNoise, point.classification);
+//! assert_eq!(1, class_map.count(7));
+//! ```
+//!
+//! Use a `ClassMapWriter` to remap points on their way into any writer.
+//!
+//! # Overlap points
+//!
+//! In the legacy point formats (0 through 5), overlap points are stored as classification 12, so
+//! a map applies to them as code 12: `12 1` turns them into unclassified points, and mapping
+//! another code to 12 makes those points overlap points. In the extended point formats the
+//! overlap bit is separate from the classification and isn't touched. Raw points are remapped by
+//! their stored code, so apply a map to raw two byte flags before
+//! `Flags::clear_overlap_class`.
+
+use crate::point::{Classification, Format};
+use crate::raw::point::Flags;
+use crate::{raw, Header, Point, Result, Write};
+use std::collections::BTreeMap;
+use std::str::FromStr;
+use thiserror::Error;
+
+const OVERLAP_CLASSIFICATION_CODE: u8 = 12;
+
+/// Class map errors.
+#[derive(Error, Clone, Debug)]
+pub enum Error {
+    /// A line of a text class map isn't a pair of codes.
+    #[error("invalid class map line {line}: {text}")]
+    #[allow(missing_docs)]
+    Line { line: usize, text: String },
+}
+
+/// A lookup table from classification codes to classification codes.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(from = "BTreeMap<u8, u8>", into = "BTreeMap<u8, u8>")
+)]
+pub struct ClassMap {
+    table: [u8; 256],
+    counts: [u64; 256],
+}
+
+impl ClassMap {
+    /// Creates a class map that leaves every code alone.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::class_map::ClassMap;
+    /// let class_map = ClassMap::new();
+    /// assert_eq!(2, class_map.get(2));
+    /// ```
+    pub fn new() -> ClassMap {
+        let mut table = [0; 256];
+        for (i, code) in table.iter_mut().enumerate() {
+            *code = i as u8;
+        }
+        ClassMap {
+            table,
+            counts: [0; 256],
+        }
+    }
+
+    /// Maps one code to another.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::class_map::ClassMap;
+    /// let mut class_map = ClassMap::new();
+    /// class_map.set(7, 18);
+    /// assert_eq!(18, class_map.get(7));
+    /// ```
+    pub fn set(&mut self, from: u8, to: u8) {
+        self.table[usize::from(from)] = to;
+    }
+
+    /// Returns the code that a code maps to.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::class_map::ClassMap;
+    /// assert_eq!(7, ClassMap::new().get(7));
+    /// ```
+    pub fn get(&self, from: u8) -> u8 {
+        self.table[usize::from(from)]
+    }
+
+    /// Returns the number of points that have been remapped from a code.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::class_map::ClassMap;
+    /// assert_eq!(0, ClassMap::new().count(7));
+    /// ```
+    pub fn count(&self, from: u8) -> u64 {
+        self.counts[usize::from(from)]
+    }
+
+    /// Returns the number of points that have been remapped from any code.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::class_map::ClassMap;
+    /// assert_eq!(0, ClassMap::new().total());
+    /// ```
+    pub fn total(&self) -> u64 {
+        self.counts.iter().sum()
+    }
+
+    /// Remaps a point's classification for a point format.
+    ///
+    /// Returns true if the classification was changed. Errors if the new code can't be stored in
+    /// the point format, e.g. a code above 31 in a legacy format.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::class_map::ClassMap;
+    /// use las::point::{Classification, Format};
+    /// use las::Point;
+    ///
+    /// let mut class_map = ClassMap::new();
+    /// class_map.set(12, 1);
+    /// let mut point = Point { is_overlap: true, classification: Classification::Unclassified, ..Default::default() };
+    /// assert!(class_map.apply(&mut point, &Format::new(1).unwrap()).unwrap());
+    /// assert!(!point.is_overlap);
+    /// ```
+    pub fn apply(&mut self, point: &mut Point, format: &Format) -> Result<bool> {
+        let from = if !format.is_extended && point.is_overlap {
+            OVERLAP_CLASSIFICATION_CODE
+        } else {
+            u8::from(point.classification)
+        };
+        let to = self.get(from);
+        if to == from {
+            return Ok(false);
+        }
+        if format.is_extended {
+            point.classification = Classification::extended(to);
+        } else if to == OVERLAP_CLASSIFICATION_CODE {
+            point.is_overlap = true;
+            point.classification = Classification::Unclassified;
+        } else {
+            point.classification = Classification::for_format(to, format)?;
+            point.is_overlap = false;
+        }
+        self.counts[usize::from(from)] += 1;
+        Ok(true)
+    }
+
+    /// Remaps a raw point's stored classification code.
+    ///
+    /// Returns true if the code was changed. Errors if two byte flags would need a code above 31.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::class_map::ClassMap;
+    /// use las::raw::point::Flags;
+    /// use las::raw;
+    ///
+    /// let mut class_map = ClassMap::new();
+    /// class_map.set(12, 1);
+    /// let mut point = raw::Point { flags: Flags::TwoByte(0, 0b1000_1100), ..Default::default() };
+    /// assert!(class_map.apply_raw(&mut point).unwrap());
+    /// assert_eq!(Flags::TwoByte(0, 0b1000_0001), point.flags);
+    /// ```
+    pub fn apply_raw(&mut self, point: &mut raw::Point) -> Result<bool> {
+        let (from, to) = match point.flags {
+            Flags::TwoByte(_, ref mut b) => {
+                let from = *b & 0b1_1111;
+                let to = self.get(from);
+                if to > 31 {
+                    return Err(crate::point::Error::Classification(to).into());
+                }
+                *b = (*b & 0b1110_0000) | to;
+                (from, to)
+            }
+            Flags::ThreeByte(_, _, ref mut c) => {
+                let from = *c;
+                *c = self.get(from);
+                (from, *c)
+            }
+        };
+        if to == from {
+            Ok(false)
+        } else {
+            self.counts[usize::from(from)] += 1;
+            Ok(true)
+        }
+    }
+}
+
+impl Default for ClassMap {
+    fn default() -> ClassMap {
+        ClassMap::new()
+    }
+}
+
+impl FromStr for ClassMap {
+    type Err = crate::Error;
+
+    /// Parses a class map from lines of `from to` pairs.
+    ///
+    /// The codes can be separated by whitespace, commas, or colons, and `#` starts a comment.
+    fn from_str(s: &str) -> Result<ClassMap> {
+        let mut class_map = ClassMap::new();
+        for (i, line) in s.lines().enumerate() {
+            let text = line.split('#').next().unwrap_or_default();
+            let codes = text
+                .split(|c: char| c.is_whitespace() || c == ',' || c == ':')
+                .filter(|word| !word.is_empty())
+                .map(str::parse::<u8>)
+                .collect::<std::result::Result<Vec<_>, _>>();
+            match codes.as_deref() {
+                Ok([]) => {}
+                Ok(&[from, to]) => class_map.set(from, to),
+                _ => {
+                    return Err(Error::Line {
+                        line: i + 1,
+                        text: line.to_string(),
+                    }
+                    .into())
+                }
+            }
+        }
+        Ok(class_map)
+    }
+}
+
+impl From<BTreeMap<u8, u8>> for ClassMap {
+    fn from(map: BTreeMap<u8, u8>) -> ClassMap {
+        let mut class_map = ClassMap::new();
+        for (from, to) in map {
+            class_map.set(from, to);
+        }
+        class_map
+    }
+}
+
+impl From<ClassMap> for BTreeMap<u8, u8> {
+    fn from(class_map: ClassMap) -> BTreeMap<u8, u8> {
+        (0..=255)
+            .filter(|&from| class_map.get(from) != from)
+            .map(|from| (from, class_map.get(from)))
+            .collect()
+    }
+}
+
+/// Remaps the classification of every point before passing it to another writer.
+///
+/// ```
+/// use las::class_map::{ClassMap, ClassMapWriter};
+/// use las::point::Classification;
+/// use las::{Point, Write, Writer};
+/// use std::io::Cursor;
+///
+/// let writer = Writer::new(Cursor::new(Vec::new()), Default::default()).unwrap();
+/// let mut writer = ClassMapWriter::new(writer, "1 2".parse().unwrap());
+/// writer.write(Point { classification: Classification::Unclassified, ..Default::default() }).unwrap();
+/// assert_eq!(1, writer.class_map().count(1));
+/// ```
+#[derive(Debug)]
+pub struct ClassMapWriter<W: Write> {
+    inner: W,
+    class_map: ClassMap,
+}
+
+impl<W: Write> ClassMapWriter<W> {
+    /// Wraps a writer.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::class_map::{ClassMap, ClassMapWriter};
+    /// use las::Writer;
+    /// use std::io::Cursor;
+    /// let writer = Writer::new(Cursor::new(Vec::new()), Default::default()).unwrap();
+    /// let writer = ClassMapWriter::new(writer, ClassMap::new());
+    /// ```
+    pub fn new(inner: W, class_map: ClassMap) -> ClassMapWriter<W> {
+        ClassMapWriter { inner, class_map }
+    }
+
+    /// Returns the class map, with its counts of remapped points.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::class_map::{ClassMap, ClassMapWriter};
+    /// use las::Writer;
+    /// use std::io::Cursor;
+    /// let writer = Writer::new(Cursor::new(Vec::new()), Default::default()).unwrap();
+    /// let writer = ClassMapWriter::new(writer, ClassMap::new());
+    /// assert_eq!(0, writer.class_map().total());
+    /// ```
+    pub fn class_map(&self) -> &ClassMap {
+        &self.class_map
+    }
+
+    /// Returns the wrapped writer and the class map.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::class_map::{ClassMap, ClassMapWriter};
+    /// use las::Writer;
+    /// use std::io::Cursor;
+    /// let writer = Writer::new(Cursor::new(Vec::new()), Default::default()).unwrap();
+    /// let (writer, class_map) = ClassMapWriter::new(writer, ClassMap::new()).into_inner();
+    /// ```
+    pub fn into_inner(self) -> (W, ClassMap) {
+        (self.inner, self.class_map)
+    }
+}
+
+impl<W: Write> Write for ClassMapWriter<W> {
+    fn header(&self) -> &Header {
+        self.inner.header()
+    }
+
+    fn write(&mut self, mut point: Point) -> Result<()> {
+        let format = *self.inner.header().point_format();
+        let _ = self.class_map.apply(&mut point, &format)?;
+        self.inner.write(point)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Read, Reader, Writer};
+    use std::io::Cursor;
+
+    #[test]
+    fn parse() {
+        let class_map: ClassMap = "1 2\n\n3,4 # comment\n5: 6\n".parse().unwrap();
+        assert_eq!(2, class_map.get(1));
+        assert_eq!(4, class_map.get(3));
+        assert_eq!(6, class_map.get(5));
+        assert_eq!(7, class_map.get(7));
+        assert!("1".parse::<ClassMap>().is_err());
+        assert!("1 256".parse::<ClassMap>().is_err());
+        assert!("1 2 3".parse::<ClassMap>().is_err());
+    }
+
+    #[test]
+    fn legacy_overlap() {
+        let format = Format::new(0).unwrap();
+        let mut class_map: ClassMap = "2 12".parse().unwrap();
+        let mut point = Point {
+            classification: Classification::Ground,
+            ..Default::default()
+        };
+        assert!(class_map.apply(&mut point, &format).unwrap());
+        assert!(point.is_overlap);
+        assert_eq!(Classification::Unclassified, point.classification);
+    }
+
+    #[test]
+    fn extended_overlap_bit_is_kept() {
+        let format = Format::new(6).unwrap();
+        let mut class_map: ClassMap = "2 12".parse().unwrap();
+        let mut point = Point {
+            classification: Classification::Ground,
+            is_overlap: true,
+            ..Default::default()
+        };
+        assert!(class_map.apply(&mut point, &format).unwrap());
+        assert!(point.is_overlap);
+        assert_eq!(Classification::OverlapPoints, point.classification);
+    }
+
+    #[test]
+    fn legacy_code_too_large() {
+        let mut class_map: ClassMap = "2 64".parse().unwrap();
+        let mut point = Point {
+            classification: Classification::Ground,
+            ..Default::default()
+        };
+        assert!(class_map
+            .apply(&mut point, &Format::new(1).unwrap())
+            .is_err());
+        let mut point = raw::Point {
+            flags: Flags::TwoByte(0, 2),
+            ..Default::default()
+        };
+        assert!(class_map.apply_raw(&mut point).is_err());
+    }
+
+    #[test]
+    fn writer() {
+        let mut reader = Reader::from_path("tests/data/autzen.las").unwrap();
+        let grounds = reader
+            .points()
+            .filter(|point| point.as_ref().unwrap().classification == Classification::Ground)
+            .count() as u64;
+        let mut class_map = ClassMap::new();
+        class_map.set(2, 40);
+        class_map.set(40, 2);
+
+        reader.seek(0).unwrap();
+        let writer = Writer::new(Cursor::new(Vec::new()), reader.header().clone()).unwrap();
+        let mut writer = ClassMapWriter::new(writer, class_map.clone());
+        assert!(writer
+            .write(reader.points().next().unwrap().unwrap())
+            .is_ok());
+        let ground = reader
+            .points()
+            .map(|point| point.unwrap())
+            .find(|point| point.classification == Classification::Ground)
+            .unwrap();
+        assert!(writer.write(ground).is_err());
+
+        let mut builder = crate::Builder::from((1, 4));
+        builder.point_format = Format::new(6).unwrap();
+        let writer = Writer::new(Cursor::new(Vec::new()), builder.into_header().unwrap()).unwrap();
+        let mut writer = ClassMapWriter::new(writer, class_map);
+        reader.seek(0).unwrap();
+        for point in reader.points() {
+            let mut point = point.unwrap();
+            point.gps_time = Some(point.gps_time.unwrap_or_default());
+            writer.write(point).unwrap();
+        }
+        assert_eq!(grounds, writer.class_map().count(2));
+        assert_eq!(grounds, writer.class_map().total());
+        let (writer, _) = writer.into_inner();
+        let mut reader = Reader::new(writer.into_inner().unwrap()).unwrap();
+        assert_eq!(
+            grounds as usize,
+            reader
+                .points()
+                .filter(
+                    |point| point.as_ref().unwrap().classification == Classification::Reserved(40)
+                )
+                .count()
+        );
+    }
+}
@@ -1,16 +1,12 @@
 use crate::error::Error;
 use laz::las::laszip::LazVlr;
-use crate::reader::{read_point_from, PointReader};
+use crate::reader::PointReader;
 use std::fmt::Debug;
 /// Module with functions and structs specific to brigde the las crate and laz crate to allow
 /// writing & reading LAZ data
 use std::io::{Cursor, Read, Seek, SeekFrom, Write};
 use crate::writer::{write_header_and_vlrs_to, write_point_to, PointWriter};
-use crate::{Header, Point, Result, Vlr};
-
-fn is_laszip_vlr(vlr: &Vlr) -> bool {
-    vlr.user_id == LazVlr::USER_ID && vlr.record_id == LazVlr::RECORD_ID
-}
+use crate::{raw, Header, Point, Result, Vlr};
 
 fn create_laszip_vlr(laszip_vlr: &LazVlr) -> std::io::Result<Vlr> {
     let mut cursor = Cursor::new(Vec::<u8>::new());
@@ -40,9 +36,9 @@ pub(crate) struct CompressedPointReader<'a, R: Read + Seek + Send> {
 
 impl<'a, R: Read + Seek + Send> CompressedPointReader<'a, R> {
     pub(crate) fn new(source: R, header: Header) -> Result<Self> {
-        let laszip_vlr = match header.vlrs().iter().find(|vlr| is_laszip_vlr(*vlr)) {
+        let laszip_vlr = match header.vlrs().iter().find(|vlr| vlr.is_laszip()) {
             None => return Err(Error::LasZipVlrNotFound),
-            Some(vlr) => laz::las::laszip::LazVlr::from_buffer(&vlr.data)?,
+            Some(vlr) => LazVlr::from_buffer(&vlr.data)?,
         };
         let decompressor_output = Cursor::new(vec![0u8; header.point_format().len() as usize]);
 
@@ -66,7 +62,7 @@ impl<'a, R: Read + Seek + Send> Debug for CompressedPointReader<'a, R> {
 }
 
 impl<'a, R: Read + Seek + Send> PointReader for CompressedPointReader<'a, R> {
-    fn read_next(&mut self) -> Option<Result<Point>> {
+    fn read_next_raw(&mut self) -> Option<Result<raw::Point>> {
         if self.last_point_idx < self.header.number_of_points() {
             self.last_point_idx += 1;
             let res = self.decompressor
@@ -76,7 +72,10 @@ impl<'a, R: Read + Seek + Send> PointReader for CompressedPointReader<'a, R> {
             } else if let Err(e) = self.decompressor_output.seek(SeekFrom::Start(0)) {
                 Some(Err(e.into()))
             } else {
-                Some(read_point_from(&mut self.decompressor_output, &self.header))
+                Some(raw::Point::read_from(
+                    &mut self.decompressor_output,
+                    self.header.point_format(),
+                ))
             }
         } else {
             None
@@ -125,7 +124,7 @@ fn laz_vlr_from_point_format(point_format: &crate::point::Format) -> LazVlr {
             laz_items.add_item(laz::LazItemType::Byte14(point_format.extra_bytes));
         }
     }
-    laz::LazVlr::from_laz_items(laz_items.build())
+    LazVlr::from_laz_items(laz_items.build())
 }
 
 /// struct that knows how to write LAZ
@@ -145,7 +144,7 @@ impl<'a, W: Write + Seek + Send> CompressedPointWriter<'a, W> {
     pub(crate) fn new(mut dest: W, mut header: Header) -> Result<Self> {
         let laz_vlr = laz_vlr_from_point_format(header.point_format());
         // Clear any existing laszip vlr as they might not be correct
-        header.vlrs_mut().retain(|vlr| !is_laszip_vlr(vlr));
+        header.vlrs_mut().retain(|vlr| !vlr.is_laszip());
         header.vlrs_mut().push(create_laszip_vlr(&laz_vlr)?);
 
         write_header_and_vlrs_to(&mut dest, &header)?;
@@ -171,6 +170,15 @@ impl<'a, W: Write + Seek + Send> PointWriter<W> for CompressedPointWriter<'a, W>
         Ok(())
     }
 
+    fn write_next_raw(&mut self, point: raw::Point) -> Result<()> {
+        self.header.add_raw_point(&point);
+        self.compressor_input.seek(SeekFrom::Start(0))?;
+        point.write_to(&mut self.compressor_input, self.header.point_format())?;
+        self.compressor
+            .compress_one(self.compressor_input.get_ref())?;
+        Ok(())
+    }
+
     fn into_inner(self: Box<Self>) -> W {
         self.compressor.into_inner()
     }
@@ -194,3 +202,14 @@ impl<'a, W: Write + Seek + Send> Debug for CompressedPointWriter<'a, W> {
         write!(f, "CompressedPointWriter(header: {:?})", self.header)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn laszip_vlr() {
+        let vlr = create_laszip_vlr(&laz_vlr_from_point_format(&Default::default())).unwrap();
+        assert!(vlr.is_laszip());
+    }
+}
@@ -0,0 +1,529 @@
+//! Thin point clouds as they stream.
+//!
+//! The `Decimate` trait adds decimating adapters to any iterator of point results, such as the one
+//! returned by `Read::points`:
+//!
+//! ```
+//! use las::decimate::{Decimate, Selection};
+//! use las::{Read, Reader};
+//!
+//! let mut reader = Reader::from_path("tests/data/autzen.las").unwrap();
+//! let points = reader
+//!     .points()
+//!     .every_nth(2)
+//!     .unwrap()
+//!     .voxel_grid(10., Selection::First)
+//!     .unwrap()
+//!     .collect::<las::Result<Vec<_>>>()
+//!     .unwrap();
+//! assert!(points.len() <= 53);
+//! ```
+//!
+//! Errors from the underlying iterator are passed through, and never count as points.
+
+use crate::{Point, Result, Vector};
+use std::collections::HashMap;
+use thiserror::Error;
+
+/// Decimation errors.
+#[derive(Error, Clone, Copy, Debug)]
+pub enum Error {
+    /// The step of `every_nth` must be at least one.
+    #[error("the decimation step must be at least one")]
+    Step,
+
+    /// The fraction of points to keep must be between zero and one.
+    #[error("invalid fraction: {0}")]
+    Fraction(f64),
+
+    /// Voxel sizes and poisson disk radii must be positive and finite.
+    #[error("invalid size: {0}")]
+    Size(f64),
+}
+
+/// Which point a voxel grid keeps from each cell.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Selection {
+    /// The first point in the cell.
+    First,
+
+    /// The point with the lowest z.
+    Lowest,
+
+    /// The point with the highest z.
+    Highest,
+
+    /// The point closest to the center of the cell.
+    Center,
+}
+
+/// Decimating adapters for iterators of point results.
+pub trait Decimate: Iterator<Item = Result<Point>> + Sized {
+    /// Keeps the first point and every nth point after it.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::decimate::Decimate;
+    /// use las::Point;
+    /// let points = (0..10).map(|_| Ok(Point::default()));
+    /// assert_eq!(4, points.every_nth(3).unwrap().count());
+    /// ```
+    fn every_nth(self, n: usize) -> Result<EveryNth<Self>> {
+        if n == 0 {
+            return Err(Error::Step.into());
+        }
+        Ok(EveryNth {
+            iter: self,
+            n,
+            index: 0,
+        })
+    }
+
+    /// Keeps each point with probability `fraction`.
+    ///
+    /// The same seed always keeps the same points.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::decimate::Decimate;
+    /// use las::Point;
+    /// let points = || (0..1000).map(|_| Ok(Point::default()));
+    /// let n = points().random(0.1, 42).unwrap().count();
+    /// assert!(n > 50 && n < 150);
+    /// assert_eq!(n, points().random(0.1, 42).unwrap().count());
+    /// assert!(points().random(1.5, 42).is_err());
+    /// ```
+    fn random(self, fraction: f64, seed: u64) -> Result<Random<Self>> {
+        if !(0. ..=1.).contains(&fraction) {
+            return Err(Error::Fraction(fraction).into());
+        }
+        Ok(Random {
+            iter: self,
+            fraction,
+            state: seed,
+        })
+    }
+
+    /// Keeps one point from each cubic cell of a grid aligned to the origin.
+    ///
+    /// `Selection::First` streams. The other selections have to see every point before they know
+    /// which to keep, so they consume the whole iterator on the first call to `next` and hold one
+    /// point per cell. Points are returned in the order their cells were first seen.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::decimate::{Decimate, Selection};
+    /// use las::Point;
+    /// let points = vec![
+    ///     Ok(Point { z: 2., ..Default::default() }),
+    ///     Ok(Point { z: 1., ..Default::default() }),
+    ///     Ok(Point { x: 5., ..Default::default() }),
+    /// ];
+    /// let points = points
+    ///     .into_iter()
+    ///     .voxel_grid(4., Selection::Lowest)
+    ///     .unwrap()
+    ///     .collect::<las::Result<Vec<_>>>()
+    ///     .unwrap();
+    /// assert_eq!(2, points.len());
+    /// assert_eq!(1., points[0].z);
+    /// ```
+    fn voxel_grid(self, size: f64, selection: Selection) -> Result<VoxelGrid<Self>> {
+        if !(size > 0. && size.is_finite()) {
+            return Err(Error::Size(size).into());
+        }
+        Ok(VoxelGrid {
+            iter: self,
+            size,
+            selection,
+            seen: HashMap::new(),
+            points: Vec::new(),
+            selected: None,
+        })
+    }
+
+    /// Keeps a point only if no kept point is within `radius` of it.
+    ///
+    /// This is greedy dart throwing in input order, so the result depends on the order of the
+    /// points, but it streams and only holds the kept points.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::decimate::Decimate;
+    /// use las::Point;
+    /// let points = (0..10).map(|i| Ok(Point { x: i as f64, ..Default::default() }));
+    /// assert_eq!(4, points.poisson_disk(2.5).unwrap().count());
+    /// ```
+    fn poisson_disk(self, radius: f64) -> Result<PoissonDisk<Self>> {
+        if !(radius > 0. && radius.is_finite()) {
+            return Err(Error::Size(radius).into());
+        }
+        Ok(PoissonDisk {
+            iter: self,
+            radius,
+            kept: HashMap::new(),
+        })
+    }
+}
+
+impl<I: Iterator<Item = Result<Point>>> Decimate for I {}
+
+/// Keeps every nth point.
+///
+/// This struct is created by `Decimate::every_nth`.
+#[derive(Debug)]
+pub struct EveryNth<I> {
+    iter: I,
+    n: usize,
+    index: usize,
+}
+
+impl<I: Iterator<Item = Result<Point>>> Iterator for EveryNth<I> {
+    type Item = Result<Point>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        for result in self.iter.by_ref() {
+            if result.is_ok() {
+                let index = self.index;
+                self.index += 1;
+                if !index.is_multiple_of(self.n) {
+                    continue;
+                }
+            }
+            return Some(result);
+        }
+        None
+    }
+}
+
+/// Keeps a seeded random sample of points.
+///
+/// This struct is created by `Decimate::random`.
+#[derive(Debug)]
+pub struct Random<I> {
+    iter: I,
+    fraction: f64,
+    state: u64,
+}
+
+impl<I> Random<I> {
+    /// SplitMix64, which is plenty for sampling and keeps us free of a rand dependency.
+    fn next_f64(&mut self) -> f64 {
+        self.state = self.state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^= z >> 31;
+        (z >> 11) as f64 / (1u64 << 53) as f64
+    }
+}
+
+impl<I: Iterator<Item = Result<Point>>> Iterator for Random<I> {
+    type Item = Result<Point>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while let Some(result) = self.iter.next() {
+            if result.is_err() || self.next_f64() < self.fraction {
+                return Some(result);
+            }
+        }
+        None
+    }
+}
+
+/// Keeps one point per voxel.
+///
+/// This struct is created by `Decimate::voxel_grid`.
+#[derive(Debug)]
+pub struct VoxelGrid<I> {
+    iter: I,
+    size: f64,
+    selection: Selection,
+    seen: HashMap<(i64, i64, i64), usize>,
+    points: Vec<Point>,
+    selected: Option<std::vec::IntoIter<Point>>,
+}
+
+impl<I> VoxelGrid<I> {
+    fn cell(&self, point: &Point) -> (i64, i64, i64) {
+        (
+            (point.x / self.size).floor() as i64,
+            (point.y / self.size).floor() as i64,
+            (point.z / self.size).floor() as i64,
+        )
+    }
+
+    fn is_better(&self, cell: (i64, i64, i64), point: &Point, current: &Point) -> bool {
+        match self.selection {
+            Selection::First => false,
+            Selection::Lowest => point.z < current.z,
+            Selection::Highest => point.z > current.z,
+            Selection::Center => {
+                let distance2 = |point: &Point| {
+                    let dx = point.x - (cell.0 as f64 + 0.5) * self.size;
+                    let dy = point.y - (cell.1 as f64 + 0.5) * self.size;
+                    let dz = point.z - (cell.2 as f64 + 0.5) * self.size;
+                    dx * dx + dy * dy + dz * dz
+                };
+                distance2(point) < distance2(current)
+            }
+        }
+    }
+}
+
+impl<I: Iterator<Item = Result<Point>>> Iterator for VoxelGrid<I> {
+    type Item = Result<Point>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.selection == Selection::First {
+            while let Some(result) = self.iter.next() {
+                match result {
+                    Ok(point) => {
+                        let cell = self.cell(&point);
+                        if self.seen.insert(cell, 0).is_none() {
+                            return Some(Ok(point));
+                        }
+                    }
+                    Err(err) => return Some(Err(err)),
+                }
+            }
+            return None;
+        }
+        if let Some(selected) = self.selected.as_mut() {
+            return selected.next().map(Ok);
+        }
+        while let Some(result) = self.iter.next() {
+            match result {
+                Ok(point) => {
+                    let cell = self.cell(&point);
+                    if let Some(&index) = self.seen.get(&cell) {
+                        if self.is_better(cell, &point, &self.points[index]) {
+                            self.points[index] = point;
+                        }
+                    } else {
+                        let _ = self.seen.insert(cell, self.points.len());
+                        self.points.push(point);
+                    }
+                }
+                Err(err) => return Some(Err(err)),
+            }
+        }
+        let mut selected = std::mem::take(&mut self.points).into_iter();
+        let next = selected.next().map(Ok);
+        self.selected = Some(selected);
+        next
+    }
+}
+
+/// Keeps points that are at least a radius apart.
+///
+/// This struct is created by `Decimate::poisson_disk`.
+#[derive(Debug)]
+pub struct PoissonDisk<I> {
+    iter: I,
+    radius: f64,
+    kept: HashMap<(i64, i64, i64), Vec<Vector<f64>>>,
+}
+
+impl<I> PoissonDisk<I> {
+    fn cell(&self, position: &Vector<f64>) -> (i64, i64, i64) {
+        (
+            (position.x / self.radius).floor() as i64,
+            (position.y / self.radius).floor() as i64,
+            (position.z / self.radius).floor() as i64,
+        )
+    }
+
+    fn is_far(&self, position: &Vector<f64>) -> bool {
+        let (x, y, z) = self.cell(position);
+        let radius2 = self.radius * self.radius;
+        for i in x - 1..=x + 1 {
+            for j in y - 1..=y + 1 {
+                for k in z - 1..=z + 1 {
+                    for other in self.kept.get(&(i, j, k)).into_iter().flatten() {
+                        let dx = position.x - other.x;
+                        let dy = position.y - other.y;
+                        let dz = position.z - other.z;
+                        if dx * dx + dy * dy + dz * dz < radius2 {
+                            return false;
+                        }
+                    }
+                }
+            }
+        }
+        true
+    }
+}
+
+impl<I: Iterator<Item = Result<Point>>> Iterator for PoissonDisk<I> {
+    type Item = Result<Point>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while let Some(result) = self.iter.next() {
+            match result {
+                Ok(point) => {
+                    let position = Vector {
+                        x: point.x,
+                        y: point.y,
+                        z: point.z,
+                    };
+                    if self.is_far(&position) {
+                        let cell = self.cell(&position);
+                        self.kept.entry(cell).or_default().push(position);
+                        return Some(Ok(point));
+                    }
+                }
+                Err(err) => return Some(Err(err)),
+            }
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Read, Reader};
+
+    fn autzen() -> Vec<Point> {
+        Reader::from_path("tests/data/autzen.las")
+            .unwrap()
+            .points()
+            .map(|point| point.unwrap())
+            .collect()
+    }
+
+    fn decimated<I: Iterator<Item = Result<Point>>>(iter: I) -> Vec<Point> {
+        iter.map(|point| point.unwrap()).collect()
+    }
+
+    #[test]
+    fn every_nth() {
+        let points = autzen();
+        let decimated = decimated(points.clone().into_iter().map(Ok).every_nth(10).unwrap());
+        assert_eq!(11, decimated.len());
+        assert_eq!(points[10], decimated[1]);
+        assert!(Vec::new().into_iter().every_nth(0).is_err());
+    }
+
+    #[test]
+    fn random_is_seeded() {
+        let points = autzen();
+        let sample = |seed| {
+            decimated(
+                points
+                    .clone()
+                    .into_iter()
+                    .map(Ok)
+                    .random(0.5, seed)
+                    .unwrap(),
+            )
+        };
+        assert_eq!(sample(1), sample(1));
+        assert_ne!(sample(1), sample(2));
+        assert!(decimated(points.clone().into_iter().map(Ok).random(0., 1).unwrap()).is_empty());
+        assert_eq!(
+            points.len(),
+            decimated(points.clone().into_iter().map(Ok).random(1., 1).unwrap()).len()
+        );
+    }
+
+    #[test]
+    fn voxel_grid_keeps_one_per_cell() {
+        let points = autzen();
+        let size = 50.;
+        let cells = |points: &[Point]| {
+            points
+                .iter()
+                .map(|point| {
+                    (
+                        (point.x / size).floor() as i64,
+                        (point.y / size).floor() as i64,
+                        (point.z / size).floor() as i64,
+                    )
+                })
+                .collect::<Vec<_>>()
+        };
+        let mut expected = cells(&points);
+        expected.sort();
+        expected.dedup();
+        for &selection in &[
+            Selection::First,
+            Selection::Lowest,
+            Selection::Highest,
+            Selection::Center,
+        ] {
+            let decimated = decimated(
+                points
+                    .clone()
+                    .into_iter()
+                    .map(Ok)
+                    .voxel_grid(size, selection)
+                    .unwrap(),
+            );
+            let mut actual = cells(&decimated);
+            actual.sort();
+            assert_eq!(expected, actual);
+        }
+    }
+
+    #[test]
+    fn voxel_grid_highest() {
+        let points = vec![
+            Ok(Point {
+                z: 1.,
+                ..Default::default()
+            }),
+            Ok(Point {
+                z: 3.,
+                ..Default::default()
+            }),
+            Ok(Point {
+                z: 2.,
+                ..Default::default()
+            }),
+        ];
+        let decimated = decimated(
+            points
+                .into_iter()
+                .voxel_grid(10., Selection::Highest)
+                .unwrap(),
+        );
+        assert_eq!(1, decimated.len());
+        assert_eq!(3., decimated[0].z);
+    }
+
+    #[test]
+    fn poisson_disk_spacing() {
+        let radius = 30.;
+        let decimated = decimated(autzen().into_iter().map(Ok).poisson_disk(radius).unwrap());
+        assert!(!decimated.is_empty());
+        for (i, a) in decimated.iter().enumerate() {
+            for b in &decimated[i + 1..] {
+                let distance =
+                    ((a.x - b.x).powi(2) + (a.y - b.y).powi(2) + (a.z - b.z).powi(2)).sqrt();
+                assert!(distance >= radius);
+            }
+        }
+    }
+
+    #[test]
+    fn errors_pass_through() {
+        let points = vec![
+            Ok(Point::default()),
+            Err(Error::Step.into()),
+            Ok(Point::default()),
+        ];
+        let results = points
+            .into_iter()
+            .voxel_grid(1., Selection::Center)
+            .unwrap()
+            .collect::<Vec<_>>();
+        assert!(results[0].is_err());
+        assert_eq!(2, results.len());
+    }
+}
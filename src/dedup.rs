@@ -0,0 +1,334 @@
+//! Find and remove duplicate points.
+//!
+//! Overlapping flight lines often deliver the same return twice. `Dedup` compares points by their
+//! integer coordinates, and optionally their gps time and return number, and either drops every
+//! copy after the first or marks the copies as withheld:
+//!
+//! ```
+//! use std::io::Cursor;
+//! use las::dedup::{Action, Dedup};
+//! use las::{Read, Reader, Writer};
+//!
+//! let mut reader = Reader::from_path("tests/data/autzen.las").unwrap();
+//! let mut writer = Writer::new(Cursor::new(Vec::new()), reader.header().clone()).unwrap();
+//! let duplicates = Dedup::new(Action::Drop).run(&mut reader, &mut writer).unwrap();
+//! assert_eq!(0, duplicates);
+//! ```
+//!
+//! Files with more than `max_points_in_memory` points are deduplicated with sorted runs in
+//! temporary files, so memory use stays bounded apart from one bit per point.
+
+use crate::{raw, Read, Result, Writer};
+use byteorder::{LittleEndian, ReadBytesExt, WriteBytesExt};
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashSet};
+use std::fmt::Debug;
+use std::fs::{self, File};
+use std::io::{BufReader, BufWriter, Seek};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// The default number of points whose keys are held in memory while deduplicating.
+pub const DEFAULT_MAX_POINTS_IN_MEMORY: usize = 10_000_000;
+
+static RUN_ID: AtomicUsize = AtomicUsize::new(0);
+
+type Key = (i32, i32, i32, u64, u8);
+
+/// What to do with duplicate points.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Action {
+    /// Leave duplicates out of the output.
+    Drop,
+
+    /// Write duplicates with their withheld flag set.
+    Withhold,
+}
+
+/// Removes or flags duplicate points.
+///
+/// The first point with a given key is always kept as is.
+#[derive(Clone, Debug)]
+pub struct Dedup {
+    /// What to do with duplicates.
+    pub action: Action,
+
+    /// Whether points must also have the same gps time to be duplicates.
+    pub gps_time: bool,
+
+    /// Whether points must also have the same return number to be duplicates.
+    pub return_number: bool,
+
+    /// The number of points whose keys are held in memory at once.
+    pub max_points_in_memory: usize,
+
+    /// The directory for temporary files.
+    pub temp_dir: PathBuf,
+}
+
+impl Dedup {
+    /// Creates a deduplicator that compares only integer xyz.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::dedup::{Action, Dedup};
+    /// let dedup = Dedup::new(Action::Withhold);
+    /// assert!(!dedup.gps_time);
+    /// ```
+    pub fn new(action: Action) -> Dedup {
+        Dedup {
+            action,
+            gps_time: false,
+            return_number: false,
+            max_points_in_memory: DEFAULT_MAX_POINTS_IN_MEMORY,
+            temp_dir: std::env::temp_dir(),
+        }
+    }
+
+    /// Reads every point from the reader, starting at the first, and writes them to the writer
+    /// with duplicates dropped or withheld.
+    ///
+    /// Returns the number of duplicates found.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::io::Cursor;
+    /// use las::dedup::{Action, Dedup};
+    /// use las::{Read, Reader, Writer};
+    ///
+    /// let mut reader = Reader::from_path("tests/data/autzen.las").unwrap();
+    /// let mut writer = Writer::new(Cursor::new(Vec::new()), reader.header().clone()).unwrap();
+    /// let dedup = Dedup { gps_time: true, ..Dedup::new(Action::Withhold) };
+    /// dedup.run(&mut reader, &mut writer).unwrap();
+    /// ```
+    pub fn run<R, W>(&self, reader: &mut R, writer: &mut Writer<W>) -> Result<u64>
+    where
+        R: Read,
+        W: 'static + std::io::Write + Seek + Debug + Send,
+    {
+        reader.seek(0)?;
+        if reader.header().number_of_points() > self.max_points_in_memory.max(1) as u64 {
+            let duplicates = self.find_duplicates(reader)?;
+            reader.seek(0)?;
+            let mut n = 0;
+            for (i, point) in reader.raw_points().enumerate() {
+                let is_duplicate = duplicates[i / 64] >> (i % 64) & 1 == 1;
+                if is_duplicate {
+                    n += 1;
+                }
+                self.write(writer, point?, is_duplicate)?;
+            }
+            Ok(n)
+        } else {
+            let mut seen = HashSet::new();
+            let mut n = 0;
+            for point in reader.raw_points() {
+                let point = point?;
+                let is_duplicate = !seen.insert(self.key(&point));
+                if is_duplicate {
+                    n += 1;
+                }
+                self.write(writer, point, is_duplicate)?;
+            }
+            Ok(n)
+        }
+    }
+
+    fn key(&self, point: &raw::Point) -> Key {
+        (
+            point.x,
+            point.y,
+            point.z,
+            if self.gps_time {
+                point.gps_time.unwrap_or(0.).to_bits()
+            } else {
+                0
+            },
+            if self.return_number {
+                point.flags.return_number()
+            } else {
+                0
+            },
+        )
+    }
+
+    fn write<W>(
+        &self,
+        writer: &mut Writer<W>,
+        mut point: raw::Point,
+        is_duplicate: bool,
+    ) -> Result<()>
+    where
+        W: 'static + std::io::Write + Seek + Debug + Send,
+    {
+        if is_duplicate {
+            match self.action {
+                Action::Drop => return Ok(()),
+                Action::Withhold => match point.flags {
+                    raw::point::Flags::TwoByte(_, ref mut b) => *b |= 0b1000_0000,
+                    raw::point::Flags::ThreeByte(_, ref mut b, _) => *b |= 0b100,
+                },
+            }
+        }
+        writer.write_raw(point)
+    }
+
+    /// Returns a bitset with a bit set for every point that repeats an earlier point's key.
+    fn find_duplicates<R: Read>(&self, reader: &mut R) -> Result<Vec<u64>> {
+        let max_points_in_memory = self.max_points_in_memory.max(1);
+        let mut runs = Vec::new();
+        let mut entries = Vec::new();
+        let mut count = 0;
+        let result = (|| {
+            for point in reader.raw_points() {
+                entries.push((self.key(&point?), count));
+                count += 1;
+                if entries.len() == max_points_in_memory {
+                    runs.push(self.spill(&mut entries)?);
+                }
+            }
+            if !entries.is_empty() {
+                runs.push(self.spill(&mut entries)?);
+            }
+            merge(&runs, count)
+        })();
+        for run in runs {
+            let _ = fs::remove_file(run);
+        }
+        result
+    }
+
+    fn spill(&self, entries: &mut Vec<(Key, u64)>) -> Result<PathBuf> {
+        entries.sort_unstable();
+        let path = self.temp_dir.join(format!(
+            "las-dedup-{}-{}.tmp",
+            std::process::id(),
+            RUN_ID.fetch_add(1, Ordering::Relaxed)
+        ));
+        let mut write = BufWriter::new(File::create(&path)?);
+        for ((x, y, z, gps_time, return_number), index) in entries.drain(..) {
+            write.write_i32::<LittleEndian>(x)?;
+            write.write_i32::<LittleEndian>(y)?;
+            write.write_i32::<LittleEndian>(z)?;
+            write.write_u64::<LittleEndian>(gps_time)?;
+            write.write_u8(return_number)?;
+            write.write_u64::<LittleEndian>(index)?;
+        }
+        std::io::Write::flush(&mut write)?;
+        Ok(path)
+    }
+}
+
+const ENTRY_LEN: u64 = 29;
+
+fn read_entry(read: &mut BufReader<File>) -> Result<(Key, u64)> {
+    let key = (
+        read.read_i32::<LittleEndian>()?,
+        read.read_i32::<LittleEndian>()?,
+        read.read_i32::<LittleEndian>()?,
+        read.read_u64::<LittleEndian>()?,
+        read.read_u8()?,
+    );
+    Ok((key, read.read_u64::<LittleEndian>()?))
+}
+
+fn merge(runs: &[PathBuf], count: u64) -> Result<Vec<u64>> {
+    let mut duplicates = vec![0u64; count.div_ceil(64) as usize];
+    let mut lens = Vec::with_capacity(runs.len());
+    let mut reads = Vec::with_capacity(runs.len());
+    for run in runs {
+        lens.push(fs::metadata(run)?.len() / ENTRY_LEN);
+        reads.push(BufReader::new(File::open(run)?));
+    }
+    let mut heap = BinaryHeap::new();
+    for (i, read) in reads.iter_mut().enumerate() {
+        if lens[i] > 0 {
+            lens[i] -= 1;
+            heap.push(Reverse((read_entry(read)?, i)));
+        }
+    }
+    let mut previous = None;
+    while let Some(Reverse(((key, index), i))) = heap.pop() {
+        if previous == Some(key) {
+            duplicates[(index / 64) as usize] |= 1 << (index % 64);
+        }
+        previous = Some(key);
+        if lens[i] > 0 {
+            lens[i] -= 1;
+            heap.push(Reverse((read_entry(&mut reads[i])?, i)));
+        }
+    }
+    Ok(duplicates)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Reader, Write};
+    use std::io::Cursor;
+
+    fn with_duplicates() -> Reader<'static> {
+        let mut reader = Reader::from_path("tests/data/autzen.las").unwrap();
+        let points = reader
+            .points()
+            .map(|point| point.unwrap())
+            .collect::<Vec<_>>();
+        let mut writer = Writer::new(Cursor::new(Vec::new()), reader.header().clone()).unwrap();
+        for point in points.iter().chain(&points[..10]) {
+            writer.write(point.clone()).unwrap();
+        }
+        let mut point = points[20].clone();
+        point.gps_time = point.gps_time.map(|gps_time| gps_time + 1.);
+        writer.write(point).unwrap();
+        Reader::new(writer.into_inner().unwrap()).unwrap()
+    }
+
+    fn dedup(dedup: Dedup) -> (u64, Vec<raw::Point>) {
+        let mut reader = with_duplicates();
+        let mut writer = Writer::new(Cursor::new(Vec::new()), reader.header().clone()).unwrap();
+        let n = dedup.run(&mut reader, &mut writer).unwrap();
+        let mut reader = Reader::new(writer.into_inner().unwrap()).unwrap();
+        let points = reader.raw_points().collect::<Result<Vec<_>>>().unwrap();
+        (n, points)
+    }
+
+    #[test]
+    fn drop() {
+        let (n, points) = dedup(Dedup::new(Action::Drop));
+        assert_eq!(11, n);
+        assert_eq!(106, points.len());
+    }
+
+    #[test]
+    fn gps_time() {
+        let (n, points) = dedup(Dedup {
+            gps_time: true,
+            ..Dedup::new(Action::Drop)
+        });
+        assert_eq!(10, n);
+        assert_eq!(107, points.len());
+    }
+
+    #[test]
+    fn withhold() {
+        let (n, points) = dedup(Dedup::new(Action::Withhold));
+        assert_eq!(11, n);
+        assert_eq!(117, points.len());
+        assert!(points[..106].iter().all(|point| !point.flags.is_withheld()));
+        assert!(points[106..].iter().all(|point| point.flags.is_withheld()));
+    }
+
+    #[test]
+    fn external_matches_in_memory() {
+        for &action in &[Action::Drop, Action::Withhold] {
+            let in_memory = dedup(Dedup::new(action));
+            let external = dedup(Dedup {
+                max_points_in_memory: 7,
+                ..Dedup::new(action)
+            });
+            assert_eq!(in_memory, external);
+        }
+    }
+}
@@ -0,0 +1,560 @@
+//! Point density and coverage grids.
+//!
+//! A `Grid` counts points in square cells, so you can check density requirements and find voids:
+//!
+//! ```
+//! use las::density::{Grid, Layer};
+//! use las::Reader;
+//!
+//! let mut reader = Reader::from_path("tests/data/autzen.las").unwrap();
+//! let grid = Grid::from_reader(&mut reader, 500.).unwrap();
+//! assert!(grid.void_count() > 0);
+//! let mut asc = Vec::new();
+//! grid.write_asc(&mut asc, Layer::Density).unwrap();
+//! ```
+
+use crate::{Bounds, Point, Read, Result};
+use std::io::Write;
+use thiserror::Error;
+
+/// The value written for void cells in ESRI ASCII grids.
+pub const NODATA_VALUE: f64 = -9999.;
+
+/// Density errors.
+#[derive(Error, Clone, Copy, Debug)]
+pub enum Error {
+    /// The cell size must be positive and finite.
+    #[error("invalid cell size: {0}")]
+    CellSize(f64),
+
+    /// The grid's bounds must be finite.
+    #[error("the grid bounds are not finite")]
+    Bounds,
+
+    /// The grid has too many cells to allocate.
+    #[error("too many cells: {columns} columns by {rows} rows")]
+    TooManyCells {
+        /// The number of columns.
+        columns: usize,
+
+        /// The number of rows.
+        rows: usize,
+    },
+}
+
+/// A value to export from a grid.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Layer {
+    /// The number of points in each cell.
+    Count,
+
+    /// Points per square unit.
+    Density,
+
+    /// First returns per square unit.
+    FirstReturnDensity,
+}
+
+/// A grid of point counts.
+///
+/// The grid covers the xy extent of some bounds, with its lower left corner at the minimum x and y.
+/// Cells are indexed by column from west to east and by row from south to north. The per-cell
+/// accessors return `None` for a column or row outside of the grid.
+///
+/// The examples on the methods use this two by two grid with one first return in it:
+///
+/// ```
+/// use las::density::Grid;
+/// use las::{Bounds, Point, Vector};
+/// let bounds = Bounds {
+///     min: Vector { x: 0., y: 0., z: 0. },
+///     max: Vector { x: 2., y: 2., z: 0. },
+/// };
+/// let mut grid = Grid::new(&bounds, 1.).unwrap();
+/// grid.add(&Point { return_number: 1, ..Default::default() });
+/// assert_eq!(Some(1), grid.count(0, 0));
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+pub struct Grid {
+    x: f64,
+    y: f64,
+    max_x: f64,
+    max_y: f64,
+    cell_size: f64,
+    columns: usize,
+    rows: usize,
+    counts: Vec<u64>,
+    first_return_counts: Vec<u64>,
+    outside_count: u64,
+}
+
+impl Grid {
+    /// Creates an empty grid that covers the bounds.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::density::Grid;
+    /// use las::{Bounds, Vector};
+    /// let bounds = Bounds {
+    ///     min: Vector { x: 0., y: 0., z: 0. },
+    ///     max: Vector { x: 10., y: 5., z: 0. },
+    /// };
+    /// let grid = Grid::new(&bounds, 2.).unwrap();
+    /// assert_eq!((5, 3), (grid.columns(), grid.rows()));
+    /// assert!(Grid::new(&bounds, 0.).is_err());
+    /// assert!(Grid::new(&Bounds::default(), 2.).is_err());
+    /// assert!(Grid::new(&bounds, 1e-12).is_err());
+    /// ```
+    pub fn new(bounds: &Bounds, cell_size: f64) -> Result<Grid> {
+        if !(cell_size > 0. && cell_size.is_finite()) {
+            return Err(Error::CellSize(cell_size).into());
+        }
+        let is_finite = |n: f64| n.is_finite();
+        if ![bounds.min.x, bounds.min.y, bounds.max.x, bounds.max.y]
+            .iter()
+            .all(|&n| is_finite(n))
+            || bounds.min.x > bounds.max.x
+            || bounds.min.y > bounds.max.y
+        {
+            return Err(Error::Bounds.into());
+        }
+        let extent = |min: f64, max: f64| (((max - min) / cell_size).ceil() as usize).max(1);
+        let columns = extent(bounds.min.x, bounds.max.x);
+        let rows = extent(bounds.min.y, bounds.max.y);
+        let too_many_cells = || Error::TooManyCells { columns, rows };
+        let cells = columns.checked_mul(rows).ok_or_else(too_many_cells)?;
+        let zeros = || {
+            let mut zeros = Vec::new();
+            zeros
+                .try_reserve_exact(cells)
+                .map_err(|_| too_many_cells())?;
+            zeros.resize(cells, 0);
+            Ok::<_, Error>(zeros)
+        };
+        Ok(Grid {
+            x: bounds.min.x,
+            y: bounds.min.y,
+            max_x: bounds.max.x,
+            max_y: bounds.max.y,
+            cell_size,
+            columns,
+            rows,
+            counts: zeros()?,
+            first_return_counts: zeros()?,
+            outside_count: 0,
+        })
+    }
+
+    /// Grids the remaining points in a reader, covering the bounds in its header.
+    ///
+    /// Points outside of the header's bounds aren't gridded, but they're counted by
+    /// `outside_count`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::density::Grid;
+    /// use las::Reader;
+    /// let mut reader = Reader::from_path("tests/data/autzen.las").unwrap();
+    /// let grid = Grid::from_reader(&mut reader, 100.).unwrap();
+    /// assert_eq!(106, grid.total());
+    /// ```
+    pub fn from_reader<R: Read>(reader: &mut R, cell_size: f64) -> Result<Grid> {
+        let mut grid = Grid::new(&reader.header().bounds(), cell_size)?;
+        for point in reader.points() {
+            grid.add(&point?);
+        }
+        Ok(grid)
+    }
+
+    /// Adds a point, returning false if it's outside of the grid.
+    ///
+    /// Points outside of the grid are counted by `outside_count`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::density::Grid;
+    /// use las::{Bounds, Point, Vector};
+    /// let bounds = Bounds {
+    ///     min: Vector { x: 0., y: 0., z: 0. },
+    ///     max: Vector { x: 10., y: 10., z: 0. },
+    /// };
+    /// let mut grid = Grid::new(&bounds, 5.).unwrap();
+    /// assert!(grid.add(&Point { x: 1., y: 6., return_number: 1, ..Default::default() }));
+    /// assert!(!grid.add(&Point { x: -1., ..Default::default() }));
+    /// assert_eq!(Some(1), grid.first_return_count(0, 1));
+    /// assert_eq!(1, grid.outside_count());
+    /// ```
+    pub fn add(&mut self, point: &Point) -> bool {
+        if !(self.x <= point.x
+            && point.x <= self.max_x
+            && self.y <= point.y
+            && point.y <= self.max_y)
+        {
+            self.outside_count += 1;
+            return false;
+        }
+        let column = ((point.x - self.x) / self.cell_size) as usize;
+        let row = ((point.y - self.y) / self.cell_size) as usize;
+        let index = row.min(self.rows - 1) * self.columns + column.min(self.columns - 1);
+        self.counts[index] += 1;
+        if point.return_number <= 1 {
+            self.first_return_counts[index] += 1;
+        }
+        true
+    }
+
+    /// Returns the number of columns.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use las::{density::Grid, Bounds, Vector};
+    /// # let grid = Grid::new(&Bounds { min: Vector::default(), max: Vector { x: 2., y: 2., z: 0. } }, 1.).unwrap();
+    /// assert_eq!(2, grid.columns());
+    /// ```
+    pub fn columns(&self) -> usize {
+        self.columns
+    }
+
+    /// Returns the number of rows.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use las::{density::Grid, Bounds, Vector};
+    /// # let grid = Grid::new(&Bounds { min: Vector::default(), max: Vector { x: 2., y: 2., z: 0. } }, 1.).unwrap();
+    /// assert_eq!(2, grid.rows());
+    /// ```
+    pub fn rows(&self) -> usize {
+        self.rows
+    }
+
+    /// Returns the cell size.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use las::{density::Grid, Bounds, Vector};
+    /// # let grid = Grid::new(&Bounds { min: Vector::default(), max: Vector { x: 2., y: 2., z: 0. } }, 1.).unwrap();
+    /// assert_eq!(1., grid.cell_size());
+    /// ```
+    pub fn cell_size(&self) -> f64 {
+        self.cell_size
+    }
+
+    /// Returns the number of points in a cell, or `None` if the cell is outside of the grid.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use las::{density::Grid, Bounds, Point, Vector};
+    /// # let mut grid = Grid::new(&Bounds { min: Vector::default(), max: Vector { x: 2., y: 2., z: 0. } }, 1.).unwrap();
+    /// # grid.add(&Point { return_number: 1, ..Default::default() });
+    /// assert_eq!(Some(1), grid.count(0, 0));
+    /// assert_eq!(None, grid.count(2, 0));
+    /// ```
+    pub fn count(&self, column: usize, row: usize) -> Option<u64> {
+        self.index(column, row).map(|index| self.counts[index])
+    }
+
+    /// Returns the number of first returns in a cell, or `None` if the cell is outside of the grid.
+    ///
+    /// First returns are the points that `Filter::first_return` matches, so a return number of zero
+    /// counts too.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use las::{density::Grid, Bounds, Point, Vector};
+    /// # let mut grid = Grid::new(&Bounds { min: Vector::default(), max: Vector { x: 2., y: 2., z: 0. } }, 1.).unwrap();
+    /// # grid.add(&Point { return_number: 1, ..Default::default() });
+    /// assert_eq!(Some(1), grid.first_return_count(0, 0));
+    /// ```
+    pub fn first_return_count(&self, column: usize, row: usize) -> Option<u64> {
+        self.index(column, row)
+            .map(|index| self.first_return_counts[index])
+    }
+
+    /// Returns the points per square unit in a cell, or `None` if the cell is outside of the grid.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use las::{density::Grid, Bounds, Point, Vector};
+    /// # let mut grid = Grid::new(&Bounds { min: Vector::default(), max: Vector { x: 2., y: 2., z: 0. } }, 1.).unwrap();
+    /// # grid.add(&Point { return_number: 1, ..Default::default() });
+    /// assert_eq!(Some(1.), grid.density(0, 0));
+    /// ```
+    pub fn density(&self, column: usize, row: usize) -> Option<f64> {
+        self.count(column, row)
+            .map(|count| count as f64 / self.cell_area())
+    }
+
+    /// Returns the first returns per square unit in a cell, or `None` if the cell is outside of
+    /// the grid.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use las::{density::Grid, Bounds, Point, Vector};
+    /// # let mut grid = Grid::new(&Bounds { min: Vector::default(), max: Vector { x: 2., y: 2., z: 0. } }, 1.).unwrap();
+    /// # grid.add(&Point { return_number: 1, ..Default::default() });
+    /// assert_eq!(Some(0.), grid.first_return_density(1, 1));
+    /// ```
+    pub fn first_return_density(&self, column: usize, row: usize) -> Option<f64> {
+        self.first_return_count(column, row)
+            .map(|count| count as f64 / self.cell_area())
+    }
+
+    /// Returns the number of points in the grid.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use las::{density::Grid, Bounds, Point, Vector};
+    /// # let mut grid = Grid::new(&Bounds { min: Vector::default(), max: Vector { x: 2., y: 2., z: 0. } }, 1.).unwrap();
+    /// # grid.add(&Point { return_number: 1, ..Default::default() });
+    /// assert_eq!(1, grid.total());
+    /// ```
+    pub fn total(&self) -> u64 {
+        self.counts.iter().sum()
+    }
+
+    /// Returns the number of points that were outside of the grid.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use las::{density::Grid, Bounds, Point, Vector};
+    /// # let mut grid = Grid::new(&Bounds { min: Vector::default(), max: Vector { x: 2., y: 2., z: 0. } }, 1.).unwrap();
+    /// # grid.add(&Point { return_number: 1, ..Default::default() });
+    /// assert_eq!(0, grid.outside_count());
+    /// ```
+    pub fn outside_count(&self) -> u64 {
+        self.outside_count
+    }
+
+    /// Returns the column and row of every cell without points.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use las::{density::Grid, Bounds, Point, Vector};
+    /// # let mut grid = Grid::new(&Bounds { min: Vector::default(), max: Vector { x: 2., y: 2., z: 0. } }, 1.).unwrap();
+    /// # grid.add(&Point { return_number: 1, ..Default::default() });
+    /// assert_eq!(vec![(1, 0), (0, 1), (1, 1)], grid.voids());
+    /// ```
+    pub fn voids(&self) -> Vec<(usize, usize)> {
+        (0..self.rows)
+            .flat_map(|row| (0..self.columns).map(move |column| (column, row)))
+            .filter(|&(column, row)| self.count(column, row) == Some(0))
+            .collect()
+    }
+
+    /// Returns the number of cells without points.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use las::{density::Grid, Bounds, Point, Vector};
+    /// # let mut grid = Grid::new(&Bounds { min: Vector::default(), max: Vector { x: 2., y: 2., z: 0. } }, 1.).unwrap();
+    /// # grid.add(&Point { return_number: 1, ..Default::default() });
+    /// assert_eq!(3, grid.void_count());
+    /// ```
+    pub fn void_count(&self) -> usize {
+        self.counts.iter().filter(|&&count| count == 0).count()
+    }
+
+    /// Returns the mean density of the cells that have points, or `None` if every cell is a void.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use las::{density::Grid, Bounds, Point, Vector};
+    /// # let mut grid = Grid::new(&Bounds { min: Vector::default(), max: Vector { x: 2., y: 2., z: 0. } }, 1.).unwrap();
+    /// # grid.add(&Point { return_number: 1, ..Default::default() });
+    /// assert_eq!(Some(1.), grid.mean_density());
+    /// ```
+    pub fn mean_density(&self) -> Option<f64> {
+        let covered = self.counts.len() - self.void_count();
+        if covered == 0 {
+            None
+        } else {
+            Some(self.total() as f64 / (covered as f64 * self.cell_area()))
+        }
+    }
+
+    /// Writes a layer of the grid as an ESRI ASCII grid.
+    ///
+    /// Void cells are written as `NODATA_VALUE`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use las::{density::Grid, Bounds, Vector};
+    /// # let grid = Grid::new(&Bounds { min: Vector::default(), max: Vector { x: 2., y: 2., z: 0. } }, 1.).unwrap();
+    /// let mut asc = Vec::new();
+    /// grid.write_asc(&mut asc, las::density::Layer::Count).unwrap();
+    /// assert!(String::from_utf8(asc).unwrap().starts_with("ncols 2\n"));
+    /// ```
+    pub fn write_asc<W: Write>(&self, mut write: W, layer: Layer) -> Result<()> {
+        writeln!(write, "ncols {}", self.columns)?;
+        writeln!(write, "nrows {}", self.rows)?;
+        writeln!(write, "xllcorner {}", self.x)?;
+        writeln!(write, "yllcorner {}", self.y)?;
+        writeln!(write, "cellsize {}", self.cell_size)?;
+        writeln!(write, "NODATA_value {}", NODATA_VALUE)?;
+        for row in (0..self.rows).rev() {
+            let values = (0..self.columns)
+                .map(|column| {
+                    let index = row * self.columns + column;
+                    let count = self.counts[index];
+                    if count == 0 {
+                        NODATA_VALUE.to_string()
+                    } else {
+                        match layer {
+                            Layer::Count => count.to_string(),
+                            Layer::Density => (count as f64 / self.cell_area()).to_string(),
+                            Layer::FirstReturnDensity => (self.first_return_counts[index] as f64
+                                / self.cell_area())
+                            .to_string(),
+                        }
+                    }
+                })
+                .collect::<Vec<_>>();
+            writeln!(write, "{}", values.join(" "))?;
+        }
+        Ok(())
+    }
+
+    fn index(&self, column: usize, row: usize) -> Option<usize> {
+        if column < self.columns && row < self.rows {
+            Some(row * self.columns + column)
+        } else {
+            None
+        }
+    }
+
+    fn cell_area(&self) -> f64 {
+        self.cell_size * self.cell_size
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Reader, Vector};
+
+    #[test]
+    fn autzen() {
+        let mut reader = Reader::from_path("tests/data/autzen.las").unwrap();
+        let grid = Grid::from_reader(&mut reader, 250.).unwrap();
+        assert_eq!(106, grid.total());
+        let first_returns = (0..grid.rows())
+            .flat_map(|row| (0..grid.columns()).map(move |column| (column, row)))
+            .filter_map(|(column, row)| grid.first_return_count(column, row))
+            .sum::<u64>();
+        assert_eq!(
+            reader.header().number_of_points_by_return(1).unwrap(),
+            first_returns
+        );
+        assert_eq!(grid.void_count(), grid.voids().len());
+        assert!(grid.mean_density().unwrap() > 0.);
+    }
+
+    #[test]
+    fn max_edge_is_inside() {
+        let bounds = Bounds {
+            min: Vector {
+                x: 0.,
+                y: 0.,
+                z: 0.,
+            },
+            max: Vector {
+                x: 10.,
+                y: 10.,
+                z: 0.,
+            },
+        };
+        let mut grid = Grid::new(&bounds, 5.).unwrap();
+        assert!(grid.add(&Point {
+            x: 10.,
+            y: 10.,
+            ..Default::default()
+        }));
+        assert_eq!(Some(1), grid.count(1, 1));
+    }
+
+    #[test]
+    fn too_many_cells() {
+        let bounds = Bounds {
+            min: Vector::default(),
+            max: Vector {
+                x: 1e10,
+                y: 1e10,
+                z: 0.,
+            },
+        };
+        assert!(Grid::new(&bounds, 1e-3).is_err());
+    }
+
+    #[test]
+    fn first_returns_match_filter() {
+        let bounds = Bounds {
+            min: Vector::default(),
+            max: Vector {
+                x: 1.,
+                y: 1.,
+                z: 0.,
+            },
+        };
+        let mut grid = Grid::new(&bounds, 1.).unwrap();
+        let filter = crate::Filter::first_return();
+        let mut first_returns = 0;
+        for return_number in 0..3 {
+            let point = Point {
+                return_number,
+                number_of_returns: 2,
+                ..Default::default()
+            };
+            if filter.matches(&point) {
+                first_returns += 1;
+            }
+            assert!(grid.add(&point));
+        }
+        assert_eq!(2, first_returns);
+        assert_eq!(Some(first_returns), grid.first_return_count(0, 0));
+    }
+
+    #[test]
+    fn asc() {
+        let bounds = Bounds {
+            min: Vector {
+                x: 100.,
+                y: 200.,
+                z: 0.,
+            },
+            max: Vector {
+                x: 103.,
+                y: 201.,
+                z: 0.,
+            },
+        };
+        let mut grid = Grid::new(&bounds, 2.).unwrap();
+        grid.add(&Point {
+            x: 100.5,
+            y: 200.5,
+            return_number: 1,
+            ..Default::default()
+        });
+        let mut asc = Vec::new();
+        grid.write_asc(&mut asc, Layer::Density).unwrap();
+        assert_eq!(
+            "ncols 2\nnrows 1\nxllcorner 100\nyllcorner 200\ncellsize 2\nNODATA_value -9999\n0.25 -9999\n",
+            String::from_utf8(asc).unwrap()
+        );
+    }
+}
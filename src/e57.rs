@@ -0,0 +1,1077 @@
+//! Read points from ASTM E57 files.
+//!
+//! E57 files hold an XML section that describes one or more scans, and a binary CompressedVector
+//! section per scan with the point records. `E57Reader` reads the XML when it's created, then
+//! streams each scan's records as `Point`s with the scan's pose applied:
+//!
+//! - `cartesianX`, `cartesianY`, and `cartesianZ`, or `sphericalRange`, `sphericalAzimuth`, and
+//!   `sphericalElevation`, become the coordinates. Records marked invalid by
+//!   `cartesianInvalidState` or `sphericalInvalidState` are skipped.
+//! - `intensity` is scaled to the `u16` range using the scan's intensity limits.
+//! - `colorRed`, `colorGreen`, and `colorBlue` are scaled to the `u16` range using the scan's color
+//!   limits.
+//! - `timeStamp` is relative to the scan's `acquisitionStart`, so the two are added and stored as
+//!   adjusted standard gps time. If a scan with timestamps has no acquisition start, the
+//!   timestamps of every scan are kept as they are, since they can't be put on one clock.
+//! - `returnIndex` and `returnCount` become the return number and number of returns.
+//! - Each scan's index in the file becomes the `point_source_id`.
+//!
+//! Other fields are ignored, and page checksums aren't verified. This module requires the `e57`
+//! feature.
+//!
+//! ```no_run
+//! use las::e57::E57Reader;
+//! use las::{Write, Writer};
+//!
+//! let reader = E57Reader::from_path("scans.e57").unwrap();
+//! let (header, points) = reader.read_to_end(0.001).unwrap();
+//! let mut writer = Writer::from_path("scans.las", header).unwrap();
+//! for point in points {
+//!     writer.write(point).unwrap();
+//! }
+//! ```
+
+use crate::gps_time::ADJUSTED_STANDARD_OFFSET;
+use crate::point::Format;
+use crate::{Affine, Bounds, Builder, Color, GpsTimeType, Header, Point, Result, Vector};
+use byteorder::{ByteOrder, LittleEndian};
+use roxmltree::{Document, Node};
+use std::fs::File;
+use std::io::{BufReader, Read, Seek, SeekFrom};
+use std::path::Path;
+use thiserror::Error;
+
+const SIGNATURE: &[u8; 8] = b"ASTM-E57";
+const PAGE_SIZE: u64 = 1024;
+const PAGE_DATA_SIZE: u64 = 1020;
+const FILE_HEADER_SIZE: usize = 48;
+const SECTION_HEADER_SIZE: u64 = 32;
+
+/// E57 errors.
+#[derive(Error, Debug)]
+pub enum Error {
+    /// The file doesn't start with the E57 signature.
+    #[error("the data are not e57")]
+    Signature,
+
+    /// The file's major version isn't supported.
+    #[error("unsupported e57 version: {0}.{1}")]
+    Version(u32, u32),
+
+    /// The XML section couldn't be parsed.
+    #[error("invalid e57 xml: {0}")]
+    Xml(String),
+
+    /// A required XML element is missing or invalid.
+    #[error("missing or invalid e57 xml element: {0}")]
+    Element(String),
+
+    /// A prototype field has a type that can't be read.
+    #[error("unsupported e57 field type: {0}")]
+    FieldType(String),
+
+    /// A binary section has the wrong id.
+    #[error("invalid e57 section id: {0}")]
+    Section(u8),
+
+    /// A packet has an unknown type.
+    #[error("invalid e57 packet type: {0}")]
+    Packet(u8),
+
+    /// A data packet has a different number of bytestreams than the prototype has fields.
+    #[error("e57 data packet has {found} bytestreams, expected {expected}")]
+    #[allow(missing_docs)]
+    BytestreamCount { expected: usize, found: usize },
+
+    /// A scan's binary section ended before all of its records were read.
+    #[error("e57 binary section ended before all records were read")]
+    EndOfSection,
+
+    /// A return index doesn't fit in a las return number.
+    #[error("e57 return index is out of range: {0}")]
+    ReturnIndex(f64),
+
+    /// A return count doesn't fit in a las number of returns.
+    #[error("e57 return count is out of range: {0}")]
+    ReturnCount(f64),
+}
+
+/// A scan in an E57 file.
+#[derive(Clone, Debug)]
+pub struct Scan {
+    /// The scan's name.
+    pub name: Option<String>,
+
+    /// The scan's globally unique identifier.
+    pub guid: Option<String>,
+
+    /// The number of records, including invalid ones.
+    pub record_count: u64,
+
+    /// The transformation from the scan's coordinates to the file's coordinates.
+    pub pose: Affine,
+
+    /// When the scan started, in standard (not adjusted) gps time.
+    pub acquisition_start: Option<f64>,
+
+    file_offset: u64,
+    fields: Vec<Field>,
+    intensity_limits: Option<(f64, f64)>,
+    color_limits: [Option<(f64, f64)>; 3],
+}
+
+impl Scan {
+    fn has(&self, target: Target) -> bool {
+        self.fields.iter().any(|field| field.target == target)
+    }
+
+    fn from_node(node: Node<'_, '_>) -> Result<Scan> {
+        let points = child(node, "points").ok_or_else(|| element("points"))?;
+        let file_offset = points
+            .attribute("fileOffset")
+            .and_then(|s| s.parse().ok())
+            .ok_or_else(|| element("points"))?;
+        let record_count = points
+            .attribute("recordCount")
+            .and_then(|s| s.parse().ok())
+            .ok_or_else(|| element("points"))?;
+        let prototype = child(points, "prototype").ok_or_else(|| element("prototype"))?;
+        let fields = prototype
+            .children()
+            .filter(Node::is_element)
+            .map(Field::from_node)
+            .collect::<Result<Vec<_>>>()?;
+        let pose = match child(node, "pose") {
+            Some(pose) => {
+                let rotation = child(pose, "rotation");
+                let translation = child(pose, "translation");
+                let get = |node: Option<Node<'_, '_>>, name, default| {
+                    node.and_then(|node| number(node, name)).unwrap_or(default)
+                };
+                rigid_body(
+                    [
+                        get(rotation, "w", 1.),
+                        get(rotation, "x", 0.),
+                        get(rotation, "y", 0.),
+                        get(rotation, "z", 0.),
+                    ],
+                    [
+                        get(translation, "x", 0.),
+                        get(translation, "y", 0.),
+                        get(translation, "z", 0.),
+                    ],
+                )
+            }
+            None => Affine::identity(),
+        };
+        let limits = |node: Option<Node<'_, '_>>, min, max| {
+            let node = node?;
+            Some((number(node, min)?, number(node, max)?))
+        };
+        let color_limits = child(node, "colorLimits");
+        Ok(Scan {
+            name: child(node, "name").and_then(|node| node.text().map(String::from)),
+            guid: child(node, "guid").and_then(|node| node.text().map(String::from)),
+            record_count,
+            pose,
+            acquisition_start: child(node, "acquisitionStart")
+                .and_then(|node| number(node, "dateTimeValue")),
+            file_offset,
+            fields,
+            intensity_limits: limits(
+                child(node, "intensityLimits"),
+                "intensityMinimum",
+                "intensityMaximum",
+            ),
+            color_limits: [
+                limits(color_limits, "colorRedMinimum", "colorRedMaximum"),
+                limits(color_limits, "colorGreenMinimum", "colorGreenMaximum"),
+                limits(color_limits, "colorBlueMinimum", "colorBlueMaximum"),
+            ],
+        })
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Target {
+    X,
+    Y,
+    Z,
+    Range,
+    Azimuth,
+    Elevation,
+    CartesianInvalidState,
+    SphericalInvalidState,
+    Intensity,
+    Red,
+    Green,
+    Blue,
+    TimeStamp,
+    ReturnIndex,
+    ReturnCount,
+    Ignore,
+}
+
+#[derive(Clone, Copy, Debug)]
+enum Encoding {
+    F32,
+    F64,
+    Integer {
+        minimum: i64,
+        bits: u32,
+        scale: f64,
+        offset: f64,
+        maximum: i64,
+    },
+}
+
+#[derive(Clone, Copy, Debug)]
+struct Field {
+    target: Target,
+    encoding: Encoding,
+}
+
+impl Field {
+    fn from_node(node: Node<'_, '_>) -> Result<Field> {
+        let target = match node.tag_name().name() {
+            "cartesianX" => Target::X,
+            "cartesianY" => Target::Y,
+            "cartesianZ" => Target::Z,
+            "sphericalRange" => Target::Range,
+            "sphericalAzimuth" => Target::Azimuth,
+            "sphericalElevation" => Target::Elevation,
+            "cartesianInvalidState" => Target::CartesianInvalidState,
+            "sphericalInvalidState" => Target::SphericalInvalidState,
+            "intensity" => Target::Intensity,
+            "colorRed" => Target::Red,
+            "colorGreen" => Target::Green,
+            "colorBlue" => Target::Blue,
+            "timeStamp" => Target::TimeStamp,
+            "returnIndex" => Target::ReturnIndex,
+            "returnCount" => Target::ReturnCount,
+            _ => Target::Ignore,
+        };
+        let attribute = |name, default| -> Result<f64> {
+            match node.attribute(name) {
+                Some(s) => s
+                    .parse()
+                    .map_err(|_| element(node.tag_name().name()).into()),
+                None => Ok(default),
+            }
+        };
+        let integer = |name, default| -> Result<i64> {
+            match node.attribute(name) {
+                Some(s) => s
+                    .parse()
+                    .map_err(|_| element(node.tag_name().name()).into()),
+                None => Ok(default),
+            }
+        };
+        let encoding = match node.attribute("type") {
+            Some("Float") => match node.attribute("precision") {
+                Some("single") => Encoding::F32,
+                _ => Encoding::F64,
+            },
+            Some(type_ @ ("Integer" | "ScaledInteger")) => {
+                let minimum = integer("minimum", i64::MIN)?;
+                let maximum = integer("maximum", i64::MAX)?;
+                let range = (i128::from(maximum) - i128::from(minimum)).max(0) as u128;
+                let (scale, offset) = if type_ == "ScaledInteger" {
+                    (attribute("scale", 1.)?, attribute("offset", 0.)?)
+                } else {
+                    (1., 0.)
+                };
+                Encoding::Integer {
+                    minimum,
+                    maximum,
+                    bits: 128 - range.leading_zeros(),
+                    scale,
+                    offset,
+                }
+            }
+            type_ => {
+                return Err(Error::FieldType(format!(
+                    "{} is {}",
+                    node.tag_name().name(),
+                    type_.unwrap_or("untyped")
+                ))
+                .into())
+            }
+        };
+        Ok(Field { target, encoding })
+    }
+
+    fn bits(&self) -> u32 {
+        match self.encoding {
+            Encoding::F32 => 32,
+            Encoding::F64 => 64,
+            Encoding::Integer { bits, .. } => bits,
+        }
+    }
+
+    fn decode(&self, raw: u64) -> f64 {
+        match self.encoding {
+            Encoding::F32 => f64::from(f32::from_bits(raw as u32)),
+            Encoding::F64 => f64::from_bits(raw),
+            Encoding::Integer {
+                minimum,
+                scale,
+                offset,
+                ..
+            } => (minimum as f64 + raw as f64) * scale + offset,
+        }
+    }
+
+    /// The range of values the field can hold, used when a scan has no limits.
+    fn limits(&self, float_limits: (f64, f64)) -> (f64, f64) {
+        match self.encoding {
+            Encoding::F32 | Encoding::F64 => float_limits,
+            Encoding::Integer {
+                minimum,
+                maximum,
+                scale,
+                offset,
+                ..
+            } => (
+                minimum as f64 * scale + offset,
+                maximum as f64 * scale + offset,
+            ),
+        }
+    }
+}
+
+/// Reads logical bytes from the paged physical layout, skipping each page's checksum.
+#[derive(Debug)]
+struct Paged<R> {
+    read: R,
+    position: u64,
+}
+
+impl<R: Read + Seek> Paged<R> {
+    fn seek(&mut self, position: u64) -> Result<()> {
+        let _ = self.read.seek(SeekFrom::Start(position))?;
+        self.position = position;
+        Ok(())
+    }
+
+    fn read_exact(&mut self, mut buf: &mut [u8]) -> Result<()> {
+        while !buf.is_empty() {
+            let in_page = self.position % PAGE_SIZE;
+            if in_page >= PAGE_DATA_SIZE {
+                let mut checksum = [0; 4];
+                let n = (PAGE_SIZE - in_page) as usize;
+                self.read.read_exact(&mut checksum[..n])?;
+                self.position += n as u64;
+                continue;
+            }
+            let n = buf.len().min((PAGE_DATA_SIZE - in_page) as usize);
+            self.read.read_exact(&mut buf[..n])?;
+            self.position += n as u64;
+            buf = &mut buf[n..];
+        }
+        Ok(())
+    }
+
+    fn skip(&mut self, n: usize) -> Result<()> {
+        let mut buf = vec![0; n];
+        self.read_exact(&mut buf)
+    }
+}
+
+/// One field's values, bit-packed least significant bit first.
+#[derive(Debug, Default)]
+struct Bytestream {
+    bytes: Vec<u8>,
+    bit: usize,
+}
+
+impl Bytestream {
+    fn available(&self) -> usize {
+        self.bytes.len() * 8 - self.bit
+    }
+
+    fn push(&mut self, bytes: &[u8]) {
+        let _ = self.bytes.drain(..self.bit / 8);
+        self.bit %= 8;
+        self.bytes.extend_from_slice(bytes);
+    }
+
+    fn take(&mut self, bits: u32) -> u64 {
+        let mut value = 0;
+        let mut taken = 0;
+        while taken < bits {
+            let offset = (self.bit % 8) as u32;
+            let n = (8 - offset).min(bits - taken);
+            let chunk = u64::from(self.bytes[self.bit / 8] >> offset) & ((1 << n) - 1);
+            value |= chunk << taken;
+            taken += n;
+            self.bit += n as usize;
+        }
+        value
+    }
+}
+
+/// The scan whose binary section is being read.
+#[derive(Debug)]
+struct Section {
+    bytestreams: Vec<Bytestream>,
+    remaining: u64,
+    length: u64,
+    consumed: u64,
+}
+
+/// Reads points from an E57 file.
+#[derive(Debug)]
+pub struct E57Reader<R: Read + Seek> {
+    paged: Paged<R>,
+    scans: Vec<Scan>,
+    scan: usize,
+    section: Option<Section>,
+    is_standard_time: bool,
+}
+
+impl E57Reader<BufReader<File>> {
+    /// Opens an E57 file.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use las::e57::E57Reader;
+    /// let reader = E57Reader::from_path("scans.e57").unwrap();
+    /// ```
+    pub fn from_path<P: AsRef<Path>>(path: P) -> Result<E57Reader<BufReader<File>>> {
+        E57Reader::new(BufReader::new(File::open(path)?))
+    }
+}
+
+impl<R: Read + Seek> E57Reader<R> {
+    /// Reads the E57 file header and XML section.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use las::e57::E57Reader;
+    /// use std::fs::File;
+    /// use std::io::BufReader;
+    /// let reader = E57Reader::new(BufReader::new(File::open("scans.e57").unwrap())).unwrap();
+    /// ```
+    pub fn new(read: R) -> Result<E57Reader<R>> {
+        let mut paged = Paged { read, position: 0 };
+        paged.seek(0)?;
+        let mut header = [0; FILE_HEADER_SIZE];
+        paged.read_exact(&mut header)?;
+        if &header[..8] != SIGNATURE {
+            return Err(Error::Signature.into());
+        }
+        let major = LittleEndian::read_u32(&header[8..]);
+        let minor = LittleEndian::read_u32(&header[12..]);
+        if major != 1 {
+            return Err(Error::Version(major, minor).into());
+        }
+        let xml_offset = LittleEndian::read_u64(&header[24..]);
+        let xml_length = LittleEndian::read_u64(&header[32..]);
+        paged.seek(xml_offset)?;
+        let mut xml = vec![0; xml_length as usize];
+        paged.read_exact(&mut xml)?;
+        let xml = String::from_utf8(xml).map_err(|err| Error::Xml(err.to_string()))?;
+        let document = Document::parse(&xml).map_err(|err| Error::Xml(err.to_string()))?;
+        let scans = match child(document.root_element(), "data3D") {
+            Some(data3d) => data3d
+                .children()
+                .filter(Node::is_element)
+                .map(Scan::from_node)
+                .collect::<Result<Vec<_>>>()?,
+            None => Vec::new(),
+        };
+        let is_standard_time = scans
+            .iter()
+            .filter(|scan| scan.has(Target::TimeStamp))
+            .all(|scan| scan.acquisition_start.is_some());
+        Ok(E57Reader {
+            paged,
+            scans,
+            scan: 0,
+            section: None,
+            is_standard_time,
+        })
+    }
+
+    /// Returns the scans in the file.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use las::e57::E57Reader;
+    /// let reader = E57Reader::from_path("scans.e57").unwrap();
+    /// for scan in reader.scans() {
+    ///     println!("{:?}: {} records", scan.name, scan.record_count);
+    /// }
+    /// ```
+    pub fn scans(&self) -> &[Scan] {
+        &self.scans
+    }
+
+    /// Reads every remaining point and builds a header for them.
+    ///
+    /// The point format has color if any scan has color, and gps time if any scan has
+    /// timestamps, and points from scans without them get black or zero. The gps time type is
+    /// standard if the timestamps could be made absolute. The coordinates are stored with the
+    /// given precision, e.g. 0.001 for millimeters.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use las::e57::E57Reader;
+    /// let reader = E57Reader::from_path("scans.e57").unwrap();
+    /// let (header, points) = reader.read_to_end(0.001).unwrap();
+    /// ```
+    pub fn read_to_end(mut self, precision: f64) -> Result<(Header, Vec<Point>)> {
+        let mut points = self.by_ref().collect::<Result<Vec<_>>>()?;
+        let has_color = self.scans.iter().any(|scan| scan.has(Target::Red));
+        let has_gps_time = self.scans.iter().any(|scan| scan.has(Target::TimeStamp));
+        for point in &mut points {
+            if has_color && point.color.is_none() {
+                point.color = Some(Color::default());
+            }
+            if has_gps_time && point.gps_time.is_none() {
+                point.gps_time = Some(0.);
+            }
+        }
+        let mut builder = Builder::from((1, 2));
+        if has_gps_time && self.is_standard_time {
+            builder.gps_time_type = GpsTimeType::Standard;
+        }
+        builder.point_format = Format::new(match (has_gps_time, has_color) {
+            (false, false) => 0,
+            (true, false) => 1,
+            (false, true) => 2,
+            (true, true) => 3,
+        })?;
+        let mut bounds = Bounds::default();
+        for point in &points {
+            bounds.grow(point);
+        }
+        if !points.is_empty() {
+            builder.fit_transforms(&bounds, precision);
+            builder.bounds = bounds;
+        }
+        Ok((builder.into_header()?, points))
+    }
+
+    fn open_section(&mut self) -> Result<Section> {
+        let scan = &self.scans[self.scan];
+        self.paged.seek(scan.file_offset)?;
+        let mut header = [0; SECTION_HEADER_SIZE as usize];
+        self.paged.read_exact(&mut header)?;
+        if header[0] != 1 {
+            return Err(Error::Section(header[0]).into());
+        }
+        let length = LittleEndian::read_u64(&header[8..]);
+        let data_offset = LittleEndian::read_u64(&header[16..]);
+        self.paged.seek(data_offset)?;
+        Ok(Section {
+            bytestreams: scan.fields.iter().map(|_| Bytestream::default()).collect(),
+            remaining: scan.record_count,
+            length,
+            consumed: SECTION_HEADER_SIZE,
+        })
+    }
+
+    fn read_packet(paged: &mut Paged<R>, section: &mut Section) -> Result<()> {
+        if section.consumed >= section.length {
+            return Err(Error::EndOfSection.into());
+        }
+        let mut header = [0; 4];
+        paged.read_exact(&mut header)?;
+        let length = usize::from(LittleEndian::read_u16(&header[2..])) + 1;
+        section.consumed += length as u64;
+        match header[0] {
+            0 | 2 => paged.skip(length.saturating_sub(header.len())),
+            1 => {
+                let mut count = [0; 2];
+                paged.read_exact(&mut count)?;
+                let count = usize::from(LittleEndian::read_u16(&count));
+                if count != section.bytestreams.len() {
+                    return Err(Error::BytestreamCount {
+                        expected: section.bytestreams.len(),
+                        found: count,
+                    }
+                    .into());
+                }
+                let mut lengths = vec![0; 2 * count];
+                paged.read_exact(&mut lengths)?;
+                let mut consumed = header.len() + 2 + lengths.len();
+                let mut buffer = Vec::new();
+                for (bytestream, n) in section.bytestreams.iter_mut().zip(lengths.chunks(2)) {
+                    buffer.resize(usize::from(LittleEndian::read_u16(n)), 0);
+                    paged.read_exact(&mut buffer)?;
+                    bytestream.push(&buffer);
+                    consumed += buffer.len();
+                }
+                paged.skip(length.saturating_sub(consumed))
+            }
+            packet_type => Err(Error::Packet(packet_type).into()),
+        }
+    }
+
+    /// Reads the next record of the current scan, returning `None` if it's invalid.
+    fn read_record(&mut self) -> Result<Option<Point>> {
+        let scan = &self.scans[self.scan];
+        let section = self.section.as_mut().expect("the section is open");
+        section.remaining -= 1;
+        let mut values = Vec::with_capacity(scan.fields.len());
+        for (i, field) in scan.fields.iter().enumerate() {
+            let bits = field.bits() as usize;
+            while section.bytestreams[i].available() < bits {
+                E57Reader::read_packet(&mut self.paged, section)?;
+            }
+            let raw = section.bytestreams[i].take(field.bits());
+            values.push(field.decode(raw));
+        }
+
+        let mut point = Point {
+            point_source_id: self.scan as u16,
+            ..Default::default()
+        };
+        let mut cartesian = Vector::default();
+        let mut spherical = Vector::default();
+        let mut color = [0.; 3];
+        for (field, &value) in scan.fields.iter().zip(&values) {
+            let scale = |limits: Option<(f64, f64)>, float_limits| {
+                let (min, max) = limits.unwrap_or_else(|| field.limits(float_limits));
+                if max > min {
+                    ((value - min) / (max - min)).clamp(0., 1.)
+                } else {
+                    0.
+                }
+            };
+            match field.target {
+                Target::X => cartesian.x = value,
+                Target::Y => cartesian.y = value,
+                Target::Z => cartesian.z = value,
+                Target::Range => spherical.x = value,
+                Target::Azimuth => spherical.y = value,
+                Target::Elevation => spherical.z = value,
+                Target::CartesianInvalidState | Target::SphericalInvalidState => {
+                    if value != 0. {
+                        return Ok(None);
+                    }
+                }
+                Target::Intensity => {
+                    point.intensity =
+                        (scale(scan.intensity_limits, (0., 1.)) * 65535.).round() as u16
+                }
+                Target::Red => color[0] = scale(scan.color_limits[0], (0., 1.)),
+                Target::Green => color[1] = scale(scan.color_limits[1], (0., 1.)),
+                Target::Blue => color[2] = scale(scan.color_limits[2], (0., 1.)),
+                Target::TimeStamp => {
+                    point.gps_time = Some(match scan.acquisition_start {
+                        Some(start) if self.is_standard_time => {
+                            start + value - ADJUSTED_STANDARD_OFFSET
+                        }
+                        _ => value,
+                    })
+                }
+                Target::ReturnIndex => {
+                    if !(0. ..255.).contains(&value) {
+                        return Err(Error::ReturnIndex(value).into());
+                    }
+                    point.return_number = value as u8 + 1
+                }
+                Target::ReturnCount => {
+                    if !(0. ..=255.).contains(&value) {
+                        return Err(Error::ReturnCount(value).into());
+                    }
+                    point.number_of_returns = value as u8
+                }
+                Target::Ignore => {}
+            }
+        }
+        if scan.has(Target::Red) {
+            let channel = |value: f64| (value * 65535.).round() as u16;
+            point.color = Some(Color::new(
+                channel(color[0]),
+                channel(color[1]),
+                channel(color[2]),
+            ));
+        }
+        let position = if scan.has(Target::X) {
+            cartesian
+        } else {
+            let (range, azimuth, elevation) = (spherical.x, spherical.y, spherical.z);
+            Vector {
+                x: range * elevation.cos() * azimuth.cos(),
+                y: range * elevation.cos() * azimuth.sin(),
+                z: range * elevation.sin(),
+            }
+        };
+        let position = scan.pose.transform(position);
+        point.x = position.x;
+        point.y = position.y;
+        point.z = position.z;
+        Ok(Some(point))
+    }
+
+    fn next_point(&mut self) -> Result<Option<Point>> {
+        while self.scan < self.scans.len() {
+            if self.section.is_none() {
+                self.section = Some(self.open_section()?);
+            }
+            if self.section.as_ref().map_or(0, |section| section.remaining) == 0 {
+                self.section = None;
+                self.scan += 1;
+                continue;
+            }
+            if let Some(point) = self.read_record()? {
+                return Ok(Some(point));
+            }
+        }
+        Ok(None)
+    }
+}
+
+impl<R: Read + Seek> Iterator for E57Reader<R> {
+    type Item = Result<Point>;
+
+    fn next(&mut self) -> Option<Result<Point>> {
+        let result = self.next_point();
+        if result.is_err() {
+            // The binary section can't be resynchronized, so stop after an error.
+            self.scan = self.scans.len();
+        }
+        result.transpose()
+    }
+}
+
+fn child<'a, 'input>(node: Node<'a, 'input>, name: &str) -> Option<Node<'a, 'input>> {
+    node.children()
+        .find(|child| child.is_element() && child.tag_name().name() == name)
+}
+
+fn number(node: Node<'_, '_>, name: &str) -> Option<f64> {
+    let text = child(node, name)?.text().unwrap_or("").trim();
+    if text.is_empty() {
+        Some(0.)
+    } else {
+        text.parse().ok()
+    }
+}
+
+fn element(name: &str) -> Error {
+    Error::Element(name.to_string())
+}
+
+/// Builds a rotation, from a unit quaternion, followed by a translation.
+fn rigid_body([w, x, y, z]: [f64; 4], [tx, ty, tz]: [f64; 3]) -> Affine {
+    Affine::new([
+        [
+            1. - 2. * (y * y + z * z),
+            2. * (x * y - z * w),
+            2. * (x * z + y * w),
+            tx,
+        ],
+        [
+            2. * (x * y + z * w),
+            1. - 2. * (x * x + z * z),
+            2. * (y * z - x * w),
+            ty,
+        ],
+        [
+            2. * (x * z - y * w),
+            2. * (y * z + x * w),
+            1. - 2. * (x * x + y * y),
+            tz,
+        ],
+        [0., 0., 0., 1.],
+    ])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use byteorder::WriteBytesExt;
+    use std::io::Cursor;
+
+    fn physical(logical: usize) -> usize {
+        logical / PAGE_DATA_SIZE as usize * PAGE_SIZE as usize + logical % PAGE_DATA_SIZE as usize
+    }
+
+    fn pack(values: &[u64], bits: u32) -> Vec<u8> {
+        let mut bytes = vec![0u8; (values.len() * bits as usize).div_ceil(8)];
+        for (i, &value) in values.iter().enumerate() {
+            for bit in 0..bits as usize {
+                if value >> bit & 1 == 1 {
+                    let n = i * bits as usize + bit;
+                    bytes[n / 8] |= 1 << (n % 8);
+                }
+            }
+        }
+        bytes
+    }
+
+    fn packet(buffers: &[&[u8]]) -> Vec<u8> {
+        let mut packet = vec![1, 0, 0, 0];
+        packet
+            .write_u16::<LittleEndian>(buffers.len() as u16)
+            .unwrap();
+        for buffer in buffers {
+            packet
+                .write_u16::<LittleEndian>(buffer.len() as u16)
+                .unwrap();
+        }
+        for buffer in buffers {
+            packet.extend_from_slice(buffer);
+        }
+        while packet.len() % 4 != 0 {
+            packet.push(0);
+        }
+        let length = packet.len() as u16 - 1;
+        LittleEndian::write_u16(&mut packet[2..], length);
+        packet
+    }
+
+    /// Appends a binary section to the logical data, returning its physical offset.
+    fn section(data: &mut Vec<u8>, packets: &[Vec<u8>]) -> usize {
+        let start = data.len();
+        let length = SECTION_HEADER_SIZE as usize + packets.iter().map(Vec::len).sum::<usize>();
+        data.extend_from_slice(&[1, 0, 0, 0, 0, 0, 0, 0]);
+        data.write_u64::<LittleEndian>(length as u64).unwrap();
+        data.write_u64::<LittleEndian>(physical(start + 32) as u64)
+            .unwrap();
+        data.write_u64::<LittleEndian>(0).unwrap();
+        for packet in packets {
+            data.extend_from_slice(packet);
+        }
+        physical(start)
+    }
+
+    fn e57() -> Vec<u8> {
+        let mut data = vec![0; FILE_HEADER_SIZE];
+        data.resize(1500, 0);
+
+        // Two packets, so the 12-bit intensities straddle a packet boundary.
+        let xs = [1f64, 2., 3.];
+        let intensities = pack(&[0, 4095, 2048], 12);
+        let reds = pack(&[255, 0, 0], 8);
+        let bytes = |values: &[f64]| {
+            values
+                .iter()
+                .flat_map(|value| value.to_le_bytes())
+                .collect::<Vec<_>>()
+        };
+        let first = section(
+            &mut data,
+            &[
+                packet(&[
+                    &bytes(&xs[..2]),
+                    &bytes(&[0., 0.]),
+                    &bytes(&[0., 0.]),
+                    &intensities[..2],
+                    &reds[..1],
+                ]),
+                packet(&[
+                    &bytes(&xs[2..]),
+                    &bytes(&[0.]),
+                    &bytes(&[0.]),
+                    &intensities[2..],
+                    &reds[1..],
+                ]),
+            ],
+        );
+
+        let ranges = pack(&[1000, 2000, 3000], 17);
+        let zeros = 0f32.to_le_bytes().repeat(3);
+        let states = pack(&[0, 2, 0], 2);
+        let second = section(&mut data, &[packet(&[&ranges, &zeros, &zeros, &states])]);
+
+        let scans = format!(
+            r#"<vectorChild type="Structure">
+<name type="String"><![CDATA[first]]></name>
+<pose type="Structure">
+<rotation type="Structure"><w type="Float">{w}</w><x type="Float">0</x><y type="Float">0</y><z type="Float">{w}</z></rotation>
+<translation type="Structure"><x type="Float">10</x><y type="Float">0</y><z type="Float">0</z></translation>
+</pose>
+<intensityLimits type="Structure"><intensityMinimum type="Integer">0</intensityMinimum><intensityMaximum type="Integer">4095</intensityMaximum></intensityLimits>
+<points type="CompressedVector" fileOffset="{first}" recordCount="3">
+<prototype type="Structure">
+<cartesianX type="Float"/><cartesianY type="Float"/><cartesianZ type="Float"/>
+<intensity type="Integer" minimum="0" maximum="4095"/>
+<colorRed type="Integer" minimum="0" maximum="255"/>
+</prototype>
+<codecs type="Vector" allowHeterogeneousChildren="1"/>
+</points>
+</vectorChild>
+<vectorChild type="Structure">
+<name type="String"><![CDATA[second]]></name>
+<points type="CompressedVector" fileOffset="{second}" recordCount="3">
+<prototype type="Structure">
+<sphericalRange type="ScaledInteger" minimum="0" maximum="100000" scale="0.001"/>
+<sphericalAzimuth type="Float" precision="single"/>
+<sphericalElevation type="Float" precision="single"/>
+<sphericalInvalidState type="Integer" minimum="0" maximum="2"/>
+</prototype>
+<codecs type="Vector" allowHeterogeneousChildren="1"/>
+</points>
+</vectorChild>"#,
+            w = std::f64::consts::FRAC_1_SQRT_2,
+        );
+        file(data, &scans)
+    }
+
+    /// Wraps the logical data and the scans' xml into a paged file.
+    fn file(mut data: Vec<u8>, scans: &str) -> Vec<u8> {
+        let xml = format!(
+            r#"<?xml version="1.0" encoding="UTF-8"?>
+<e57Root type="Structure" xmlns="http://www.astm.org/COMMIT/E57/2010-e57-v1.0">
+<formatName type="String"><![CDATA[ASTM E57 3D Imaging Data File]]></formatName>
+<data3D type="Vector" allowHeterogeneousChildren="1">
+{scans}
+</data3D>
+</e57Root>
+"#
+        );
+        let xml_offset = physical(data.len());
+        data.extend_from_slice(xml.as_bytes());
+
+        data[..8].copy_from_slice(SIGNATURE);
+        LittleEndian::write_u32(&mut data[8..], 1);
+        LittleEndian::write_u64(&mut data[24..], xml_offset as u64);
+        LittleEndian::write_u64(&mut data[32..], xml.len() as u64);
+        LittleEndian::write_u64(&mut data[40..], PAGE_SIZE);
+
+        let mut file = Vec::new();
+        for page in data.chunks(PAGE_DATA_SIZE as usize) {
+            file.extend_from_slice(page);
+            file.resize(file.len() + PAGE_DATA_SIZE as usize - page.len() + 4, 0);
+        }
+        let length = file.len() as u64;
+        LittleEndian::write_u64(&mut file[16..], length);
+        file
+    }
+
+    #[test]
+    fn scans() {
+        let reader = E57Reader::new(Cursor::new(e57())).unwrap();
+        let names = reader
+            .scans()
+            .iter()
+            .map(|scan| scan.name.as_deref().unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(vec!["first", "second"], names);
+        assert_eq!(3, reader.scans()[1].record_count);
+    }
+
+    #[test]
+    fn points() {
+        let (header, points) = E57Reader::new(Cursor::new(e57()))
+            .unwrap()
+            .read_to_end(0.001)
+            .unwrap();
+        assert!(header.point_format().has_color);
+        assert_eq!(5, points.len());
+
+        // The first scan is rotated a quarter turn about z and moved 10 along x.
+        for (point, x) in points[..3].iter().zip(&[1., 2., 3.]) {
+            assert!((point.x - 10.).abs() < 1e-9);
+            assert!((point.y - x).abs() < 1e-9);
+            assert_eq!(0, point.point_source_id);
+        }
+        assert_eq!(
+            vec![0, 65535, 32776],
+            points[..3]
+                .iter()
+                .map(|point| point.intensity)
+                .collect::<Vec<_>>()
+        );
+        assert_eq!(65535, points[0].color.unwrap().red);
+        assert_eq!(0, points[1].color.unwrap().red);
+
+        // The second scan's invalid record is skipped.
+        assert_eq!(1, points[3].point_source_id);
+        assert_eq!(Some(Color::default()), points[3].color);
+        assert!((points[3].x - 1.).abs() < 1e-9);
+        assert!((points[4].x - 3.).abs() < 1e-9);
+    }
+
+    #[test]
+    fn return_index_out_of_range() {
+        let mut data = vec![0; FILE_HEADER_SIZE];
+        data.resize(1500, 0);
+        let zeros = 0f64.to_le_bytes();
+        let offset = section(
+            &mut data,
+            &[packet(&[&zeros, &zeros, &zeros, &pack(&[255], 8)])],
+        );
+        let scans = format!(
+            r#"<vectorChild type="Structure">
+<points type="CompressedVector" fileOffset="{offset}" recordCount="1">
+<prototype type="Structure">
+<cartesianX type="Float"/><cartesianY type="Float"/><cartesianZ type="Float"/>
+<returnIndex type="Integer" minimum="0" maximum="255"/>
+</prototype>
+<codecs type="Vector" allowHeterogeneousChildren="1"/>
+</points>
+</vectorChild>"#
+        );
+        let error = E57Reader::new(Cursor::new(file(data, &scans)))
+            .unwrap()
+            .read_to_end(0.001)
+            .unwrap_err();
+        assert!(matches!(
+            error,
+            crate::Error::E57(Error::ReturnIndex(index)) if index == 255.
+        ));
+    }
+
+    #[test]
+    fn timestamps() {
+        let scans = |starts: [&str; 2]| {
+            let mut data = vec![0; FILE_HEADER_SIZE];
+            data.resize(1500, 0);
+            let zeros = 0f64.to_le_bytes();
+            let mut xml = String::new();
+            for (time, start) in [1f64, 2.].iter().zip(starts) {
+                let offset = section(
+                    &mut data,
+                    &[packet(&[&zeros, &zeros, &zeros, &time.to_le_bytes()])],
+                );
+                xml.push_str(&format!(
+                    r#"<vectorChild type="Structure">
+{start}
+<points type="CompressedVector" fileOffset="{offset}" recordCount="1">
+<prototype type="Structure">
+<cartesianX type="Float"/><cartesianY type="Float"/><cartesianZ type="Float"/>
+<timeStamp type="Float"/>
+</prototype>
+<codecs type="Vector" allowHeterogeneousChildren="1"/>
+</points>
+</vectorChild>"#
+                ));
+            }
+            E57Reader::new(Cursor::new(file(data, &xml)))
+                .unwrap()
+                .read_to_end(0.001)
+                .unwrap()
+        };
+        let start = |time: f64| {
+            format!(
+                r#"<acquisitionStart type="Structure"><dateTimeValue type="Float">{time}</dateTimeValue></acquisitionStart>"#
+            )
+        };
+
+        let (header, points) = scans([&start(1_000_000_100.), &start(1_000_000_200.)]);
+        assert_eq!(GpsTimeType::Standard, header.gps_time_type());
+        assert_eq!(Some(101.), points[0].gps_time);
+        assert_eq!(Some(202.), points[1].gps_time);
+
+        let (header, points) = scans([&start(1_000_000_100.), ""]);
+        assert_eq!(GpsTimeType::Week, header.gps_time_type());
+        assert_eq!(Some(1.), points[0].gps_time);
+        assert_eq!(Some(2.), points[1].gps_time);
+    }
+
+    #[test]
+    fn not_e57() {
+        assert!(E57Reader::new(Cursor::new(vec![0; 1024])).is_err());
+    }
+}
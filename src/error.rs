@@ -1,11 +1,32 @@
 use std::io;
 use std::str;
 use thiserror::Error;
-use crate::{header, point, reader, vlr, writer, Transform, Version};
+use crate::{class_map, decimate, density, extra_bytes, gps_time, header, pcd, ply, point, reader, text, tiling, trajectory, vlr, writer, Transform, Version};
 
 /// Crate-specific error enum.
 #[derive(Error, Debug)]
 pub enum Error {
+    /// Wrapper around `las::class_map::Error`.
+    #[error(transparent)]
+    ClassMap(#[from] class_map::Error),
+
+    /// Wrapper around `las::decimate::Error`.
+    #[error(transparent)]
+    Decimate(#[from] decimate::Error),
+
+    /// Wrapper around `las::density::Error`.
+    #[error(transparent)]
+    Density(#[from] density::Error),
+
+    /// Wrapper around `las::e57::Error`.
+    #[cfg(feature = "e57")]
+    #[error(transparent)]
+    E57(#[from] crate::e57::Error),
+
+    /// Wrapper around `las::extra_bytes::Error`.
+    #[error(transparent)]
+    ExtraBytes(#[from] extra_bytes::Error),
+
     /// Feature is not supported by version.
     #[error("feature {feature} is not supported by version {version}")]
     #[allow(missing_docs)]
@@ -14,6 +35,10 @@ pub enum Error {
         feature: &'static str,
     },
 
+    /// Wrapper around `las::gps_time::Error`.
+    #[error(transparent)]
+    GpsTime(#[from] gps_time::Error),
+
     /// A wrapper around `las::header::Error`.
     #[error(transparent)]
     Header(#[from] header::Error),
@@ -41,6 +66,14 @@ pub enum Error {
     #[error("the bytes are not zero-filled: {0:?}")]
     NotZeroFilled(Vec<u8>),
 
+    /// Wrapper around `las::pcd::Error`.
+    #[error(transparent)]
+    Pcd(#[from] pcd::Error),
+
+    /// Wrapper around `las::ply::Error`.
+    #[error(transparent)]
+    Ply(#[from] ply::Error),
+
     /// Wrapper around `las::point::Error`.
     #[error(transparent)]
     Point(#[from] point::Error),
@@ -54,6 +87,18 @@ pub enum Error {
     #[allow(missing_docs)]
     StringTooLong { string: String, len: usize },
 
+    /// Wrapper around `las::text::Error`.
+    #[error(transparent)]
+    Text(#[from] text::Error),
+
+    /// Wrapper around `las::tiling::Error`.
+    #[error(transparent)]
+    Tiling(#[from] tiling::Error),
+
+    /// Wrapper around `las::trajectory::Error`.
+    #[error(transparent)]
+    Trajectory(#[from] trajectory::Error),
+
     /// Wrapper around `std::str::Utf8Error`.
     #[error(transparent)]
     Utf8(#[from] str::Utf8Error),
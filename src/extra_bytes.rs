@@ -0,0 +1,450 @@
+//! Typed extra bytes.
+//!
+//! The extra bytes at the end of each point record can be described by an extra bytes vlr, which
+//! holds one `Descriptor` per attribute. The attributes are packed in order, so each one starts
+//! where the one before it ends:
+//!
+//! ```
+//! use las::extra_bytes::{self, DataType, Descriptor};
+//!
+//! let descriptors = vec![
+//!     Descriptor::new("range", DataType::F32),
+//!     Descriptor::new("amplitude", DataType::U16),
+//! ];
+//! let vlr = extra_bytes::vlr(&descriptors).unwrap();
+//! assert_eq!(descriptors, extra_bytes::descriptors_from_vlr(&vlr).unwrap());
+//!
+//! let mut bytes = vec![0; 6];
+//! descriptors[1].set(&mut bytes[4..], 42.).unwrap();
+//! assert_eq!(Some(42.), descriptors[1].get(&bytes[4..]));
+//! ```
+
+use crate::utils::{AsLasStr, FromLasStr};
+use crate::{Header, Result, Vlr};
+use byteorder::{ByteOrder, LittleEndian};
+use thiserror::Error;
+
+/// The user id of extra bytes vlrs.
+pub const USER_ID: &str = "LASF_Spec";
+
+/// The record id of extra bytes vlrs.
+pub const RECORD_ID: u16 = 4;
+
+const RECORD_LENGTH: usize = 192;
+
+/// Extra bytes errors.
+#[derive(Error, Clone, Copy, Debug)]
+pub enum Error {
+    /// The extra bytes vlr isn't a whole number of records.
+    #[error("the extra bytes vlr has {0} bytes, which isn't a multiple of 192")]
+    Length(usize),
+
+    /// The data type is reserved, or deprecated and can't hold values.
+    #[error("unsupported extra bytes data type: {0}")]
+    DataType(u8),
+
+    /// The value can't be stored in this data type.
+    #[error("{value} can't be stored as {data_type:?}")]
+    #[allow(missing_docs)]
+    Value { value: f64, data_type: DataType },
+
+    /// The slice is too short for this data type.
+    #[error("{len} bytes is too short for {data_type:?}")]
+    #[allow(missing_docs)]
+    TooShort { len: usize, data_type: DataType },
+}
+
+/// The type of an extra bytes attribute.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DataType {
+    /// Bytes without a documented meaning, of the given length.
+    Undocumented(u8),
+    /// An unsigned byte.
+    U8,
+    /// A signed byte.
+    I8,
+    /// An unsigned short.
+    U16,
+    /// A signed short.
+    I16,
+    /// An unsigned long.
+    U32,
+    /// A signed long.
+    I32,
+    /// An unsigned long long.
+    U64,
+    /// A signed long long.
+    I64,
+    /// A float.
+    F32,
+    /// A double.
+    F64,
+    /// One of the deprecated two or three element arrays, by its code, e.g. 23 for three unsigned
+    /// shorts. Their values can't be read or written.
+    Deprecated(u8),
+}
+
+impl DataType {
+    /// Returns the number of bytes taken by this data type.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::extra_bytes::DataType;
+    /// assert_eq!(8, DataType::F64.size());
+    /// assert_eq!(3, DataType::Undocumented(3).size());
+    /// assert_eq!(6, DataType::Deprecated(23).size());
+    /// ```
+    pub fn size(&self) -> usize {
+        match *self {
+            DataType::Undocumented(n) => usize::from(n),
+            DataType::Deprecated(n) => {
+                let element = DataType::from_u8((n - 11) % 10 + 1, 0).map_or(0, |t| t.size());
+                element * if n <= 20 { 2 } else { 3 }
+            }
+            DataType::U8 | DataType::I8 => 1,
+            DataType::U16 | DataType::I16 => 2,
+            DataType::U32 | DataType::I32 | DataType::F32 => 4,
+            DataType::U64 | DataType::I64 | DataType::F64 => 8,
+        }
+    }
+
+    fn from_u8(n: u8, options: u8) -> Result<DataType> {
+        Ok(match n {
+            0 => DataType::Undocumented(options),
+            1 => DataType::U8,
+            2 => DataType::I8,
+            3 => DataType::U16,
+            4 => DataType::I16,
+            5 => DataType::U32,
+            6 => DataType::I32,
+            7 => DataType::U64,
+            8 => DataType::I64,
+            9 => DataType::F32,
+            10 => DataType::F64,
+            11..=30 => DataType::Deprecated(n),
+            _ => return Err(Error::DataType(n).into()),
+        })
+    }
+
+    fn to_u8(self) -> u8 {
+        match self {
+            DataType::Undocumented(_) => 0,
+            DataType::U8 => 1,
+            DataType::I8 => 2,
+            DataType::U16 => 3,
+            DataType::I16 => 4,
+            DataType::U32 => 5,
+            DataType::I32 => 6,
+            DataType::U64 => 7,
+            DataType::I64 => 8,
+            DataType::F32 => 9,
+            DataType::F64 => 10,
+            DataType::Deprecated(n) => n,
+        }
+    }
+}
+
+/// Describes one extra bytes attribute.
+///
+/// The no data, min, and max fields of the record aren't kept.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Descriptor {
+    /// The attribute's name, at most 32 characters.
+    pub name: String,
+
+    /// The attribute's data type.
+    pub data_type: DataType,
+
+    /// A description of the attribute, at most 32 characters.
+    pub description: String,
+
+    /// The scale applied to stored values.
+    pub scale: Option<f64>,
+
+    /// The offset applied to stored values, after scaling.
+    pub offset: Option<f64>,
+}
+
+impl Descriptor {
+    /// Creates a descriptor without a description, scale, or offset.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::extra_bytes::{DataType, Descriptor};
+    /// let descriptor = Descriptor::new("range", DataType::F32);
+    /// assert_eq!(None, descriptor.scale);
+    /// ```
+    pub fn new<S: Into<String>>(name: S, data_type: DataType) -> Descriptor {
+        Descriptor {
+            name: name.into(),
+            data_type,
+            description: String::new(),
+            scale: None,
+            offset: None,
+        }
+    }
+
+    /// Reads a descriptor from a 192 byte record.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::extra_bytes::{DataType, Descriptor};
+    /// let descriptor = Descriptor::new("range", DataType::F32);
+    /// let record = descriptor.to_bytes().unwrap();
+    /// assert_eq!(descriptor, Descriptor::from_bytes(&record).unwrap());
+    /// ```
+    pub fn from_bytes(record: &[u8]) -> Result<Descriptor> {
+        if record.len() != RECORD_LENGTH {
+            return Err(Error::Length(record.len()).into());
+        }
+        let options = record[3];
+        let option = |bit: u8, start: usize| {
+            if options >> bit & 1 == 1 {
+                Some(LittleEndian::read_f64(&record[start..start + 8]))
+            } else {
+                None
+            }
+        };
+        let data_type = DataType::from_u8(record[2], options)?;
+        let is_typed = data_type.to_u8() != 0;
+        Ok(Descriptor {
+            name: (&record[4..36]).as_las_string_lossy(),
+            data_type,
+            description: (&record[160..192]).as_las_string_lossy(),
+            scale: if is_typed { option(3, 112) } else { None },
+            offset: if is_typed { option(4, 136) } else { None },
+        })
+    }
+
+    /// Writes this descriptor to a 192 byte record.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::extra_bytes::{DataType, Descriptor};
+    /// let record = Descriptor::new("range", DataType::F32).to_bytes().unwrap();
+    /// assert_eq!(192, record.len());
+    /// ```
+    pub fn to_bytes(&self) -> Result<Vec<u8>> {
+        let mut record = vec![0; RECORD_LENGTH];
+        record[2] = self.data_type.to_u8();
+        if let DataType::Undocumented(n) = self.data_type {
+            record[3] = n;
+        } else {
+            if let Some(scale) = self.scale {
+                record[3] |= 1 << 3;
+                LittleEndian::write_f64(&mut record[112..120], scale);
+            }
+            if let Some(offset) = self.offset {
+                record[3] |= 1 << 4;
+                LittleEndian::write_f64(&mut record[136..144], offset);
+            }
+        }
+        record[4..36].as_mut().from_las_str(&self.name)?;
+        record[160..192].as_mut().from_las_str(&self.description)?;
+        Ok(record)
+    }
+
+    /// Returns the value of this attribute from the start of the bytes, with scale and offset
+    /// applied.
+    ///
+    /// Returns `None` for undocumented and deprecated attributes and for slices that are too short.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::extra_bytes::{DataType, Descriptor};
+    /// let mut descriptor = Descriptor::new("temperature", DataType::I16);
+    /// descriptor.scale = Some(0.1);
+    /// assert_eq!(Some(-1.), descriptor.get(&(-10i16).to_le_bytes()));
+    /// ```
+    pub fn get(&self, bytes: &[u8]) -> Option<f64> {
+        if bytes.len() < self.data_type.size() {
+            return None;
+        }
+        let value = match self.data_type {
+            DataType::Undocumented(_) | DataType::Deprecated(_) => return None,
+            DataType::U8 => f64::from(bytes[0]),
+            DataType::I8 => f64::from(bytes[0] as i8),
+            DataType::U16 => f64::from(LittleEndian::read_u16(bytes)),
+            DataType::I16 => f64::from(LittleEndian::read_i16(bytes)),
+            DataType::U32 => f64::from(LittleEndian::read_u32(bytes)),
+            DataType::I32 => f64::from(LittleEndian::read_i32(bytes)),
+            DataType::U64 => LittleEndian::read_u64(bytes) as f64,
+            DataType::I64 => LittleEndian::read_i64(bytes) as f64,
+            DataType::F32 => f64::from(LittleEndian::read_f32(bytes)),
+            DataType::F64 => LittleEndian::read_f64(bytes),
+        };
+        Some(value * self.scale.unwrap_or(1.) + self.offset.unwrap_or(0.))
+    }
+
+    /// Stores a value at the start of the bytes, removing the offset and scale first.
+    ///
+    /// Integer types are rounded to the nearest value.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::extra_bytes::{DataType, Descriptor};
+    /// let descriptor = Descriptor::new("class", DataType::U8);
+    /// let mut bytes = [0];
+    /// descriptor.set(&mut bytes, 7.).unwrap();
+    /// assert_eq!([7], bytes);
+    /// assert!(descriptor.set(&mut bytes, 256.).is_err());
+    /// ```
+    pub fn set(&self, bytes: &mut [u8], value: f64) -> Result<()> {
+        let data_type = self.data_type;
+        if bytes.len() < data_type.size() {
+            return Err(Error::TooShort {
+                len: bytes.len(),
+                data_type,
+            }
+            .into());
+        }
+        let stored = (value - self.offset.unwrap_or(0.)) / self.scale.unwrap_or(1.);
+        let integer = |min: f64, max: f64| {
+            let stored = stored.round();
+            if (min..=max).contains(&stored) {
+                Ok(stored)
+            } else {
+                Err(Error::Value { value, data_type })
+            }
+        };
+        match data_type {
+            DataType::Undocumented(_) => return Err(Error::Value { value, data_type }.into()),
+            DataType::Deprecated(n) => return Err(Error::DataType(n).into()),
+            DataType::U8 => bytes[0] = integer(0., 255.)? as u8,
+            DataType::I8 => bytes[0] = integer(-128., 127.)? as i8 as u8,
+            DataType::U16 => LittleEndian::write_u16(bytes, integer(0., 65535.)? as u16),
+            DataType::I16 => LittleEndian::write_i16(bytes, integer(-32768., 32767.)? as i16),
+            DataType::U32 => {
+                LittleEndian::write_u32(bytes, integer(0., f64::from(u32::MAX))? as u32)
+            }
+            DataType::I32 => LittleEndian::write_i32(
+                bytes,
+                integer(f64::from(i32::MIN), f64::from(i32::MAX))? as i32,
+            ),
+            DataType::U64 => LittleEndian::write_u64(bytes, integer(0., u64::MAX as f64)? as u64),
+            DataType::I64 => {
+                LittleEndian::write_i64(bytes, integer(i64::MIN as f64, i64::MAX as f64)? as i64)
+            }
+            DataType::F32 => LittleEndian::write_f32(bytes, stored as f32),
+            DataType::F64 => LittleEndian::write_f64(bytes, stored),
+        }
+        Ok(())
+    }
+}
+
+/// Returns the descriptors in an extra bytes vlr.
+///
+/// # Examples
+///
+/// ```
+/// use las::extra_bytes::{self, DataType, Descriptor};
+/// let vlr = extra_bytes::vlr(&[Descriptor::new("range", DataType::F32)]).unwrap();
+/// assert_eq!(1, extra_bytes::descriptors_from_vlr(&vlr).unwrap().len());
+/// ```
+pub fn descriptors_from_vlr(vlr: &Vlr) -> Result<Vec<Descriptor>> {
+    if !vlr.data.len().is_multiple_of(RECORD_LENGTH) {
+        return Err(Error::Length(vlr.data.len()).into());
+    }
+    vlr.data
+        .chunks(RECORD_LENGTH)
+        .map(Descriptor::from_bytes)
+        .collect()
+}
+
+/// Returns the descriptors from the first extra bytes vlr or evlr in a header.
+///
+/// # Examples
+///
+/// ```
+/// use las::{extra_bytes, Header};
+/// assert!(extra_bytes::descriptors(&Header::default()).unwrap().is_empty());
+/// ```
+pub fn descriptors(header: &Header) -> Result<Vec<Descriptor>> {
+    header
+        .vlrs()
+        .iter()
+        .chain(header.evlrs())
+        .find(|vlr| vlr.user_id == USER_ID && vlr.record_id == RECORD_ID)
+        .map_or_else(|| Ok(Vec::new()), descriptors_from_vlr)
+}
+
+/// Creates an extra bytes vlr.
+///
+/// # Examples
+///
+/// ```
+/// use las::extra_bytes::{self, DataType, Descriptor};
+/// let vlr = extra_bytes::vlr(&[Descriptor::new("range", DataType::F32)]).unwrap();
+/// assert_eq!(192, vlr.data.len());
+/// ```
+pub fn vlr(descriptors: &[Descriptor]) -> Result<Vlr> {
+    let mut data = Vec::with_capacity(descriptors.len() * RECORD_LENGTH);
+    for descriptor in descriptors {
+        data.extend(descriptor.to_bytes()?);
+    }
+    Ok(Vlr {
+        user_id: USER_ID.to_string(),
+        record_id: RECORD_ID,
+        description: "Extra bytes".to_string(),
+        data,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn scale_and_offset_roundtrip() {
+        let descriptor = Descriptor {
+            name: "height".to_string(),
+            data_type: DataType::I32,
+            description: "height above ground".to_string(),
+            scale: Some(0.01),
+            offset: Some(100.),
+        };
+        let record = descriptor.to_bytes().unwrap();
+        assert_eq!(0b11000, record[3]);
+        assert_eq!(descriptor, Descriptor::from_bytes(&record).unwrap());
+        let mut bytes = [0; 4];
+        descriptor.set(&mut bytes, 98.76).unwrap();
+        assert_eq!(-124, i32::from_le_bytes(bytes));
+        assert!((descriptor.get(&bytes).unwrap() - 98.76).abs() < 1e-9);
+    }
+
+    #[test]
+    fn undocumented() {
+        let descriptor = Descriptor::new("padding", DataType::Undocumented(3));
+        let record = descriptor.to_bytes().unwrap();
+        assert_eq!((0, 3), (record[2], record[3]));
+        assert_eq!(descriptor, Descriptor::from_bytes(&record).unwrap());
+        assert_eq!(None, descriptor.get(&[0, 0, 0]));
+    }
+
+    #[test]
+    fn deprecated_data_type() {
+        let mut record = Descriptor::new("a", DataType::U8).to_bytes().unwrap();
+        record[2] = 11;
+        let descriptor = Descriptor::from_bytes(&record).unwrap();
+        assert_eq!(DataType::Deprecated(11), descriptor.data_type);
+        assert_eq!(2, descriptor.data_type.size());
+        assert_eq!(None, descriptor.get(&[0, 0]));
+        assert!(descriptor.set(&mut [0, 0], 1.).is_err());
+        assert_eq!(record, descriptor.to_bytes().unwrap());
+        record[2] = 31;
+        assert!(Descriptor::from_bytes(&record).is_err());
+    }
+
+    #[test]
+    fn name_too_long() {
+        assert!(Descriptor::new("a".repeat(33), DataType::U8)
+            .to_bytes()
+            .is_err());
+    }
+}
@@ -0,0 +1,449 @@
+//! Composable point filters.
+//!
+//! A `Filter` is a predicate over points, built from a handful of common tests and combined with
+//! `and`, `or`, and `!`:
+//!
+//! ```
+//! use las::point::Classification;
+//! use las::Filter;
+//!
+//! let filter = Filter::classification([Classification::Ground, Classification::Water])
+//!     .and(Filter::last_return())
+//!     .and(!Filter::withheld());
+//! ```
+//!
+//! Use `Reader::filtered_points` to read only the matching points. The reader tests each record
+//! before converting it to a `Point`, and skips the file entirely if the filter can't match
+//! anything inside the header's bounds. Otherwise every record is still read, and decompressed
+//! for laz data; spatial indexes (e.g. LAX files) and laz chunk tables aren't used to skip
+//! records.
+
+use crate::point::Classification;
+use crate::{raw, Bounds, Header, Point, Transform, Vector};
+use std::ops::{Bound, Not, RangeBounds};
+
+/// A predicate over points.
+///
+/// # Examples
+///
+/// ```
+/// use las::{Filter, Point};
+///
+/// let filter = Filter::first_return().and(Filter::point_source_id(42));
+/// let point = Point { return_number: 1, point_source_id: 42, ..Default::default() };
+/// assert!(filter.matches(&point));
+/// assert!(!(!filter).matches(&point));
+/// ```
+#[derive(Clone, Debug)]
+pub struct Filter(Predicate);
+
+#[derive(Clone, Debug)]
+enum Predicate {
+    All,
+    Classification(Vec<Classification>),
+    FirstReturn,
+    LastReturn,
+    OnlyReturn,
+    Bounds(Bounds),
+    GpsTime(Bound<f64>, Bound<f64>),
+    Withheld,
+    Synthetic,
+    PointSourceId(u16),
+    And(Box<Filter>, Box<Filter>),
+    Or(Box<Filter>, Box<Filter>),
+    Not(Box<Filter>),
+}
+
+impl Filter {
+    /// Creates a filter that matches every point.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::{Filter, Point};
+    /// assert!(Filter::all().matches(&Point::default()));
+    /// ```
+    pub fn all() -> Filter {
+        Filter(Predicate::All)
+    }
+
+    /// Creates a filter that matches points with any of these classifications.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::point::Classification;
+    /// use las::{Filter, Point};
+    /// let filter = Filter::classification([Classification::Ground]);
+    /// let point = Point { classification: Classification::Ground, ..Default::default() };
+    /// assert!(filter.matches(&point));
+    /// ```
+    pub fn classification<I: IntoIterator<Item = Classification>>(classifications: I) -> Filter {
+        Filter(Predicate::Classification(
+            classifications.into_iter().collect(),
+        ))
+    }
+
+    /// Creates a filter that matches first returns.
+    ///
+    /// A return number of zero, which some files use when they don't record returns, counts as a
+    /// first return.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::{Filter, Point};
+    /// let point = Point { return_number: 1, number_of_returns: 2, ..Default::default() };
+    /// assert!(Filter::first_return().matches(&point));
+    /// ```
+    pub fn first_return() -> Filter {
+        Filter(Predicate::FirstReturn)
+    }
+
+    /// Creates a filter that matches last returns.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::{Filter, Point};
+    /// let point = Point { return_number: 2, number_of_returns: 2, ..Default::default() };
+    /// assert!(Filter::last_return().matches(&point));
+    /// ```
+    pub fn last_return() -> Filter {
+        Filter(Predicate::LastReturn)
+    }
+
+    /// Creates a filter that matches points that are the only return of their pulse.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::{Filter, Point};
+    /// let point = Point { return_number: 1, number_of_returns: 2, ..Default::default() };
+    /// assert!(!Filter::only_return().matches(&point));
+    /// ```
+    pub fn only_return() -> Filter {
+        Filter(Predicate::OnlyReturn)
+    }
+
+    /// Creates a filter that matches points inside these bounds, inclusive.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::{Bounds, Filter, Point, Vector};
+    /// let bounds = Bounds {
+    ///     min: Vector { x: 0., y: 0., z: 0. },
+    ///     max: Vector { x: 1., y: 1., z: 1. },
+    /// };
+    /// let point = Point { x: 0.5, y: 0.5, z: 1., ..Default::default() };
+    /// assert!(Filter::bounds(bounds).matches(&point));
+    /// ```
+    pub fn bounds(bounds: Bounds) -> Filter {
+        Filter(Predicate::Bounds(bounds))
+    }
+
+    /// Creates a filter that matches points with a gps time in this range.
+    ///
+    /// Points without a gps time never match.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::{Filter, Point};
+    /// let filter = Filter::gps_time(10.0..20.0);
+    /// assert!(filter.matches(&Point { gps_time: Some(10.), ..Default::default() }));
+    /// assert!(!filter.matches(&Point { gps_time: Some(20.), ..Default::default() }));
+    /// assert!(!filter.matches(&Point::default()));
+    /// ```
+    pub fn gps_time<R: RangeBounds<f64>>(range: R) -> Filter {
+        Filter(Predicate::GpsTime(
+            range.start_bound().cloned(),
+            range.end_bound().cloned(),
+        ))
+    }
+
+    /// Creates a filter that matches withheld points.
+    ///
+    /// Use `!Filter::withheld()` to drop them instead.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::{Filter, Point};
+    /// let point = Point { is_withheld: true, ..Default::default() };
+    /// assert!(Filter::withheld().matches(&point));
+    /// ```
+    pub fn withheld() -> Filter {
+        Filter(Predicate::Withheld)
+    }
+
+    /// Creates a filter that matches synthetic points.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::{Filter, Point};
+    /// let point = Point { is_synthetic: true, ..Default::default() };
+    /// assert!(Filter::synthetic().matches(&point));
+    /// ```
+    pub fn synthetic() -> Filter {
+        Filter(Predicate::Synthetic)
+    }
+
+    /// Creates a filter that matches points with this point source id.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::{Filter, Point};
+    /// let point = Point { point_source_id: 7, ..Default::default() };
+    /// assert!(Filter::point_source_id(7).matches(&point));
+    /// ```
+    pub fn point_source_id(point_source_id: u16) -> Filter {
+        Filter(Predicate::PointSourceId(point_source_id))
+    }
+
+    /// Returns a filter that matches points matched by both this filter and the other.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::{Filter, Point};
+    /// let filter = Filter::withheld().and(Filter::synthetic());
+    /// assert!(!filter.matches(&Point { is_withheld: true, ..Default::default() }));
+    /// ```
+    pub fn and(self, other: Filter) -> Filter {
+        Filter(Predicate::And(Box::new(self), Box::new(other)))
+    }
+
+    /// Returns a filter that matches points matched by either this filter or the other.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::{Filter, Point};
+    /// let filter = Filter::withheld().or(Filter::synthetic());
+    /// assert!(filter.matches(&Point { is_withheld: true, ..Default::default() }));
+    /// ```
+    pub fn or(self, other: Filter) -> Filter {
+        Filter(Predicate::Or(Box::new(self), Box::new(other)))
+    }
+
+    /// Returns true if this point passes the filter.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::{Filter, Read, Reader};
+    /// let mut reader = Reader::from_path("tests/data/autzen.las").unwrap();
+    /// let filter = Filter::first_return();
+    /// let first_returns = reader
+    ///     .points()
+    ///     .map(|point| point.unwrap())
+    ///     .filter(|point| filter.matches(point))
+    ///     .count();
+    /// ```
+    pub fn matches(&self, point: &Point) -> bool {
+        match self.0 {
+            Predicate::All => true,
+            Predicate::Classification(ref classifications) => {
+                classifications.contains(&point.classification)
+            }
+            Predicate::FirstReturn => point.return_number <= 1,
+            Predicate::LastReturn => point.return_number >= point.number_of_returns,
+            Predicate::OnlyReturn => point.number_of_returns <= 1,
+            Predicate::Bounds(ref bounds) => contains(bounds, point.x, point.y, point.z),
+            Predicate::GpsTime(start, end) => point
+                .gps_time
+                .is_some_and(|gps_time| (start, end).contains(&gps_time)),
+            Predicate::Withheld => point.is_withheld,
+            Predicate::Synthetic => point.is_synthetic,
+            Predicate::PointSourceId(id) => point.point_source_id == id,
+            Predicate::And(ref a, ref b) => a.matches(point) && b.matches(point),
+            Predicate::Or(ref a, ref b) => a.matches(point) || b.matches(point),
+            Predicate::Not(ref filter) => !filter.matches(point),
+        }
+    }
+
+    /// Returns true if this raw point passes the filter.
+    ///
+    /// This gives the same answer as `matches` on the converted point, without doing the
+    /// conversion.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::raw::point::Flags;
+    /// use las::{raw, Filter};
+    /// let point = raw::Point { flags: Flags::TwoByte(0b00001001, 0), ..Default::default() };
+    /// assert!(Filter::only_return().matches_raw(&point, &Default::default()));
+    /// ```
+    pub fn matches_raw(&self, point: &raw::Point, transforms: &Vector<Transform>) -> bool {
+        match self.0 {
+            Predicate::All => true,
+            Predicate::Classification(ref classifications) => {
+                let mut flags = point.flags;
+                flags.clear_overlap_class();
+                flags
+                    .to_classification()
+                    .is_ok_and(|classification| classifications.contains(&classification))
+            }
+            Predicate::FirstReturn => point.flags.return_number() <= 1,
+            Predicate::LastReturn => point.flags.return_number() >= point.flags.number_of_returns(),
+            Predicate::OnlyReturn => point.flags.number_of_returns() <= 1,
+            Predicate::Bounds(ref bounds) => contains(
+                bounds,
+                transforms.x.direct(point.x),
+                transforms.y.direct(point.y),
+                transforms.z.direct(point.z),
+            ),
+            Predicate::GpsTime(start, end) => point
+                .gps_time
+                .is_some_and(|gps_time| (start, end).contains(&gps_time)),
+            Predicate::Withheld => point.flags.is_withheld(),
+            Predicate::Synthetic => point.flags.is_synthetic(),
+            Predicate::PointSourceId(id) => point.point_source_id == id,
+            Predicate::And(ref a, ref b) => {
+                a.matches_raw(point, transforms) && b.matches_raw(point, transforms)
+            }
+            Predicate::Or(ref a, ref b) => {
+                a.matches_raw(point, transforms) || b.matches_raw(point, transforms)
+            }
+            Predicate::Not(ref filter) => !filter.matches_raw(point, transforms),
+        }
+    }
+
+    /// Returns false if no point described by this header can pass the filter.
+    ///
+    /// Only the header's bounds are considered, so a true result doesn't mean that any point
+    /// will match.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::{Bounds, Filter, Read, Reader, Vector};
+    /// let reader = Reader::from_path("tests/data/autzen.las").unwrap();
+    /// let bounds = Bounds {
+    ///     min: Vector { x: 0., y: 0., z: 0. },
+    ///     max: Vector { x: 1., y: 1., z: 1. },
+    /// };
+    /// assert!(!Filter::bounds(bounds).may_match(reader.header()));
+    /// ```
+    pub fn may_match(&self, header: &Header) -> bool {
+        match self.0 {
+            Predicate::Bounds(ref bounds) => intersects(bounds, &header.bounds()),
+            Predicate::And(ref a, ref b) => a.may_match(header) && b.may_match(header),
+            Predicate::Or(ref a, ref b) => a.may_match(header) || b.may_match(header),
+            _ => true,
+        }
+    }
+}
+
+impl Default for Filter {
+    fn default() -> Filter {
+        Filter::all()
+    }
+}
+
+impl Not for Filter {
+    type Output = Filter;
+
+    fn not(self) -> Filter {
+        Filter(Predicate::Not(Box::new(self)))
+    }
+}
+
+fn contains(bounds: &Bounds, x: f64, y: f64, z: f64) -> bool {
+    bounds.min.x <= x
+        && x <= bounds.max.x
+        && bounds.min.y <= y
+        && y <= bounds.max.y
+        && bounds.min.z <= z
+        && z <= bounds.max.z
+}
+
+fn intersects(a: &Bounds, b: &Bounds) -> bool {
+    a.min.x <= b.max.x
+        && b.min.x <= a.max.x
+        && a.min.y <= b.max.y
+        && b.min.y <= a.max.y
+        && a.min.z <= b.max.z
+        && b.min.z <= a.max.z
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::raw::point::Flags;
+    use crate::{Read, Reader};
+
+    fn autzen() -> (Vec<raw::Point>, Header) {
+        let mut reader = Reader::from_path("tests/data/autzen.las").unwrap();
+        let header = reader.header().clone();
+        let points = reader
+            .raw_points()
+            .collect::<crate::Result<Vec<_>>>()
+            .unwrap();
+        (points, header)
+    }
+
+    #[test]
+    fn raw_agrees_with_point() {
+        let (points, header) = autzen();
+        let bounds = header.bounds();
+        let middle = Bounds {
+            min: bounds.min,
+            max: Vector {
+                x: (bounds.min.x + bounds.max.x) / 2.,
+                y: bounds.max.y,
+                z: bounds.max.z,
+            },
+        };
+        let filters = [
+            Filter::classification([Classification::Ground]),
+            Filter::first_return(),
+            Filter::last_return(),
+            Filter::only_return(),
+            Filter::bounds(middle),
+            Filter::gps_time(..),
+            Filter::withheld().or(Filter::synthetic()),
+            !Filter::point_source_id(7326),
+        ];
+        for filter in &filters {
+            for raw_point in &points {
+                let point = Point::new(raw_point.clone(), header.transforms());
+                assert_eq!(
+                    filter.matches(&point),
+                    filter.matches_raw(raw_point, header.transforms()),
+                    "{:?}",
+                    filter
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn overlap_classification() {
+        let point = raw::Point {
+            flags: Flags::TwoByte(0, 12),
+            ..Default::default()
+        };
+        let filter = Filter::classification([Classification::Unclassified]);
+        assert!(filter.matches_raw(&point, &Default::default()));
+    }
+
+    #[test]
+    fn may_match_or() {
+        let (_, header) = autzen();
+        let outside = Bounds {
+            min: Vector::default(),
+            max: Vector::default(),
+        };
+        assert!(!Filter::bounds(outside).may_match(&header));
+        assert!(Filter::bounds(outside)
+            .or(Filter::first_return())
+            .may_match(&header));
+        assert!((!Filter::bounds(outside)).may_match(&header));
+    }
+}
@@ -0,0 +1,279 @@
+//! Convert between gps week time, adjusted standard gps time, and UTC.
+//!
+//! Las files store gps time in one of two ways, recorded by the header's `GpsTimeType`:
+//!
+//! - Week time is the number of seconds since the start of the gps week, so it needs a reference
+//!   week to mean anything.
+//! - Adjusted standard time is the number of seconds since the gps epoch, 1980-01-06 00:00:00 UTC,
+//!   minus one billion.
+//!
+//! Gps time doesn't have leap seconds, so it's ahead of UTC by the number of leap seconds since
+//! the gps epoch. The conversions to and from UTC use a built-in leap second table.
+//!
+//! ```
+//! use chrono::{TimeZone, Utc};
+//! use las::gps_time;
+//!
+//! let standard = gps_time::week_to_standard(2000, 86_400.);
+//! assert_eq!((2000, 86_400.), gps_time::standard_to_week(standard));
+//! assert_eq!(
+//!     Utc.with_ymd_and_hms(2018, 5, 7, 0, 0, 0).unwrap(),
+//!     gps_time::standard_to_utc(standard).unwrap() + chrono::Duration::seconds(18),
+//! );
+//! ```
+
+use crate::{Builder, GpsTimeType, Header, Read, Result, Writer};
+use chrono::{DateTime, Utc};
+use std::fmt::Debug;
+use std::io::Seek;
+use thiserror::Error;
+
+/// The number of seconds in a gps week.
+pub const SECONDS_PER_WEEK: f64 = 604_800.;
+
+/// The difference between standard gps time and adjusted standard gps time.
+pub const ADJUSTED_STANDARD_OFFSET: f64 = 1e9;
+
+/// The gps epoch, 1980-01-06 00:00:00 UTC, as a unix timestamp.
+const GPS_EPOCH: i64 = 315_964_800;
+
+/// The unix timestamps at which each leap second since the gps epoch took effect.
+const LEAP_SECONDS: [i64; 18] = [
+    362_793_600,   // 1981-07-01
+    394_329_600,   // 1982-07-01
+    425_865_600,   // 1983-07-01
+    489_024_000,   // 1985-07-01
+    567_993_600,   // 1988-01-01
+    631_152_000,   // 1990-01-01
+    662_688_000,   // 1991-01-01
+    709_948_800,   // 1992-07-01
+    741_484_800,   // 1993-07-01
+    773_020_800,   // 1994-07-01
+    820_454_400,   // 1996-01-01
+    867_715_200,   // 1997-07-01
+    915_148_800,   // 1999-01-01
+    1_136_073_600, // 2006-01-01
+    1_230_768_000, // 2009-01-01
+    1_341_100_800, // 2012-07-01
+    1_435_708_800, // 2015-07-01
+    1_483_228_800, // 2017-01-01
+];
+
+/// Gps time errors.
+#[derive(Error, Clone, Copy, Debug)]
+pub enum Error {
+    /// The gps time can't be represented as a UTC date and time.
+    #[error("gps time {0} is out of range")]
+    OutOfRange(f64),
+
+    /// The header's gps times are already adjusted standard time.
+    #[error("the gps times are already adjusted standard time")]
+    AlreadyStandard,
+}
+
+/// Converts a gps week and seconds of the week to adjusted standard time.
+///
+/// # Examples
+///
+/// ```
+/// use las::gps_time;
+/// assert_eq!(-1e9, gps_time::week_to_standard(0, 0.));
+/// ```
+pub fn week_to_standard(week: u32, seconds_of_week: f64) -> f64 {
+    f64::from(week) * SECONDS_PER_WEEK + seconds_of_week - ADJUSTED_STANDARD_OFFSET
+}
+
+/// Converts adjusted standard time to a gps week and seconds of the week.
+///
+/// # Examples
+///
+/// ```
+/// use las::gps_time;
+/// assert_eq!((1, 1.5), gps_time::standard_to_week(604_801.5 - 1e9));
+/// ```
+pub fn standard_to_week(standard: f64) -> (u32, f64) {
+    let gps = standard + ADJUSTED_STANDARD_OFFSET;
+    let week = (gps / SECONDS_PER_WEEK).floor();
+    (week as u32, gps - week * SECONDS_PER_WEEK)
+}
+
+/// Converts standard (not adjusted) gps time to UTC.
+///
+/// # Examples
+///
+/// ```
+/// use chrono::{TimeZone, Utc};
+/// use las::gps_time;
+/// assert_eq!(Utc.with_ymd_and_hms(1980, 1, 6, 0, 0, 0).unwrap(), gps_time::gps_to_utc(0.).unwrap());
+/// ```
+pub fn gps_to_utc(gps: f64) -> Result<DateTime<Utc>> {
+    let leap_seconds = LEAP_SECONDS
+        .iter()
+        .enumerate()
+        .take_while(|&(i, &unix)| gps >= (unix - GPS_EPOCH + i as i64 + 1) as f64)
+        .count();
+    let unix = gps + GPS_EPOCH as f64 - leap_seconds as f64;
+    let seconds = unix.floor();
+    let nanoseconds = ((unix - seconds) * 1e9).round().min(999_999_999.);
+    if !seconds.is_finite() || seconds.abs() > i64::MAX as f64 {
+        return Err(Error::OutOfRange(gps).into());
+    }
+    DateTime::from_timestamp(seconds as i64, nanoseconds as u32)
+        .ok_or_else(|| Error::OutOfRange(gps).into())
+}
+
+/// Converts UTC to standard (not adjusted) gps time.
+///
+/// # Examples
+///
+/// ```
+/// use chrono::{TimeZone, Utc};
+/// use las::gps_time;
+/// let utc = Utc.with_ymd_and_hms(2017, 1, 1, 0, 0, 0).unwrap();
+/// assert_eq!(gps_time::gps_to_utc(gps_time::utc_to_gps(&utc)).unwrap(), utc);
+/// ```
+pub fn utc_to_gps(utc: &DateTime<Utc>) -> f64 {
+    let unix = utc.timestamp();
+    let leap_seconds = LEAP_SECONDS.iter().filter(|&&leap| unix >= leap).count();
+    (unix - GPS_EPOCH + leap_seconds as i64) as f64 + f64::from(utc.timestamp_subsec_nanos()) / 1e9
+}
+
+/// Converts adjusted standard time to UTC.
+///
+/// # Examples
+///
+/// ```
+/// use chrono::{TimeZone, Utc};
+/// use las::gps_time;
+/// let utc = gps_time::standard_to_utc(0.).unwrap();
+/// assert_eq!(Utc.with_ymd_and_hms(2011, 9, 14, 1, 46, 25).unwrap(), utc);
+/// ```
+pub fn standard_to_utc(standard: f64) -> Result<DateTime<Utc>> {
+    gps_to_utc(standard + ADJUSTED_STANDARD_OFFSET)
+}
+
+/// Converts UTC to adjusted standard time.
+///
+/// # Examples
+///
+/// ```
+/// use chrono::{TimeZone, Utc};
+/// use las::gps_time;
+/// let utc = Utc.with_ymd_and_hms(2011, 9, 14, 1, 46, 25).unwrap();
+/// assert_eq!(0., gps_time::utc_to_standard(&utc));
+/// ```
+pub fn utc_to_standard(utc: &DateTime<Utc>) -> f64 {
+    utc_to_gps(utc) - ADJUSTED_STANDARD_OFFSET
+}
+
+/// Converts a point's gps time to UTC.
+///
+/// Week time is relative to the given gps week.
+///
+/// # Examples
+///
+/// ```
+/// use chrono::{TimeZone, Utc};
+/// use las::{gps_time, GpsTimeType};
+/// let utc = gps_time::to_utc(86_400., GpsTimeType::Week, 2000).unwrap();
+/// assert_eq!(Utc.with_ymd_and_hms(2018, 5, 6, 23, 59, 42).unwrap(), utc);
+/// ```
+pub fn to_utc(gps_time: f64, gps_time_type: GpsTimeType, week: u32) -> Result<DateTime<Utc>> {
+    match gps_time_type {
+        GpsTimeType::Week => standard_to_utc(week_to_standard(week, gps_time)),
+        GpsTimeType::Standard => standard_to_utc(gps_time),
+    }
+}
+
+/// Copies the remaining points from a reader to a destination, converting their gps times from
+/// week time in the given gps week to adjusted standard time.
+///
+/// The new header has `GpsTimeType::Standard`, which requires las 1.2 or later. The destination is
+/// returned once the writer is closed.
+///
+/// # Examples
+///
+/// ```
+/// use las::{gps_time, GpsTimeType, Read, Reader};
+/// use std::io::Cursor;
+///
+/// let mut reader = Reader::from_path("tests/data/autzen.las").unwrap();
+/// let cursor = gps_time::convert_to_standard(&mut reader, Cursor::new(Vec::new()), 2000).unwrap();
+/// let reader = Reader::new(cursor).unwrap();
+/// assert_eq!(GpsTimeType::Standard, reader.header().gps_time_type());
+/// ```
+pub fn convert_to_standard<R: Read, W: 'static + std::io::Write + Seek + Debug + Send>(
+    reader: &mut R,
+    dest: W,
+    week: u32,
+) -> Result<W> {
+    if reader.header().gps_time_type().is_standard() {
+        return Err(Error::AlreadyStandard.into());
+    }
+    let mut builder = Builder::from(reader.header().clone());
+    builder.gps_time_type = GpsTimeType::Standard;
+    let header: Header = builder.into_header()?;
+    let mut writer = Writer::new(dest, header)?;
+    for point in reader.raw_points() {
+        let mut point = point?;
+        point.gps_time = point
+            .gps_time
+            .map(|gps_time| week_to_standard(week, gps_time));
+        writer.write_raw(point)?;
+    }
+    writer.into_inner()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Reader;
+    use chrono::TimeZone;
+    use std::io::Cursor;
+
+    #[test]
+    fn leap_seconds() {
+        // The last leap second was inserted at the end of 2016, when gps was 17 seconds ahead.
+        let before = Utc.with_ymd_and_hms(2016, 12, 31, 23, 59, 59).unwrap();
+        let after = Utc.with_ymd_and_hms(2017, 1, 1, 0, 0, 0).unwrap();
+        assert_eq!(2., utc_to_gps(&after) - utc_to_gps(&before));
+        assert_eq!(before, gps_to_utc(utc_to_gps(&before)).unwrap());
+        assert_eq!(after, gps_to_utc(utc_to_gps(&after)).unwrap());
+        assert_eq!(
+            18.,
+            utc_to_gps(&after) - (after.timestamp() - GPS_EPOCH) as f64
+        );
+    }
+
+    #[test]
+    fn fractional_seconds() {
+        let utc = gps_to_utc(1_000_000_000.25).unwrap();
+        assert_eq!(250_000_000, utc.timestamp_subsec_nanos());
+        assert_eq!(1_000_000_000.25, utc_to_gps(&utc));
+    }
+
+    #[test]
+    fn out_of_range() {
+        assert!(gps_to_utc(f64::NAN).is_err());
+        assert!(gps_to_utc(1e300).is_err());
+    }
+
+    #[test]
+    fn convert() {
+        let mut reader = Reader::from_path("tests/data/autzen.las").unwrap();
+        let expected = reader
+            .points()
+            .map(|point| week_to_standard(2000, point.unwrap().gps_time.unwrap()))
+            .collect::<Vec<_>>();
+        reader.seek(0).unwrap();
+        let cursor = convert_to_standard(&mut reader, Cursor::new(Vec::new()), 2000).unwrap();
+        let mut reader = Reader::new(cursor).unwrap();
+        let actual = reader
+            .points()
+            .map(|point| point.unwrap().gps_time.unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(expected, actual);
+        reader.seek(0).unwrap();
+        assert!(convert_to_standard(&mut reader, Cursor::new(Vec::new()), 2000).is_err());
+    }
+}
@@ -7,6 +7,7 @@ use crate::{raw, Bounds, GpsTimeType, Header, Result, Transform, Vector, Version
 
 /// Builds headers.
 #[derive(Clone, Debug, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Builder {
     /// The date of file creation.
     pub date: Option<NaiveDate>,
@@ -58,7 +59,7 @@ pub struct Builder {
 
     number_of_points_by_return: HashMap<u8, u64>,
     number_of_points: u64,
-    bounds: Bounds,
+    pub(crate) bounds: Bounds,
 }
 
 impl Builder {
@@ -160,6 +161,34 @@ impl Builder {
         })
     }
 
+    /// Sets the transforms so every point inside these bounds can be stored with this precision.
+    ///
+    /// The precision becomes each axis' scale, and the offsets are chosen with `Transform::fit`.
+    /// If the bounds are too large for the precision, the scale is coarsened by factors of ten.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::{Bounds, Builder, Vector};
+    ///
+    /// // UTM coordinates overflow the default transforms at millimeter precision.
+    /// let bounds = Bounds {
+    ///     min: Vector { x: 500_000., y: 5_000_000., z: 0. },
+    ///     max: Vector { x: 501_000., y: 5_001_000., z: 100. },
+    /// };
+    /// let mut builder = Builder::from((1, 4));
+    /// builder.fit_transforms(&bounds, 0.001);
+    /// assert_eq!(0.001, builder.transforms.y.scale);
+    /// assert!(builder.transforms.y.inverse(5_001_000.).is_ok());
+    /// ```
+    pub fn fit_transforms(&mut self, bounds: &Bounds, precision: f64) {
+        self.transforms = Vector {
+            x: Transform::fit(bounds.min.x, bounds.max.x, precision),
+            y: Transform::fit(bounds.min.y, bounds.max.y, precision),
+            z: Transform::fit(bounds.min.z, bounds.max.z, precision),
+        };
+    }
+
     /// Converts this builder into a `Header`.
     ///
     /// # Examples
@@ -275,6 +304,14 @@ impl From<Header> for Builder {
     }
 }
 
+impl TryFrom<Builder> for Header {
+    type Error = crate::Error;
+
+    fn try_from(builder: Builder) -> Result<Header> {
+        builder.into_header()
+    }
+}
+
 fn number_of_points_hash_map<T: Copy + Into<u64>>(slice: &[T]) -> HashMap<u8, u64> {
     use std::u8;
     assert!(slice.len() < u8::MAX as usize);
@@ -74,6 +74,23 @@ pub enum Error {
     #[allow(missing_docs)]
     Format { version: Version, format: Format },
 
+    /// The headers can't be merged because they describe different coordinate reference systems.
+    #[error("cannot merge headers with different coordinate reference systems")]
+    MergeCrs,
+
+    /// The headers can't be merged because their point formats differ.
+    #[error("cannot merge {format} with {other}")]
+    #[allow(missing_docs)]
+    MergeFormat { format: Format, other: Format },
+
+    /// The headers can't be merged because their gps time types differ.
+    #[error("cannot merge gps time type {gps_time_type:?} with {other:?}")]
+    #[allow(missing_docs)]
+    MergeGpsTimeType {
+        gps_time_type: GpsTimeType,
+        other: GpsTimeType,
+    },
+
     /// The offset to point data is too large.
     #[error("the offset to the point data is too large: {0}")]
     OffsetToPointDataTooLarge(usize),
@@ -117,7 +134,15 @@ pub enum Error {
 ///
 /// Headers include *all* las metadata, including regular and extended variable length records and
 /// any file padding (e.g. extra bytes after the header).
+///
+/// With the `serde` feature, headers are serialized as a `Builder` and checked with
+/// `Builder::into_header` when they're deserialized.
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(into = "Builder", try_from = "Builder")
+)]
 pub struct Header {
     bounds: Bounds,
     date: Option<NaiveDate>,
@@ -202,6 +227,144 @@ impl Header {
         self.bounds.grow(point);
     }
 
+    /// Adds a raw point to this header, using this header's transforms to grow the bounds.
+    pub(crate) fn add_raw_point(&mut self, point: &raw::Point) {
+        self.number_of_points += 1;
+        let return_number = point.flags.return_number();
+        if return_number > 0 {
+            *self
+                .number_of_points_by_return
+                .entry(return_number)
+                .or_insert(0) += 1;
+        }
+        self.bounds.grow(&Point {
+            x: self.transforms.x.direct(point.x),
+            y: self.transforms.y.direct(point.y),
+            z: self.transforms.z.direct(point.z),
+            ..Default::default()
+        });
+    }
+
+    /// Returns an error if points described by the other header can't be written with this one.
+    ///
+    /// Headers are compatible if they have the same point format (ignoring compression) and gps
+    /// time type, and if they don't describe different coordinate reference systems. Headers with
+    /// no crs records are compatible with any crs.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::{Builder, Header};
+    /// use las::point::Format;
+    ///
+    /// let header = Header::default();
+    /// assert!(header.verify_compatibility(&Header::default()).is_ok());
+    ///
+    /// let mut builder = Builder::default();
+    /// builder.point_format = Format::new(1).unwrap();
+    /// let other = builder.into_header().unwrap();
+    /// assert!(header.verify_compatibility(&other).is_err());
+    /// ```
+    pub fn verify_compatibility(&self, other: &Header) -> Result<()> {
+        let format = Format {
+            is_compressed: false,
+            ..self.point_format
+        };
+        let other_format = Format {
+            is_compressed: false,
+            ..other.point_format
+        };
+        if format != other_format {
+            return Err(Error::MergeFormat {
+                format,
+                other: other_format,
+            }
+            .into());
+        }
+        if self.gps_time_type != other.gps_time_type {
+            return Err(Error::MergeGpsTimeType {
+                gps_time_type: self.gps_time_type,
+                other: other.gps_time_type,
+            }
+            .into());
+        }
+        let crs = self.crs_vlrs();
+        let other_crs = other.crs_vlrs();
+        if !crs.is_empty() && !other_crs.is_empty() && crs != other_crs {
+            return Err(Error::MergeCrs.into());
+        }
+        Ok(())
+    }
+
+    /// Merges another header into this one, e.g. to write several files into one.
+    ///
+    /// The headers must be compatible (see `verify_compatibility`). Point counts and bounds are
+    /// summed and unioned, vlrs and evlrs that this header doesn't already have are appended, and
+    /// the version becomes the later of the two. If the transforms differ, the finer scale is
+    /// kept for each axis, and if this header's offset can't reach the merged bounds with that
+    /// scale, the axis is refit with `Transform::fit`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::{Header, Point};
+    ///
+    /// let mut header = Header::default();
+    /// header.add_point(&Point { x: 1., ..Default::default() });
+    /// let mut other = Header::from((1, 4));
+    /// other.add_point(&Point { x: 2., ..Default::default() });
+    ///
+    /// header.merge(&other).unwrap();
+    /// assert_eq!(2, header.number_of_points());
+    /// assert_eq!(2., header.bounds().max.x);
+    /// assert_eq!((1, 4), header.version().into());
+    /// ```
+    pub fn merge(&mut self, other: &Header) -> Result<()> {
+        self.verify_compatibility(other)?;
+        self.version = self.version.max(other.version);
+        self.number_of_points += other.number_of_points;
+        for (&return_number, &n) in &other.number_of_points_by_return {
+            *self
+                .number_of_points_by_return
+                .entry(return_number)
+                .or_insert(0) += n;
+        }
+        self.bounds.min.x = self.bounds.min.x.min(other.bounds.min.x);
+        self.bounds.min.y = self.bounds.min.y.min(other.bounds.min.y);
+        self.bounds.min.z = self.bounds.min.z.min(other.bounds.min.z);
+        self.bounds.max.x = self.bounds.max.x.max(other.bounds.max.x);
+        self.bounds.max.y = self.bounds.max.y.max(other.bounds.max.y);
+        self.bounds.max.z = self.bounds.max.z.max(other.bounds.max.z);
+        let merge = |transform: Transform, other: Transform, min: f64, max: f64| {
+            let transform = Transform {
+                scale: transform.scale.min(other.scale),
+                ..transform
+            };
+            if transform.inverse(min).is_ok() && transform.inverse(max).is_ok() {
+                transform
+            } else {
+                Transform::fit(min, max, transform.scale)
+            }
+        };
+        let (min, max) = (self.bounds.min, self.bounds.max);
+        self.transforms = Vector {
+            x: merge(self.transforms.x, other.transforms.x, min.x, max.x),
+            y: merge(self.transforms.y, other.transforms.y, min.y, max.y),
+            z: merge(self.transforms.z, other.transforms.z, min.z, max.z),
+        };
+        for vlr in &other.vlrs {
+            if !vlr.is_laszip() && !self.vlrs.contains(vlr) {
+                self.vlrs.push(vlr.clone());
+            }
+        }
+        for evlr in &other.evlrs {
+            if !self.evlrs.contains(evlr) {
+                self.evlrs.push(evlr.clone());
+            }
+        }
+        Ok(())
+    }
+
     /// Returns this header's file source id.
     ///
     /// For airborne data, this is often the flight line number.
@@ -529,6 +692,15 @@ impl Header {
         })
     }
 
+    fn crs_vlrs(&self) -> Vec<&Vlr> {
+        self.all_vlrs()
+            .filter(|vlr| {
+                vlr.user_id == "LASF_Projection"
+                    || (vlr.user_id == "liblas" && vlr.record_id == 2112)
+            })
+            .collect()
+    }
+
     fn global_encoding(&self) -> u16 {
         let mut bits = self.gps_time_type.into();
         if self.has_synthetic_return_numbers {
@@ -887,6 +1059,88 @@ mod tests {
         assert!(builder.into_header().unwrap().into_raw().is_err());
     }
 
+    #[test]
+    fn merge_incompatible_format() {
+        let mut builder = Builder::default();
+        builder.point_format = Format::new(1).unwrap();
+        let other = builder.into_header().unwrap();
+        assert!(Header::default().merge(&other).is_err());
+    }
+
+    #[test]
+    fn merge_incompatible_gps_time_type() {
+        let mut builder = Builder::from((1, 2));
+        builder.gps_time_type = GpsTimeType::Standard;
+        let other = builder.into_header().unwrap();
+        assert!(Header::from((1, 2)).merge(&other).is_err());
+    }
+
+    #[test]
+    fn merge_crs() {
+        let crs = |data: Vec<u8>| {
+            let mut builder = Builder::default();
+            builder.vlrs.push(Vlr {
+                user_id: "LASF_Projection".to_string(),
+                record_id: 2112,
+                data,
+                ..Default::default()
+            });
+            builder.into_header().unwrap()
+        };
+        let mut header = crs(b"a".to_vec());
+        assert!(header.merge(&Header::default()).is_ok());
+        assert!(header.merge(&crs(b"a".to_vec())).is_ok());
+        assert_eq!(1, header.vlrs().len());
+        assert!(header.merge(&crs(b"b".to_vec())).is_err());
+    }
+
+    #[test]
+    fn merge_counts_and_vlrs() {
+        let mut header = Header::default();
+        header.add_point(&Point {
+            return_number: 1,
+            ..Default::default()
+        });
+        let mut builder = Builder::default();
+        builder.vlrs.push(Vlr::default());
+        builder.transforms.y.scale = 0.0001;
+        let mut other = builder.into_header().unwrap();
+        other.add_point(&Point {
+            y: -1.,
+            return_number: 1,
+            ..Default::default()
+        });
+        header.merge(&other).unwrap();
+        assert_eq!(2, header.number_of_points());
+        assert_eq!(Some(2), header.number_of_points_by_return(1));
+        assert_eq!(-1., header.bounds().min.y);
+        assert_eq!(0.0001, header.transforms().y.scale);
+        assert_eq!(0.001, header.transforms().x.scale);
+        assert_eq!(1, header.vlrs().len());
+    }
+
+    #[test]
+    fn merge_refits_offsets() {
+        let mut header = Header::default();
+        header.add_point(&Point {
+            x: 2e6,
+            ..Default::default()
+        });
+        let mut builder = Builder::default();
+        builder.transforms.x.offset = 3e6;
+        let mut other = builder.into_header().unwrap();
+        other.add_point(&Point {
+            x: 3e6,
+            ..Default::default()
+        });
+        header.merge(&other).unwrap();
+        let transform = header.transforms().x;
+        assert_eq!(0.001, transform.scale);
+        assert!(transform.inverse(2e6).is_ok());
+        assert!(transform.inverse(3e6).is_ok());
+        assert_eq!(0., header.transforms().y.offset);
+    }
+
     #[test]
     fn offset_to_point_data_too_large() {
         use std::u32;
@@ -160,14 +160,31 @@ extern crate laz;
 #[cfg(feature = "laz")]
 mod compression;
 
+pub mod class_map;
+pub mod decimate;
+pub mod dedup;
+pub mod density;
+#[cfg(feature = "e57")]
+pub mod e57;
+pub mod extra_bytes;
 pub mod feature;
+pub mod filter;
+pub mod gps_time;
 pub mod header;
+pub mod pcd;
+pub mod ply;
 pub mod point;
 pub mod raw;
 pub mod reader;
+pub mod sort;
+pub mod stats;
+pub mod text;
+pub mod tiling;
+pub mod trajectory;
 pub mod vlr;
 pub mod writer;
 
+mod affine;
 mod bounds;
 mod color;
 mod error;
@@ -177,14 +194,17 @@ mod utils;
 mod vector;
 mod version;
 
+pub use crate::affine::Affine;
 pub use crate::bounds::Bounds;
 pub use crate::color::Color;
 pub use crate::error::Error;
 pub use crate::feature::Feature;
+pub use crate::filter::Filter;
 pub use crate::gps_time_type::GpsTimeType;
 pub use crate::header::{Builder, Header};
 pub use crate::point::Point;
 pub use crate::reader::{Read, Reader};
+pub use crate::tiling::TilingWriter;
 pub use crate::transform::Transform;
 pub use crate::vector::Vector;
 pub use crate::version::Version;
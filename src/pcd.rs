@@ -0,0 +1,656 @@
+//! Read and write Point Cloud Library PCD files.
+//!
+//! Fields are mapped to `Point` fields by name: `x`, `y`, and `z`; `intensity`, which is rounded
+//! and clamped to the `u16` range; and `rgb` or `rgba`, PCL's `0xAARRGGBB` color word, which is
+//! scaled from eight to sixteen bits per channel. The color word is read as raw bits, or as an
+//! integer in ascii data, since opaque colors are NaNs when they're read as floats. Other fields
+//! are skipped. Ascii and binary data are supported, but `binary_compressed` is not.
+//!
+//! Organized clouds mark missing points with NaN coordinates, which las can't store, so points
+//! with a non-finite x, y, or z are skipped when reading.
+//!
+//! ```
+//! use las::pcd::{Encoding, PcdReader, PcdWriter};
+//! use las::{Read, Reader, Write};
+//!
+//! let mut reader = Reader::from_path("tests/data/autzen.las").unwrap();
+//! let mut writer = PcdWriter::new(Vec::new(), reader.header().clone(), Encoding::Binary).unwrap();
+//! for point in reader.points() {
+//!     writer.write(point.unwrap()).unwrap();
+//! }
+//! let pcd = writer.into_inner().unwrap();
+//!
+//! let reader = PcdReader::new(pcd.as_slice()).unwrap();
+//! let (header, points) = reader.read_to_end(0.01).unwrap();
+//! assert_eq!(106, points.len());
+//! ```
+
+use crate::point::Format;
+use crate::{Bounds, Builder, Color, Header, Point, Result, Write};
+use byteorder::{ByteOrder, LittleEndian};
+use std::io::BufRead;
+use thiserror::Error;
+
+/// PCD errors.
+#[derive(Error, Debug)]
+pub enum Error {
+    /// A header line couldn't be understood.
+    #[error("invalid pcd header line: {0}")]
+    HeaderLine(String),
+
+    /// The header is missing a required line.
+    #[error("the pcd header has no {0} line")]
+    MissingHeaderLine(&'static str),
+
+    /// The FIELDS, SIZE, TYPE, and COUNT lines have different lengths.
+    #[error("the pcd field descriptions have different lengths")]
+    FieldDescriptions,
+
+    /// A field has a size and type combination that isn't a number.
+    #[error("invalid pcd field type: {type_} with size {size}")]
+    #[allow(missing_docs)]
+    FieldType { type_: char, size: usize },
+
+    /// The data encoding isn't supported.
+    #[error("unsupported pcd data encoding: {0}")]
+    Encoding(String),
+
+    /// An ascii value couldn't be parsed.
+    #[error("invalid pcd value: {0}")]
+    Value(String),
+
+    /// An ascii point has too few values.
+    #[error("a pcd point has too few values")]
+    TooFewValues,
+
+    /// The pcd writer is closed.
+    #[error("the pcd writer is closed")]
+    Closed,
+}
+
+/// How the pcd data are encoded.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Encoding {
+    /// Whitespace-separated text, one point per line.
+    Ascii,
+
+    /// Little-endian binary records.
+    Binary,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Target {
+    X,
+    Y,
+    Z,
+    Intensity,
+    Rgb,
+    Skip,
+}
+
+#[derive(Clone, Copy, Debug)]
+struct Field {
+    target: Target,
+    type_: char,
+    size: usize,
+    count: usize,
+}
+
+impl Field {
+    fn decode(&self, bytes: &[u8]) -> f64 {
+        match (self.type_, self.size) {
+            ('I', 1) => f64::from(bytes[0] as i8),
+            ('I', 2) => f64::from(LittleEndian::read_i16(bytes)),
+            ('I', 4) => f64::from(LittleEndian::read_i32(bytes)),
+            ('I', _) => LittleEndian::read_i64(bytes) as f64,
+            ('U', 1) => f64::from(bytes[0]),
+            ('U', 2) => f64::from(LittleEndian::read_u16(bytes)),
+            ('U', 4) => f64::from(LittleEndian::read_u32(bytes)),
+            ('U', _) => LittleEndian::read_u64(bytes) as f64,
+            ('F', 4) => f64::from(LittleEndian::read_f32(bytes)),
+            _ => LittleEndian::read_f64(bytes),
+        }
+    }
+
+    fn packed_bits(&self, bytes: &[u8]) -> u32 {
+        // Packed colors with alpha 255 are NaNs as floats, so they never go through a float.
+        if self.size == 4 {
+            LittleEndian::read_u32(bytes)
+        } else {
+            self.decode(bytes) as u32
+        }
+    }
+}
+
+fn packed_color(bits: u32) -> Color {
+    let channel = |shift: u32| u16::from((bits >> shift) as u8) * 257;
+    Color::new(channel(16), channel(8), channel(0))
+}
+
+/// Reads points from PCD data.
+///
+/// Points with a non-finite x, y, or z, e.g. the NaN placeholders in organized clouds, are
+/// skipped.
+#[derive(Debug)]
+pub struct PcdReader<R: BufRead> {
+    read: R,
+    encoding: Encoding,
+    fields: Vec<Field>,
+    remaining: u64,
+}
+
+impl<R: BufRead> PcdReader<R> {
+    /// Reads the pcd header.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::pcd::PcdReader;
+    /// let pcd = "VERSION 0.7\nFIELDS x y z\nSIZE 4 4 4\nTYPE F F F\nCOUNT 1 1 1\nWIDTH 1\nHEIGHT 1\nPOINTS 1\nDATA ascii\n1 2 3\n";
+    /// let mut reader = PcdReader::new(pcd.as_bytes()).unwrap();
+    /// assert_eq!(2., reader.next().unwrap().unwrap().y);
+    /// ```
+    pub fn new(mut read: R) -> Result<PcdReader<R>> {
+        let mut names = None;
+        let mut sizes = None;
+        let mut types = None;
+        let mut counts = None;
+        let mut width = None;
+        let mut height = None;
+        let mut points = None;
+        let mut line = String::new();
+        let encoding = loop {
+            line.clear();
+            if read.read_line(&mut line)? == 0 {
+                return Err(Error::MissingHeaderLine("DATA").into());
+            }
+            let error = || Error::HeaderLine(line.trim_end().to_string());
+            let mut words = line.split_whitespace();
+            let keyword = match words.next() {
+                Some(keyword) if !keyword.starts_with('#') => keyword,
+                _ => continue,
+            };
+            let words = words.collect::<Vec<_>>();
+            let numbers = || {
+                words
+                    .iter()
+                    .map(|word| word.parse::<u64>().map_err(|_| error()))
+                    .collect::<std::result::Result<Vec<_>, _>>()
+            };
+            let number = || match numbers()?.as_slice() {
+                [n] => Ok(*n),
+                _ => Err(error()),
+            };
+            match keyword {
+                "VERSION" | "VIEWPOINT" => {}
+                "FIELDS" => names = Some(words.iter().map(|s| s.to_string()).collect::<Vec<_>>()),
+                "SIZE" => sizes = Some(numbers()?),
+                "TYPE" => {
+                    types = Some(
+                        words
+                            .iter()
+                            .map(|word| match word.as_bytes() {
+                                [c] => Ok(char::from(*c)),
+                                _ => Err(error()),
+                            })
+                            .collect::<std::result::Result<Vec<_>, _>>()?,
+                    )
+                }
+                "COUNT" => counts = Some(numbers()?),
+                "WIDTH" => width = Some(number()?),
+                "HEIGHT" => height = Some(number()?),
+                "POINTS" => points = Some(number()?),
+                "DATA" => match words.as_slice() {
+                    ["ascii"] => break Encoding::Ascii,
+                    ["binary"] => break Encoding::Binary,
+                    _ => return Err(Error::Encoding(words.join(" ")).into()),
+                },
+                _ => return Err(error().into()),
+            }
+        };
+        let names = names.ok_or(Error::MissingHeaderLine("FIELDS"))?;
+        let sizes = sizes.ok_or(Error::MissingHeaderLine("SIZE"))?;
+        let types = types.ok_or(Error::MissingHeaderLine("TYPE"))?;
+        let counts = counts.unwrap_or_else(|| vec![1; names.len()]);
+        if sizes.len() != names.len() || types.len() != names.len() || counts.len() != names.len() {
+            return Err(Error::FieldDescriptions.into());
+        }
+        let mut fields = Vec::with_capacity(names.len());
+        for (((name, &size), &type_), &count) in names.iter().zip(&sizes).zip(&types).zip(&counts) {
+            let size = size as usize;
+            match (type_, size) {
+                ('I', 1) | ('I', 2) | ('I', 4) | ('I', 8) => {}
+                ('U', 1) | ('U', 2) | ('U', 4) | ('U', 8) => {}
+                ('F', 4) | ('F', 8) => {}
+                _ => return Err(Error::FieldType { type_, size }.into()),
+            }
+            let target = if count != 1 {
+                Target::Skip
+            } else {
+                match name.as_str() {
+                    "x" => Target::X,
+                    "y" => Target::Y,
+                    "z" => Target::Z,
+                    "intensity" => Target::Intensity,
+                    "rgb" | "rgba" => Target::Rgb,
+                    _ => Target::Skip,
+                }
+            };
+            fields.push(Field {
+                target,
+                type_,
+                size,
+                count: count as usize,
+            });
+        }
+        let remaining = match (points, width, height) {
+            (Some(points), _, _) => points,
+            (None, Some(width), Some(height)) => width * height,
+            _ => return Err(Error::MissingHeaderLine("POINTS").into()),
+        };
+        Ok(PcdReader {
+            read,
+            encoding,
+            fields,
+            remaining,
+        })
+    }
+
+    /// Reads every remaining point and builds a header for them.
+    ///
+    /// The coordinates are stored with the given precision, e.g. 0.001 for millimeters.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::pcd::PcdReader;
+    /// let pcd = "FIELDS x y z rgb\nSIZE 4 4 4 4\nTYPE F F F U\nCOUNT 1 1 1 1\nPOINTS 1\nDATA ascii\n1 2 3 16711680\n";
+    /// let (header, points) = PcdReader::new(pcd.as_bytes()).unwrap().read_to_end(0.001).unwrap();
+    /// assert!(header.point_format().has_color);
+    /// assert_eq!(65535, points[0].color.unwrap().red);
+    /// ```
+    pub fn read_to_end(mut self, precision: f64) -> Result<(Header, Vec<Point>)> {
+        let points = self.by_ref().collect::<Result<Vec<_>>>()?;
+        let has_color = self.fields.iter().any(|field| field.target == Target::Rgb);
+        let mut builder = Builder::from((1, 2));
+        builder.point_format = Format::new(if has_color { 2 } else { 0 })?;
+        let mut bounds = Bounds::default();
+        for point in &points {
+            bounds.grow(point);
+        }
+        if !points.is_empty() {
+            builder.fit_transforms(&bounds, precision);
+            builder.bounds = bounds;
+        }
+        Ok((builder.into_header()?, points))
+    }
+
+    fn read_point(&mut self) -> Result<Point> {
+        let mut point = Point::default();
+        match self.encoding {
+            Encoding::Ascii => {
+                let mut line = String::new();
+                while line.trim().is_empty() {
+                    line.clear();
+                    if self.read.read_line(&mut line)? == 0 {
+                        return Err(Error::TooFewValues.into());
+                    }
+                }
+                let mut words = line.split_whitespace();
+                for field in &self.fields {
+                    for _ in 0..field.count {
+                        let word = words.next().ok_or(Error::TooFewValues)?;
+                        let error = || Error::Value(word.to_string());
+                        match field.target {
+                            Target::Skip => {}
+                            Target::Rgb => {
+                                // PCL writes the packed word as an integer, but older files
+                                // have it as a float.
+                                let bits = match word.parse::<u32>() {
+                                    Ok(bits) => bits,
+                                    Err(_) => word.parse::<f32>().map_err(|_| error())?.to_bits(),
+                                };
+                                point.color = Some(packed_color(bits));
+                            }
+                            _ => {
+                                let value = word.parse::<f64>().map_err(|_| error())?;
+                                set(&mut point, field, value);
+                            }
+                        }
+                    }
+                }
+            }
+            Encoding::Binary => {
+                let mut bytes = [0; 8];
+                for field in &self.fields {
+                    for _ in 0..field.count {
+                        self.read.read_exact(&mut bytes[..field.size])?;
+                        if field.target == Target::Rgb {
+                            point.color = Some(packed_color(field.packed_bits(&bytes)));
+                        } else {
+                            set(&mut point, field, field.decode(&bytes));
+                        }
+                    }
+                }
+            }
+        }
+        Ok(point)
+    }
+}
+
+fn set(point: &mut Point, field: &Field, value: f64) {
+    match field.target {
+        Target::X => point.x = value,
+        Target::Y => point.y = value,
+        Target::Z => point.z = value,
+        Target::Intensity => point.intensity = value.round().clamp(0., 65535.) as u16,
+        Target::Rgb | Target::Skip => {}
+    }
+}
+
+impl<R: BufRead> Iterator for PcdReader<R> {
+    type Item = Result<Point>;
+
+    fn next(&mut self) -> Option<Result<Point>> {
+        while self.remaining > 0 {
+            self.remaining -= 1;
+            match self.read_point() {
+                Ok(point)
+                    if !(point.x.is_finite() && point.y.is_finite() && point.z.is_finite()) => {}
+                result => return Some(result),
+            }
+        }
+        None
+    }
+}
+
+/// Writes points to PCD data.
+///
+/// Points get double x, y, and z fields, a float intensity, and, if the header's point format has
+/// color, an opaque packed rgb, written as an integer in ascii data like PCL does. PCD needs the
+/// point count before the points, so the encoded points are held in memory and written when the
+/// writer is closed.
+#[derive(Debug)]
+pub struct PcdWriter<W: std::io::Write> {
+    write: Option<W>,
+    header: Header,
+    encoding: Encoding,
+    data: Vec<u8>,
+    count: u64,
+    closed: bool,
+}
+
+impl<W: std::io::Write> PcdWriter<W> {
+    /// Creates a pcd writer.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::pcd::{Encoding, PcdWriter};
+    /// use las::Header;
+    /// let writer = PcdWriter::new(Vec::new(), Header::default(), Encoding::Ascii).unwrap();
+    /// ```
+    pub fn new(write: W, header: Header, encoding: Encoding) -> Result<PcdWriter<W>> {
+        Ok(PcdWriter {
+            write: Some(write),
+            header,
+            encoding,
+            data: Vec::new(),
+            count: 0,
+            closed: false,
+        })
+    }
+
+    /// Writes the pcd header and the points.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::pcd::{Encoding, PcdWriter};
+    /// use las::Header;
+    /// let mut writer = PcdWriter::new(Vec::new(), Header::default(), Encoding::Ascii).unwrap();
+    /// writer.close().unwrap();
+    /// assert!(writer.close().is_err());
+    /// ```
+    pub fn close(&mut self) -> Result<()> {
+        if self.closed {
+            return Err(Error::Closed.into());
+        }
+        self.closed = true;
+        let mut write = self.write.take().expect("an open writer has a write");
+        let result = self.write_to(&mut write);
+        self.write = Some(write);
+        result
+    }
+
+    /// Closes the writer, if needed, and returns the underlying writer.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::pcd::{Encoding, PcdWriter};
+    /// use las::Header;
+    /// let writer = PcdWriter::new(Vec::new(), Header::default(), Encoding::Ascii).unwrap();
+    /// let pcd = writer.into_inner().unwrap();
+    /// assert!(pcd.starts_with(b"# .PCD v0.7"));
+    /// ```
+    pub fn into_inner(mut self) -> Result<W> {
+        if !self.closed {
+            self.close()?;
+        }
+        Ok(self.write.take().expect("the writer was just closed"))
+    }
+
+    fn write_to(&self, write: &mut W) -> Result<()> {
+        let has_color = self.header.point_format().has_color;
+        writeln!(write, "# .PCD v0.7 - Point Cloud Data file format")?;
+        writeln!(write, "VERSION 0.7")?;
+        if has_color {
+            writeln!(write, "FIELDS x y z intensity rgb")?;
+            writeln!(write, "SIZE 8 8 8 4 4")?;
+            writeln!(write, "TYPE F F F F F")?;
+            writeln!(write, "COUNT 1 1 1 1 1")?;
+        } else {
+            writeln!(write, "FIELDS x y z intensity")?;
+            writeln!(write, "SIZE 8 8 8 4")?;
+            writeln!(write, "TYPE F F F F")?;
+            writeln!(write, "COUNT 1 1 1 1")?;
+        }
+        writeln!(write, "WIDTH {}", self.count)?;
+        writeln!(write, "HEIGHT 1")?;
+        writeln!(write, "VIEWPOINT 0 0 0 1 0 0 0")?;
+        writeln!(write, "POINTS {}", self.count)?;
+        match self.encoding {
+            Encoding::Ascii => writeln!(write, "DATA ascii")?,
+            Encoding::Binary => writeln!(write, "DATA binary")?,
+        }
+        write.write_all(&self.data)?;
+        Ok(())
+    }
+}
+
+impl<W: std::io::Write> Write for PcdWriter<W> {
+    fn header(&self) -> &Header {
+        &self.header
+    }
+
+    fn write(&mut self, point: Point) -> Result<()> {
+        if self.closed {
+            return Err(Error::Closed.into());
+        }
+        let intensity = f32::from(point.intensity);
+        let rgb = if self.header.point_format().has_color {
+            let color = point.color.unwrap_or_default();
+            let channel = |value: u16| (u32::from(value) + 128) / 257;
+            Some(
+                0xff00_0000
+                    | channel(color.red) << 16
+                    | channel(color.green) << 8
+                    | channel(color.blue),
+            )
+        } else {
+            None
+        };
+        match self.encoding {
+            Encoding::Ascii => {
+                let mut fields = vec![
+                    point.x.to_string(),
+                    point.y.to_string(),
+                    point.z.to_string(),
+                    intensity.to_string(),
+                ];
+                if let Some(rgb) = rgb {
+                    // Like PCL, the packed word is written as an integer, not as a float.
+                    fields.push(rgb.to_string());
+                }
+                self.data.extend_from_slice(fields.join(" ").as_bytes());
+                self.data.push(b'\n');
+            }
+            Encoding::Binary => {
+                let mut bytes = [0; 8];
+                for value in [point.x, point.y, point.z] {
+                    LittleEndian::write_f64(&mut bytes, value);
+                    self.data.extend_from_slice(&bytes);
+                }
+                LittleEndian::write_f32(&mut bytes, intensity);
+                self.data.extend_from_slice(&bytes[..4]);
+                if let Some(rgb) = rgb {
+                    LittleEndian::write_u32(&mut bytes, rgb);
+                    self.data.extend_from_slice(&bytes[..4]);
+                }
+            }
+        }
+        self.count += 1;
+        Ok(())
+    }
+}
+
+impl<W: std::io::Write> Drop for PcdWriter<W> {
+    fn drop(&mut self) {
+        if !self.closed {
+            self.close().expect("Error when dropping the writer");
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Read, Reader};
+
+    fn roundtrip(encoding: Encoding) -> (Header, Vec<Point>) {
+        let mut reader = Reader::from_path("tests/data/autzen.las").unwrap();
+        let mut builder = Builder::from(reader.header().clone());
+        builder.point_format = Format::new(2).unwrap();
+        let header = builder.into_header().unwrap();
+        let mut writer = PcdWriter::new(Vec::new(), header, encoding).unwrap();
+        for (i, point) in reader.points().enumerate() {
+            let mut point = point.unwrap();
+            point.color = Some(Color::new(257 * (i as u16), 65535, 0));
+            writer.write(point).unwrap();
+        }
+        let pcd = writer.into_inner().unwrap();
+        PcdReader::new(pcd.as_slice())
+            .unwrap()
+            .read_to_end(0.01)
+            .unwrap()
+    }
+
+    #[test]
+    fn roundtrips() {
+        let expected = Reader::from_path("tests/data/autzen.las")
+            .unwrap()
+            .points()
+            .map(|point| point.unwrap())
+            .collect::<Vec<_>>();
+        for &encoding in &[Encoding::Ascii, Encoding::Binary] {
+            let (header, points) = roundtrip(encoding);
+            assert_eq!(2, header.point_format().to_u8().unwrap());
+            assert_eq!(expected.len(), points.len());
+            for (i, (point, expected)) in points.iter().zip(&expected).enumerate() {
+                assert_eq!(expected.x, point.x);
+                assert_eq!(expected.y, point.y);
+                assert_eq!(expected.z, point.z);
+                assert_eq!(expected.intensity, point.intensity);
+                assert_eq!(Some(Color::new(257 * (i as u16), 65535, 0)), point.color);
+            }
+        }
+    }
+
+    #[test]
+    fn opaque_colors() {
+        // Alpha 255 and red 144 make the packed word a NaN when it's read as a float.
+        let bits: u32 = 0xff90_2030;
+        let header = "FIELDS x y z rgb\nSIZE 4 4 4 4\nTYPE F F F F\nCOUNT 1 1 1 1\nPOINTS 1\n";
+        let expected = Some(Color::new(144 * 257, 32 * 257, 48 * 257));
+
+        let mut pcd = format!("{}DATA binary\n", header).into_bytes();
+        for value in [1f32, 2., 3.] {
+            pcd.extend_from_slice(&value.to_le_bytes());
+        }
+        pcd.extend_from_slice(&bits.to_le_bytes());
+        let point = PcdReader::new(pcd.as_slice())
+            .unwrap()
+            .next()
+            .unwrap()
+            .unwrap();
+        assert_eq!(expected, point.color);
+
+        let pcd = format!("{}DATA ascii\n1 2 3 {}\n", header, bits);
+        let point = PcdReader::new(pcd.as_bytes())
+            .unwrap()
+            .next()
+            .unwrap()
+            .unwrap();
+        assert_eq!(expected, point.color);
+
+        let mut builder = Builder::from((1, 2));
+        builder.point_format = Format::new(2).unwrap();
+        let header = builder.into_header().unwrap();
+        let mut writer = PcdWriter::new(Vec::new(), header, Encoding::Ascii).unwrap();
+        writer
+            .write(Point {
+                color: expected,
+                ..Default::default()
+            })
+            .unwrap();
+        let pcd = String::from_utf8(writer.into_inner().unwrap()).unwrap();
+        assert!(pcd.ends_with(&format!(" {}\n", bits)));
+    }
+
+    #[test]
+    fn skips_non_finite_points() {
+        let pcd = "FIELDS x y z\nSIZE 4 4 4\nTYPE F F F\nCOUNT 1 1 1\nWIDTH 3\nHEIGHT 1\nDATA ascii\n1 2 3\nnan nan nan\n4 5 inf\n";
+        let (header, points) = PcdReader::new(pcd.as_bytes())
+            .unwrap()
+            .read_to_end(0.001)
+            .unwrap();
+        assert_eq!(1, points.len());
+        assert_eq!(1., points[0].x);
+        assert_eq!(3., header.bounds().max.z);
+    }
+
+    #[test]
+    fn skips_unknown_fields() {
+        let pcd = "FIELDS x normal y z\nSIZE 4 4 4 4\nTYPE F F F F\nCOUNT 1 3 1 1\nWIDTH 1\nHEIGHT 1\nDATA ascii\n1 0 0 1 2 3\n";
+        let points = PcdReader::new(pcd.as_bytes())
+            .unwrap()
+            .collect::<Result<Vec<_>>>()
+            .unwrap();
+        assert_eq!(1, points.len());
+        assert_eq!(2., points[0].y);
+        assert_eq!(3., points[0].z);
+    }
+
+    #[test]
+    fn binary_compressed() {
+        let pcd = "FIELDS x y z\nSIZE 4 4 4\nTYPE F F F\nPOINTS 0\nDATA binary_compressed\n";
+        assert!(PcdReader::new(pcd.as_bytes()).is_err());
+    }
+
+    #[test]
+    fn mismatched_fields() {
+        let pcd = "FIELDS x y z\nSIZE 4 4\nTYPE F F F\nPOINTS 0\nDATA ascii\n";
+        assert!(PcdReader::new(pcd.as_bytes()).is_err());
+    }
+}
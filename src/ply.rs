@@ -0,0 +1,808 @@
+//! Read and write PLY point clouds.
+//!
+//! Vertex properties are mapped to `Point` fields: `x`, `y`, and `z`; `red`, `green`, and `blue`,
+//! which are scaled from eight to sixteen bits if they're bytes; `intensity`; `classification`;
+//! and `gps_time`. Every other scalar property becomes a typed extra bytes attribute, described
+//! by an extra bytes vlr. Elements other than `vertex`, e.g. faces, are skipped.
+//!
+//! ```
+//! use las::ply::{Encoding, PlyReader, PlyWriter};
+//! use las::{Read, Reader, Write};
+//!
+//! let mut reader = Reader::from_path("tests/data/autzen.las").unwrap();
+//! let mut writer =
+//!     PlyWriter::new(Vec::new(), reader.header().clone(), Encoding::BinaryLittleEndian).unwrap();
+//! for point in reader.points() {
+//!     writer.write(point.unwrap()).unwrap();
+//! }
+//! let ply = writer.into_inner().unwrap();
+//!
+//! let reader = PlyReader::new(ply.as_slice()).unwrap();
+//! let (header, points) = reader.read_to_end(0.01).unwrap();
+//! assert_eq!(106, points.len());
+//! ```
+
+use crate::extra_bytes::{self, DataType, Descriptor};
+use crate::point::{Classification, Format};
+use crate::{Bounds, Builder, Color, Header, Point, Result, Write};
+use byteorder::{BigEndian, ByteOrder, LittleEndian, ReadBytesExt, WriteBytesExt};
+use std::io::BufRead;
+use thiserror::Error;
+
+/// PLY errors.
+#[derive(Error, Debug)]
+pub enum Error {
+    /// The data don't start with `ply`.
+    #[error("the data are not ply")]
+    Magic,
+
+    /// A header line couldn't be understood.
+    #[error("invalid ply header line: {0}")]
+    HeaderLine(String),
+
+    /// The ply data have no vertex element.
+    #[error("the ply data have no vertex element")]
+    NoVertices,
+
+    /// A vertex property has a type that can't be stored in a point.
+    #[error("the {0} property is a list")]
+    ListProperty(String),
+
+    /// An ascii value couldn't be parsed.
+    #[error("invalid ply value: {0}")]
+    Value(String),
+
+    /// An ascii element has too few values.
+    #[error("a ply element has too few values")]
+    TooFewValues,
+
+    /// The ply writer is closed.
+    #[error("the ply writer is closed")]
+    Closed,
+}
+
+/// How the ply body is encoded.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Encoding {
+    /// Whitespace-separated text, one element per line.
+    Ascii,
+
+    /// Little-endian binary.
+    BinaryLittleEndian,
+
+    /// Big-endian binary.
+    BinaryBigEndian,
+}
+
+impl Encoding {
+    fn name(&self) -> &'static str {
+        match *self {
+            Encoding::Ascii => "ascii",
+            Encoding::BinaryLittleEndian => "binary_little_endian",
+            Encoding::BinaryBigEndian => "binary_big_endian",
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Scalar {
+    I8,
+    U8,
+    I16,
+    U16,
+    I32,
+    U32,
+    F32,
+    F64,
+}
+
+impl Scalar {
+    fn from_name(name: &str) -> Option<Scalar> {
+        Some(match name {
+            "char" | "int8" => Scalar::I8,
+            "uchar" | "uint8" => Scalar::U8,
+            "short" | "int16" => Scalar::I16,
+            "ushort" | "uint16" => Scalar::U16,
+            "int" | "int32" => Scalar::I32,
+            "uint" | "uint32" => Scalar::U32,
+            "float" | "float32" => Scalar::F32,
+            "double" | "float64" => Scalar::F64,
+            _ => return None,
+        })
+    }
+
+    fn from_data_type(data_type: DataType) -> Option<Scalar> {
+        Some(match data_type {
+            DataType::U8 => Scalar::U8,
+            DataType::I8 => Scalar::I8,
+            DataType::U16 => Scalar::U16,
+            DataType::I16 => Scalar::I16,
+            DataType::U32 => Scalar::U32,
+            DataType::I32 => Scalar::I32,
+            DataType::F32 => Scalar::F32,
+            DataType::F64 | DataType::U64 | DataType::I64 => Scalar::F64,
+            DataType::Undocumented(_) | DataType::Deprecated(_) => return None,
+        })
+    }
+
+    fn name(&self) -> &'static str {
+        match *self {
+            Scalar::I8 => "char",
+            Scalar::U8 => "uchar",
+            Scalar::I16 => "short",
+            Scalar::U16 => "ushort",
+            Scalar::I32 => "int",
+            Scalar::U32 => "uint",
+            Scalar::F32 => "float",
+            Scalar::F64 => "double",
+        }
+    }
+
+    fn data_type(&self) -> DataType {
+        match *self {
+            Scalar::I8 => DataType::I8,
+            Scalar::U8 => DataType::U8,
+            Scalar::I16 => DataType::I16,
+            Scalar::U16 => DataType::U16,
+            Scalar::I32 => DataType::I32,
+            Scalar::U32 => DataType::U32,
+            Scalar::F32 => DataType::F32,
+            Scalar::F64 => DataType::F64,
+        }
+    }
+
+    fn read<B: ByteOrder, R: std::io::Read>(&self, mut read: R) -> Result<f64> {
+        Ok(match *self {
+            Scalar::I8 => f64::from(read.read_i8()?),
+            Scalar::U8 => f64::from(read.read_u8()?),
+            Scalar::I16 => f64::from(read.read_i16::<B>()?),
+            Scalar::U16 => f64::from(read.read_u16::<B>()?),
+            Scalar::I32 => f64::from(read.read_i32::<B>()?),
+            Scalar::U32 => f64::from(read.read_u32::<B>()?),
+            Scalar::F32 => f64::from(read.read_f32::<B>()?),
+            Scalar::F64 => read.read_f64::<B>()?,
+        })
+    }
+
+    fn write<B: ByteOrder, W: std::io::Write>(&self, mut write: W, value: f64) -> Result<()> {
+        match *self {
+            Scalar::I8 => write.write_i8(value as i8)?,
+            Scalar::U8 => write.write_u8(value as u8)?,
+            Scalar::I16 => write.write_i16::<B>(value as i16)?,
+            Scalar::U16 => write.write_u16::<B>(value as u16)?,
+            Scalar::I32 => write.write_i32::<B>(value as i32)?,
+            Scalar::U32 => write.write_u32::<B>(value as u32)?,
+            Scalar::F32 => write.write_f32::<B>(value as f32)?,
+            Scalar::F64 => write.write_f64::<B>(value)?,
+        }
+        Ok(())
+    }
+
+    fn format(&self, value: f64) -> String {
+        match *self {
+            Scalar::F32 => (value as f32).to_string(),
+            Scalar::F64 => value.to_string(),
+            _ => (value as i64).to_string(),
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+enum Property {
+    Scalar(String, Scalar),
+    List(String, Scalar, Scalar),
+}
+
+#[derive(Clone, Debug)]
+struct Element {
+    name: String,
+    count: u64,
+    properties: Vec<Property>,
+}
+
+/// Where a vertex property goes in a point.
+#[derive(Clone, Copy, Debug)]
+enum Target {
+    X,
+    Y,
+    Z,
+    Red,
+    Green,
+    Blue,
+    Intensity,
+    Classification,
+    GpsTime,
+    ExtraBytes(usize),
+}
+
+/// Reads points from the vertices of PLY data.
+///
+/// Classification 12 is read as it is in the legacy point formats, as an unclassified overlap
+/// point, since `read_to_end` always picks a legacy format.
+#[derive(Debug)]
+pub struct PlyReader<R: BufRead> {
+    read: R,
+    encoding: Encoding,
+    vertex: Element,
+    targets: Vec<(Target, Scalar)>,
+    descriptors: Vec<Descriptor>,
+    extra_bytes: usize,
+    remaining: u64,
+}
+
+impl<R: BufRead> PlyReader<R> {
+    /// Reads the ply header and skips any elements that come before the vertices.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::ply::PlyReader;
+    /// let ply = "ply\nformat ascii 1.0\nelement vertex 1\nproperty float x\nproperty float y\nproperty float z\nend_header\n1 2 3\n";
+    /// let mut reader = PlyReader::new(ply.as_bytes()).unwrap();
+    /// assert_eq!(2., reader.next().unwrap().unwrap().y);
+    /// ```
+    pub fn new(mut read: R) -> Result<PlyReader<R>> {
+        let mut line = String::new();
+        let _ = read.read_line(&mut line)?;
+        if line.trim_end() != "ply" {
+            return Err(Error::Magic.into());
+        }
+        let mut encoding = None;
+        let mut elements: Vec<Element> = Vec::new();
+        loop {
+            line.clear();
+            if read.read_line(&mut line)? == 0 {
+                return Err(Error::HeaderLine("end of data".to_string()).into());
+            }
+            let words = line.split_whitespace().collect::<Vec<_>>();
+            let error = || Error::HeaderLine(line.trim_end().to_string());
+            match words.as_slice() {
+                ["end_header"] => break,
+                ["comment", ..] | ["obj_info", ..] | [] => {}
+                ["format", name, _] => {
+                    encoding = Some(match *name {
+                        "ascii" => Encoding::Ascii,
+                        "binary_little_endian" => Encoding::BinaryLittleEndian,
+                        "binary_big_endian" => Encoding::BinaryBigEndian,
+                        _ => return Err(error().into()),
+                    })
+                }
+                ["element", name, count] => elements.push(Element {
+                    name: name.to_string(),
+                    count: count.parse().map_err(|_| error())?,
+                    properties: Vec::new(),
+                }),
+                ["property", "list", count, item, name] => {
+                    let count = Scalar::from_name(count).ok_or_else(error)?;
+                    let item = Scalar::from_name(item).ok_or_else(error)?;
+                    elements
+                        .last_mut()
+                        .ok_or_else(error)?
+                        .properties
+                        .push(Property::List(name.to_string(), count, item));
+                }
+                ["property", scalar, name] => {
+                    let scalar = Scalar::from_name(scalar).ok_or_else(error)?;
+                    elements
+                        .last_mut()
+                        .ok_or_else(error)?
+                        .properties
+                        .push(Property::Scalar(name.to_string(), scalar));
+                }
+                _ => return Err(error().into()),
+            }
+        }
+        let encoding = encoding.ok_or_else(|| Error::HeaderLine("no format".to_string()))?;
+        let index = elements
+            .iter()
+            .position(|element| element.name == "vertex")
+            .ok_or(Error::NoVertices)?;
+        for element in &elements[..index] {
+            for _ in 0..element.count {
+                let _ = read_element(&mut read, encoding, element)?;
+            }
+        }
+        let vertex = elements.swap_remove(index);
+        let mut targets = Vec::new();
+        let mut descriptors = Vec::new();
+        let mut extra_bytes = 0;
+        for property in &vertex.properties {
+            let (name, scalar) = match property {
+                Property::Scalar(name, scalar) => (name, *scalar),
+                Property::List(name, _, _) => return Err(Error::ListProperty(name.clone()).into()),
+            };
+            let target = match name.as_str() {
+                "x" => Target::X,
+                "y" => Target::Y,
+                "z" => Target::Z,
+                "red" => Target::Red,
+                "green" => Target::Green,
+                "blue" => Target::Blue,
+                "intensity" | "scalar_intensity" => Target::Intensity,
+                "classification" | "scalar_classification" => Target::Classification,
+                "gps_time" | "scalar_gps_time" => Target::GpsTime,
+                _ => {
+                    let data_type = scalar.data_type();
+                    let target = Target::ExtraBytes(extra_bytes);
+                    extra_bytes += data_type.size();
+                    descriptors.push(Descriptor::new(name.clone(), data_type));
+                    target
+                }
+            };
+            targets.push((target, scalar));
+        }
+        Ok(PlyReader {
+            read,
+            encoding,
+            remaining: vertex.count,
+            vertex,
+            targets,
+            descriptors,
+            extra_bytes,
+        })
+    }
+
+    /// Returns the descriptors of the vertex properties that become extra bytes.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::ply::PlyReader;
+    /// let ply = "ply\nformat ascii 1.0\nelement vertex 0\nproperty float x\nproperty float nx\nend_header\n";
+    /// let reader = PlyReader::new(ply.as_bytes()).unwrap();
+    /// assert_eq!("nx", reader.descriptors()[0].name);
+    /// ```
+    pub fn descriptors(&self) -> &[Descriptor] {
+        &self.descriptors
+    }
+
+    /// Reads every remaining vertex and builds a header for the points.
+    ///
+    /// The coordinates are stored with the given precision, e.g. 0.001 for millimeters.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::ply::PlyReader;
+    /// let ply = "ply\nformat ascii 1.0\nelement vertex 1\nproperty double x\nproperty double y\nproperty double z\nproperty uchar red\nproperty uchar green\nproperty uchar blue\nend_header\n1 2 3 255 0 0\n";
+    /// let (header, points) = PlyReader::new(ply.as_bytes()).unwrap().read_to_end(0.001).unwrap();
+    /// assert!(header.point_format().has_color);
+    /// assert_eq!(65535, points[0].color.unwrap().red);
+    /// ```
+    pub fn read_to_end(mut self, precision: f64) -> Result<(Header, Vec<Point>)> {
+        let points = self.by_ref().collect::<Result<Vec<_>>>()?;
+        let has = |target: fn(&Target) -> bool| self.targets.iter().any(|(t, _)| target(t));
+        let has_color = has(|t| matches!(t, Target::Red | Target::Green | Target::Blue));
+        let has_gps_time = has(|t| matches!(t, Target::GpsTime));
+        let mut builder = Builder::from((1, if self.descriptors.is_empty() { 2 } else { 4 }));
+        builder.point_format = Format::new(match (has_gps_time, has_color) {
+            (false, false) => 0,
+            (true, false) => 1,
+            (false, true) => 2,
+            (true, true) => 3,
+        })?;
+        builder.point_format.extra_bytes = self.extra_bytes as u16;
+        if !self.descriptors.is_empty() {
+            builder.vlrs.push(extra_bytes::vlr(&self.descriptors)?);
+        }
+        let mut bounds = Bounds::default();
+        for point in &points {
+            bounds.grow(point);
+        }
+        if !points.is_empty() {
+            builder.fit_transforms(&bounds, precision);
+            builder.bounds = bounds;
+        }
+        Ok((builder.into_header()?, points))
+    }
+
+    fn read_point(&mut self) -> Result<Point> {
+        let values = read_element(&mut self.read, self.encoding, &self.vertex)?;
+        let mut point = Point {
+            extra_bytes: vec![0; self.extra_bytes],
+            ..Default::default()
+        };
+        let mut descriptors = self.descriptors.iter();
+        for (&(target, scalar), value) in self.targets.iter().zip(values) {
+            let color = |value: f64| {
+                if scalar == Scalar::U8 || scalar == Scalar::I8 {
+                    (value as u16).saturating_mul(257)
+                } else {
+                    value as u16
+                }
+            };
+            match target {
+                Target::X => point.x = value,
+                Target::Y => point.y = value,
+                Target::Z => point.z = value,
+                Target::Red => point.color.get_or_insert_with(Color::default).red = color(value),
+                Target::Green => {
+                    point.color.get_or_insert_with(Color::default).green = color(value)
+                }
+                Target::Blue => point.color.get_or_insert_with(Color::default).blue = color(value),
+                Target::Intensity => point.intensity = value.round() as u16,
+                Target::Classification => {
+                    let n = value as u8;
+                    if n == 12 {
+                        point.is_overlap = true;
+                        point.classification = Classification::Unclassified;
+                    } else {
+                        point.classification = Classification::new(n)?;
+                    }
+                }
+                Target::GpsTime => point.gps_time = Some(value),
+                Target::ExtraBytes(offset) => {
+                    let descriptor = descriptors.next().expect("one descriptor per extra bytes");
+                    descriptor.set(&mut point.extra_bytes[offset..], value)?;
+                }
+            }
+        }
+        Ok(point)
+    }
+}
+
+impl<R: BufRead> Iterator for PlyReader<R> {
+    type Item = Result<Point>;
+
+    fn next(&mut self) -> Option<Result<Point>> {
+        if self.remaining == 0 {
+            None
+        } else {
+            self.remaining -= 1;
+            Some(self.read_point())
+        }
+    }
+}
+
+/// Reads one element, returning the values of its scalar properties.
+fn read_element<R: BufRead>(
+    read: &mut R,
+    encoding: Encoding,
+    element: &Element,
+) -> Result<Vec<f64>> {
+    let mut values = Vec::with_capacity(element.properties.len());
+    match encoding {
+        Encoding::Ascii => {
+            let mut line = String::new();
+            while line.trim().is_empty() {
+                line.clear();
+                if read.read_line(&mut line)? == 0 {
+                    return Err(Error::TooFewValues.into());
+                }
+            }
+            let mut words = line.split_whitespace();
+            let mut next = || -> Result<f64> {
+                let word = words.next().ok_or(Error::TooFewValues)?;
+                word.parse()
+                    .map_err(|_| Error::Value(word.to_string()).into())
+            };
+            for property in &element.properties {
+                match property {
+                    Property::Scalar(_, _) => values.push(next()?),
+                    Property::List(_, _, _) => {
+                        for _ in 0..next()? as usize {
+                            let _ = next()?;
+                        }
+                    }
+                }
+            }
+        }
+        Encoding::BinaryLittleEndian => read_binary::<LittleEndian, R>(read, element, &mut values)?,
+        Encoding::BinaryBigEndian => read_binary::<BigEndian, R>(read, element, &mut values)?,
+    }
+    Ok(values)
+}
+
+fn read_binary<B: ByteOrder, R: BufRead>(
+    read: &mut R,
+    element: &Element,
+    values: &mut Vec<f64>,
+) -> Result<()> {
+    for property in &element.properties {
+        match property {
+            Property::Scalar(_, scalar) => values.push(scalar.read::<B, _>(&mut *read)?),
+            Property::List(_, count, item) => {
+                for _ in 0..count.read::<B, _>(&mut *read)? as usize {
+                    let _ = item.read::<B, _>(&mut *read)?;
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Writes points as the vertices of PLY data.
+///
+/// PLY needs the vertex count before the vertices, so the encoded vertices are held in memory and
+/// written when the writer is closed.
+#[derive(Debug)]
+pub struct PlyWriter<W: std::io::Write> {
+    write: Option<W>,
+    header: Header,
+    encoding: Encoding,
+    descriptors: Vec<(usize, Descriptor, Scalar)>,
+    data: Vec<u8>,
+    count: u64,
+    closed: bool,
+}
+
+impl<W: std::io::Write> PlyWriter<W> {
+    /// Creates a ply writer.
+    ///
+    /// The vertices get x, y, z, intensity, and classification properties, plus red, green, blue,
+    /// and gps time if the header's point format has them. Typed extra bytes become properties of
+    /// their own.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::ply::{Encoding, PlyWriter};
+    /// use las::Header;
+    /// let writer = PlyWriter::new(Vec::new(), Header::default(), Encoding::Ascii).unwrap();
+    /// ```
+    pub fn new(write: W, header: Header, encoding: Encoding) -> Result<PlyWriter<W>> {
+        let mut descriptors = Vec::new();
+        let mut offset = 0;
+        for descriptor in extra_bytes::descriptors(&header)? {
+            let size = descriptor.data_type.size();
+            if let Some(scalar) = Scalar::from_data_type(descriptor.data_type) {
+                descriptors.push((offset, descriptor, scalar));
+            }
+            offset += size;
+        }
+        Ok(PlyWriter {
+            write: Some(write),
+            header,
+            encoding,
+            descriptors,
+            data: Vec::new(),
+            count: 0,
+            closed: false,
+        })
+    }
+
+    /// Writes the ply header and the vertices.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::ply::{Encoding, PlyWriter};
+    /// use las::Header;
+    /// let mut writer = PlyWriter::new(Vec::new(), Header::default(), Encoding::Ascii).unwrap();
+    /// writer.close().unwrap();
+    /// assert!(writer.close().is_err());
+    /// ```
+    pub fn close(&mut self) -> Result<()> {
+        if self.closed {
+            return Err(Error::Closed.into());
+        }
+        self.closed = true;
+        let mut write = self.write.take().expect("an open writer has a write");
+        let result = self.write_to(&mut write);
+        self.write = Some(write);
+        result
+    }
+
+    /// Closes the writer, if needed, and returns the underlying writer.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::ply::{Encoding, PlyWriter};
+    /// use las::Header;
+    /// let writer = PlyWriter::new(Vec::new(), Header::default(), Encoding::Ascii).unwrap();
+    /// let ply = writer.into_inner().unwrap();
+    /// assert!(ply.starts_with(b"ply\n"));
+    /// ```
+    pub fn into_inner(mut self) -> Result<W> {
+        if !self.closed {
+            self.close()?;
+        }
+        Ok(self.write.take().expect("the writer was just closed"))
+    }
+
+    fn write_to(&self, write: &mut W) -> Result<()> {
+        let format = self.header.point_format();
+        writeln!(write, "ply")?;
+        writeln!(write, "format {} 1.0", self.encoding.name())?;
+        writeln!(write, "comment written by las-rs")?;
+        writeln!(write, "element vertex {}", self.count)?;
+        for name in ["x", "y", "z"] {
+            writeln!(write, "property double {}", name)?;
+        }
+        writeln!(write, "property ushort intensity")?;
+        writeln!(write, "property uchar classification")?;
+        if format.has_color {
+            for name in ["red", "green", "blue"] {
+                writeln!(write, "property uchar {}", name)?;
+            }
+        }
+        if format.has_gps_time {
+            writeln!(write, "property double gps_time")?;
+        }
+        for (_, descriptor, scalar) in &self.descriptors {
+            writeln!(write, "property {} {}", scalar.name(), descriptor.name)?;
+        }
+        writeln!(write, "end_header")?;
+        write.write_all(&self.data)?;
+        Ok(())
+    }
+
+    fn values(&self, point: &Point) -> Vec<(Scalar, f64)> {
+        let format = self.header.point_format();
+        let classification = if !format.is_extended
+            && point.is_overlap
+            && point.classification == Classification::Unclassified
+        {
+            12
+        } else {
+            u8::from(point.classification)
+        };
+        let mut values = vec![
+            (Scalar::F64, point.x),
+            (Scalar::F64, point.y),
+            (Scalar::F64, point.z),
+            (Scalar::U16, f64::from(point.intensity)),
+            (Scalar::U8, f64::from(classification)),
+        ];
+        if format.has_color {
+            let color = point.color.unwrap_or_default();
+            for channel in [color.red, color.green, color.blue] {
+                values.push((Scalar::U8, f64::from((u32::from(channel) + 128) / 257)));
+            }
+        }
+        if format.has_gps_time {
+            values.push((Scalar::F64, point.gps_time.unwrap_or(0.)));
+        }
+        for (offset, descriptor, scalar) in &self.descriptors {
+            let raw = Descriptor::new("", descriptor.data_type);
+            let value = point
+                .extra_bytes
+                .get(*offset..)
+                .and_then(|bytes| raw.get(bytes))
+                .unwrap_or(0.);
+            values.push((*scalar, value));
+        }
+        values
+    }
+}
+
+impl<W: std::io::Write> Write for PlyWriter<W> {
+    fn header(&self) -> &Header {
+        &self.header
+    }
+
+    fn write(&mut self, point: Point) -> Result<()> {
+        if self.closed {
+            return Err(Error::Closed.into());
+        }
+        let values = self.values(&point);
+        match self.encoding {
+            Encoding::Ascii => {
+                let fields = values
+                    .iter()
+                    .map(|(scalar, value)| scalar.format(*value))
+                    .collect::<Vec<_>>();
+                self.data.extend_from_slice(fields.join(" ").as_bytes());
+                self.data.push(b'\n');
+            }
+            Encoding::BinaryLittleEndian => {
+                for (scalar, value) in values {
+                    scalar.write::<LittleEndian, _>(&mut self.data, value)?;
+                }
+            }
+            Encoding::BinaryBigEndian => {
+                for (scalar, value) in values {
+                    scalar.write::<BigEndian, _>(&mut self.data, value)?;
+                }
+            }
+        }
+        self.count += 1;
+        Ok(())
+    }
+}
+
+impl<W: std::io::Write> Drop for PlyWriter<W> {
+    fn drop(&mut self) {
+        if !self.closed {
+            self.close().expect("Error when dropping the writer");
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Read, Reader};
+
+    fn roundtrip(encoding: Encoding) -> (Header, Vec<Point>) {
+        let mut reader = Reader::from_path("tests/data/autzen.las").unwrap();
+        let mut builder = Builder::from(reader.header().clone());
+        builder.point_format = Format::new(3).unwrap();
+        builder.point_format.extra_bytes = 6;
+        let descriptors = vec![
+            Descriptor::new("range", DataType::F32),
+            Descriptor::new("amplitude", DataType::I16),
+        ];
+        builder.vlrs.push(extra_bytes::vlr(&descriptors).unwrap());
+        let header = builder.into_header().unwrap();
+        let mut writer = PlyWriter::new(Vec::new(), header, encoding).unwrap();
+        for (i, point) in reader.points().enumerate() {
+            let mut point = point.unwrap();
+            point.color = Some(Color::new(257 * (i as u16), 0, 65535));
+            point.extra_bytes = vec![0; 6];
+            descriptors[0]
+                .set(&mut point.extra_bytes, i as f64 + 0.5)
+                .unwrap();
+            descriptors[1]
+                .set(&mut point.extra_bytes[4..], -(i as f64))
+                .unwrap();
+            writer.write(point).unwrap();
+        }
+        let ply = writer.into_inner().unwrap();
+        PlyReader::new(ply.as_slice())
+            .unwrap()
+            .read_to_end(0.01)
+            .unwrap()
+    }
+
+    #[test]
+    fn roundtrips() {
+        let expected = Reader::from_path("tests/data/autzen.las")
+            .unwrap()
+            .points()
+            .map(|point| point.unwrap())
+            .collect::<Vec<_>>();
+        for &encoding in &[
+            Encoding::Ascii,
+            Encoding::BinaryLittleEndian,
+            Encoding::BinaryBigEndian,
+        ] {
+            let (header, points) = roundtrip(encoding);
+            let descriptors = extra_bytes::descriptors(&header).unwrap();
+            assert_eq!(
+                vec!["range", "amplitude"],
+                descriptors
+                    .iter()
+                    .map(|descriptor| descriptor.name.as_str())
+                    .collect::<Vec<_>>()
+            );
+            assert_eq!(3, header.point_format().to_u8().unwrap());
+            assert_eq!(6, header.point_format().extra_bytes);
+            for (i, (point, expected)) in points.iter().zip(&expected).enumerate() {
+                assert_eq!(expected.x, point.x);
+                assert_eq!(expected.gps_time, point.gps_time);
+                assert_eq!(expected.intensity, point.intensity);
+                assert_eq!(expected.classification, point.classification);
+                assert_eq!(257 * (i as u16), point.color.unwrap().red);
+                assert_eq!(Some(i as f64 + 0.5), descriptors[0].get(&point.extra_bytes));
+                assert_eq!(
+                    Some(-(i as f64)),
+                    descriptors[1].get(&point.extra_bytes[4..])
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn skips_faces_before_vertices() {
+        let ply = "ply\nformat ascii 1.0\nelement face 1\nproperty list uchar int vertex_indices\nelement vertex 1\nproperty float x\nproperty float y\nproperty float z\nend_header\n3 0 1 2\n1 2 3\n";
+        let points = PlyReader::new(ply.as_bytes())
+            .unwrap()
+            .collect::<Result<Vec<_>>>()
+            .unwrap();
+        assert_eq!(1, points.len());
+        assert_eq!(3., points[0].z);
+    }
+
+    #[test]
+    fn not_ply() {
+        assert!(PlyReader::new("las\n".as_bytes()).is_err());
+    }
+
+    #[test]
+    fn no_vertices() {
+        let ply = "ply\nformat ascii 1.0\nend_header\n";
+        assert!(PlyReader::new(ply.as_bytes()).is_err());
+    }
+}
@@ -1,4 +1,4 @@
-use crate::point::Error;
+use crate::point::{Error, Format};
 use crate::Result;
 
 /// The ASPRS classification table.
@@ -12,24 +12,31 @@ use crate::Result;
 /// assert_eq!(2, u8::from(classification));
 /// ```
 ///
-/// We make one modification to this table: we remove `OverlapPoints`, code 12. Las 1.4 added the
-/// extended point formats, which include an overlap bit. The overlap bit is intended to allow a
-/// point to both be an overlap point and contain some other classification.
+/// Overlap points, code 12, need some care. Las 1.4 added the extended point formats (6 and up),
+/// which include an overlap bit, so that a point can both be an overlap point and have some other
+/// classification.
 ///
 /// Here's how we deal with that change:
 ///
-/// - If the point format doesn't support the overlap bit, the classification is overwritten with
-/// the code for overlap points (12). On ingest, points with an overlap classification are given
-/// the `Unclassified` code and `Point::is_overlap` is set to `true`.
-/// - If the point format does support the overlap bit, that is preferred.
+/// - In the legacy point formats (0 through 5), code 12 means overlap. On ingest, points with an
+///   overlap classification are given the `Unclassified` code and `Point::is_overlap` is set to
+///   `true`, and on output the overlap bit is written as code 12.
+/// - In the extended point formats, the overlap bit is separate and code 12 is an ordinary class,
+///   `OverlapPoints`.
+/// - `OverlapPoints` can't be stored in the legacy formats, so writing it to one is an error
+///   rather than silently becoming the overlap code; set `Point::is_overlap` instead.
 ///
-/// Because of this change, trying to create a classification with code 12 is an error:
+/// Because `Classification::new` doesn't know the point format, it rejects code 12. Use
+/// `Classification::for_format` when the format is known:
 ///
 /// ```
-/// use las::point::Classification;
+/// use las::point::{Classification, Format};
 /// assert!(Classification::new(12).is_err());
+/// let format = Format::new(6).unwrap();
+/// assert_eq!(Classification::OverlapPoints, Classification::for_format(12, &format).unwrap());
 /// ```
 #[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[allow(missing_docs)]
 pub enum Classification {
     CreatedNeverClassified,
@@ -44,12 +51,17 @@ pub enum Classification {
     Water,
     Rail,
     RoadSurface,
+    OverlapPoints,
     WireGuard,
     WireConductor,
     TransmissionTower,
     WireStructureConnector,
     BridgeDeck,
     HighNoise,
+    OverheadStructure,
+    IgnoredGround,
+    Snow,
+    TemporalExclusion,
     Reserved(u8),
     UserDefinable(u8),
 }
@@ -57,7 +69,8 @@ pub enum Classification {
 impl Classification {
     /// Creates a new classification.
     ///
-    /// Throws an error if the classification is 12 (overlap).
+    /// Throws an error if the classification is 12 (overlap), since its meaning depends on the
+    /// point format.
     ///
     /// # Examples
     ///
@@ -87,10 +100,65 @@ impl Classification {
             16 => Classification::WireStructureConnector,
             17 => Classification::BridgeDeck,
             18 => Classification::HighNoise,
-            19..=63 => Classification::Reserved(n),
+            19 => Classification::OverheadStructure,
+            20 => Classification::IgnoredGround,
+            21 => Classification::Snow,
+            22 => Classification::TemporalExclusion,
+            23..=63 => Classification::Reserved(n),
             64..=255 => Classification::UserDefinable(n),
         })
     }
+
+    /// Creates a new classification for a point format.
+    ///
+    /// In the legacy point formats, codes above 31 don't fit and code 12 is overlap, so both are
+    /// errors. In the extended point formats, code 12 is `OverlapPoints`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::point::{Classification, Format};
+    /// let legacy = Format::new(1).unwrap();
+    /// let extended = Format::new(6).unwrap();
+    /// assert_eq!(Classification::Snow, Classification::for_format(21, &legacy).unwrap());
+    /// assert!(Classification::for_format(40, &legacy).is_err());
+    /// assert_eq!(Classification::Reserved(40), Classification::for_format(40, &extended).unwrap());
+    /// ```
+    pub fn for_format(n: u8, format: &Format) -> Result<Classification> {
+        if format.is_extended {
+            Ok(Classification::extended(n))
+        } else if n > 31 {
+            Err(Error::Classification(n).into())
+        } else {
+            Classification::new(n)
+        }
+    }
+
+    /// Returns true if this classification can be stored in a point format.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::point::{Classification, Format};
+    /// let legacy = Format::new(1).unwrap();
+    /// assert!(Classification::TemporalExclusion.is_valid_for(&legacy));
+    /// assert!(!Classification::UserDefinable(64).is_valid_for(&legacy));
+    /// assert!(!Classification::OverlapPoints.is_valid_for(&legacy));
+    /// assert!(Classification::OverlapPoints.is_valid_for(&Format::new(6).unwrap()));
+    /// ```
+    pub fn is_valid_for(&self, format: &Format) -> bool {
+        format.is_extended || (*self != Classification::OverlapPoints && u8::from(*self) <= 31)
+    }
+
+    /// Creates a classification from an extended point format's classification byte, where every
+    /// code is valid.
+    pub(crate) fn extended(n: u8) -> Classification {
+        if n == 12 {
+            Classification::OverlapPoints
+        } else {
+            Classification::new(n).expect("only code 12 is invalid")
+        }
+    }
 }
 
 impl From<Classification> for u8 {
@@ -108,12 +176,17 @@ impl From<Classification> for u8 {
             Classification::Water => 9,
             Classification::Rail => 10,
             Classification::RoadSurface => 11,
+            Classification::OverlapPoints => 12,
             Classification::WireGuard => 13,
             Classification::WireConductor => 14,
             Classification::TransmissionTower => 15,
             Classification::WireStructureConnector => 16,
             Classification::BridgeDeck => 17,
             Classification::HighNoise => 18,
+            Classification::OverheadStructure => 19,
+            Classification::IgnoredGround => 20,
+            Classification::Snow => 21,
+            Classification::TemporalExclusion => 22,
             Classification::Reserved(n) | Classification::UserDefinable(n) => n,
         }
     }
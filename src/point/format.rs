@@ -56,6 +56,7 @@ fn point_format_id_uncompressed_to_compressed(point_format_id: u8) -> u8 {
 /// assert!(format.to_u8().is_err());
 /// ```
 #[derive(Clone, Copy, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Format {
     /// Does this point format include gps time?
     pub has_gps_time: bool,
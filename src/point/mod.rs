@@ -21,9 +21,10 @@ pub use self::format::Format;
 pub use self::scan_direction::ScanDirection;
 
 use crate::raw;
-use crate::raw::point::Waveform;
+use crate::raw::point::{OutOfRange, Rounding, ScanAngle, Waveform};
 use thiserror::Error;
-use crate::{Color, Result, Transform, Vector};
+use crate::{Color, GpsTimeType, Result, Transform, Vector};
+use chrono::{DateTime, Utc};
 
 /// Point-specific errors
 #[derive(Debug, Clone, Copy, Error)]
@@ -54,13 +55,21 @@ pub enum Error {
         version: Option<crate::Version>,
     },
 
+    /// The scan angle, in degrees, is outside of the range of its representation.
+    #[error("scan angle {0} is out of range")]
+    ScanAngle(f32),
+
     /// This is not a valid scanner channel
     #[error("invalid scanner channel: {0}")]
     ScannerChannel(u8),
 }
 
 /// A three dimensional point.
-#[derive(Clone, Debug, Default, PartialEq)]
+///
+/// Points are compared by their attributes only; `raw_coordinates` and `raw_scan_angle` are
+/// ignored, since they're caches of x, y, z, and the scan angle.
+#[derive(Clone, Debug, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Point {
     /// The x coordinate, as a float.
     pub x: f64,
@@ -137,6 +146,22 @@ pub struct Point {
     ///
     /// These can have structure and meaning, but for now they don't.
     pub extra_bytes: Vec<u8>,
+
+    /// The integer coordinates of the record this point was read from, if any.
+    ///
+    /// These are exact, so they're useful for deduplication and grid hashing. When the point is
+    /// converted back to a raw point, each integer is reused as long as it still transforms to
+    /// the point's coordinate, which avoids any drift from rounding floats. If you change x, y, or
+    /// z, the stale integer is ignored.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub raw_coordinates: Option<Vector<i32>>,
+
+    /// The scan angle of the record this point was read from, if any, as it was stored.
+    ///
+    /// When the point is converted back to a raw point, this representation is kept as long as
+    /// it's still equal to `scan_angle`, so ranks stay ranks and scaled angles aren't rounded.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub raw_scan_angle: Option<ScanAngle>,
 }
 
 impl Point {
@@ -172,6 +197,7 @@ impl Point {
             is_withheld: raw_point.flags.is_withheld(),
             is_overlap,
             scan_angle: raw_point.scan_angle.into(),
+            raw_scan_angle: Some(raw_point.scan_angle),
             scanner_channel: raw_point.flags.scanner_channel(),
             user_data: raw_point.user_data,
             point_source_id: raw_point.point_source_id,
@@ -180,10 +206,20 @@ impl Point {
             waveform: raw_point.waveform,
             nir: raw_point.nir,
             extra_bytes: raw_point.extra_bytes,
+            raw_coordinates: Some(Vector {
+                x: raw_point.x,
+                y: raw_point.y,
+                z: raw_point.z,
+            }),
         }
     }
     /// Creates a raw las point from this point.
     ///
+    /// The `raw_coordinates` are used for any axis where they still transform to this point's
+    /// coordinate, and the inverse transform is used otherwise. Likewise, the `raw_scan_angle` is
+    /// used if it's still equal to the scan angle; otherwise the scan angle is rounded to the
+    /// nearest scaled angle, and angles beyond ±180° are an error.
+    ///
     /// # Examples
     ///
     /// ```
@@ -192,13 +228,22 @@ impl Point {
     /// let raw_point = point.into_raw(&Default::default()).unwrap();
     /// ```
     pub fn into_raw(self, transforms: &Vector<Transform>) -> Result<raw::Point> {
+        let raw = |n: f64, transform: &Transform, stored: Option<i32>| match stored {
+            Some(stored) if transform.direct(stored) == n => Ok(stored),
+            _ => transform.inverse(n),
+        };
+        let raw_coordinates = self.raw_coordinates;
+        let scan_angle = match self.raw_scan_angle {
+            Some(stored) if f32::from(stored) == self.scan_angle => stored,
+            _ => ScanAngle::scaled(self.scan_angle, Rounding::Nearest, OutOfRange::Error)?,
+        };
         Ok(raw::Point {
-            x: transforms.x.inverse(self.x)?,
-            y: transforms.y.inverse(self.y)?,
-            z: transforms.z.inverse(self.z)?,
+            x: raw(self.x, &transforms.x, raw_coordinates.map(|v| v.x))?,
+            y: raw(self.y, &transforms.y, raw_coordinates.map(|v| v.y))?,
+            z: raw(self.z, &transforms.z, raw_coordinates.map(|v| v.z))?,
             intensity: self.intensity,
             flags: self.flags()?,
-            scan_angle: self.scan_angle.into(),
+            scan_angle,
             user_data: self.user_data,
             point_source_id: self.point_source_id,
             gps_time: self.gps_time,
@@ -289,12 +334,155 @@ impl Point {
             && self.nir.is_some() == format.has_nir
             && self.extra_bytes.len() == format.extra_bytes as usize
     }
+
+    /// Returns this point's gps time as UTC, or `None` if the point has no gps time.
+    ///
+    /// Week time is relative to the given gps week. See `las::gps_time` for the conversions.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use chrono::{TimeZone, Utc};
+    /// use las::{GpsTimeType, Point};
+    ///
+    /// let mut point = Point::default();
+    /// assert!(point.utc(GpsTimeType::Standard, 0).unwrap().is_none());
+    /// point.gps_time = Some(0.);
+    /// assert_eq!(
+    ///     Utc.with_ymd_and_hms(2011, 9, 14, 1, 46, 25).unwrap(),
+    ///     point.utc(GpsTimeType::Standard, 0).unwrap().unwrap()
+    /// );
+    /// ```
+    pub fn utc(&self, gps_time_type: GpsTimeType, week: u32) -> Result<Option<DateTime<Utc>>> {
+        self.gps_time
+            .map(|gps_time| crate::gps_time::to_utc(gps_time, gps_time_type, week))
+            .transpose()
+    }
+}
+
+impl PartialEq for Point {
+    fn eq(&self, other: &Point) -> bool {
+        // Destructured so that a new field can't be left out of the comparison by accident.
+        let Point {
+            x,
+            y,
+            z,
+            intensity,
+            return_number,
+            number_of_returns,
+            scan_direction,
+            is_edge_of_flight_line,
+            classification,
+            is_synthetic,
+            is_key_point,
+            is_withheld,
+            is_overlap,
+            scanner_channel,
+            scan_angle,
+            user_data,
+            point_source_id,
+            gps_time,
+            color,
+            waveform,
+            nir,
+            extra_bytes,
+            raw_coordinates: _,
+            raw_scan_angle: _,
+        } = self;
+        x == &other.x
+            && y == &other.y
+            && z == &other.z
+            && intensity == &other.intensity
+            && return_number == &other.return_number
+            && number_of_returns == &other.number_of_returns
+            && scan_direction == &other.scan_direction
+            && is_edge_of_flight_line == &other.is_edge_of_flight_line
+            && classification == &other.classification
+            && is_synthetic == &other.is_synthetic
+            && is_key_point == &other.is_key_point
+            && is_withheld == &other.is_withheld
+            && is_overlap == &other.is_overlap
+            && scanner_channel == &other.scanner_channel
+            && scan_angle == &other.scan_angle
+            && user_data == &other.user_data
+            && point_source_id == &other.point_source_id
+            && gps_time == &other.gps_time
+            && color == &other.color
+            && waveform == &other.waveform
+            && nir == &other.nir
+            && extra_bytes == &other.extra_bytes
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn raw_coordinates_avoid_drift() {
+        let transform = Transform {
+            scale: 0.001,
+            offset: 1e13,
+        };
+        let transforms = Vector {
+            x: transform,
+            y: transform,
+            z: transform,
+        };
+        let raw_point = raw::Point {
+            x: 1,
+            y: 2,
+            z: 3,
+            ..Default::default()
+        };
+        let point = Point::new(raw_point, &transforms);
+        assert_eq!(Some(Vector { x: 1, y: 2, z: 3 }), point.raw_coordinates);
+        let raw_point = point.clone().into_raw(&transforms).unwrap();
+        assert_eq!((1, 2, 3), (raw_point.x, raw_point.y, raw_point.z));
+
+        let floats_only = Point {
+            raw_coordinates: None,
+            ..point
+        };
+        let raw_point = floats_only.into_raw(&transforms).unwrap();
+        assert_ne!((1, 2, 3), (raw_point.x, raw_point.y, raw_point.z));
+    }
+
+    #[test]
+    fn stale_raw_coordinates() {
+        let mut point = Point::new(Default::default(), &Default::default());
+        point.x = 1.;
+        let raw_point = point.clone().into_raw(&Default::default()).unwrap();
+        assert_eq!(1000, raw_point.x);
+        assert_eq!(0, raw_point.y);
+        assert_eq!(
+            Point {
+                x: 1.,
+                ..Default::default()
+            },
+            point
+        );
+    }
+
+    #[test]
+    fn raw_scan_angle() {
+        let raw_point = raw::Point {
+            scan_angle: ScanAngle::Rank(-45),
+            ..Default::default()
+        };
+        let mut point = Point::new(raw_point, &Default::default());
+        assert_eq!(-45., point.scan_angle);
+        let raw_point = point.clone().into_raw(&Default::default()).unwrap();
+        assert!(matches!(raw_point.scan_angle, ScanAngle::Rank(-45)));
+
+        point.scan_angle = 12.3;
+        let raw_point = point.clone().into_raw(&Default::default()).unwrap();
+        assert!(matches!(raw_point.scan_angle, ScanAngle::Scaled(2050)));
+
+        point.scan_angle = 180.5;
+        assert!(point.into_raw(&Default::default()).is_err());
+    }
+
     #[test]
     fn flags_invalid_return_number() {
         assert!(Point {
@@ -337,4 +525,18 @@ mod tests {
         assert_eq!(Classification::Unclassified, point.classification);
         assert!(point.is_overlap);
     }
+
+    #[test]
+    fn overlap_class_in_extended_formats() {
+        use crate::raw::point::Flags;
+
+        let raw_point = raw::Point {
+            flags: Flags::ThreeByte(0, 0, 12),
+            ..Default::default()
+        };
+        let point = Point::new(raw_point, &Default::default());
+        assert_eq!(Classification::OverlapPoints, point.classification);
+        assert!(!point.is_overlap);
+        assert_eq!((0, 0, 12), point.flags().unwrap().into());
+    }
 }
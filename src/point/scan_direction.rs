@@ -1,5 +1,6 @@
 /// The direction at which the scanner mirror was traveling at the time of pulse output.
 #[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum ScanDirection {
     /// The scan is moving from the right to the left.
     RightToLeft,
@@ -5,6 +5,8 @@ use std::io::{Read, Write};
 use crate::{Color, Result};
 
 const SCAN_ANGLE_SCALE_FACTOR: f32 = 0.006;
+const MAX_RANK: f64 = 90.;
+const MAX_SCALED: f64 = 30_000.;
 const OVERLAP_CLASSIFICATION_CODE: u8 = 12;
 
 /// A raw point.
@@ -242,6 +244,7 @@ pub struct Point {
 }
 
 #[derive(Clone, Copy, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[allow(missing_docs)]
 pub struct Waveform {
     /// This value plus 99 is the Record ID of the Waveform Packet Descriptor and indicates the
@@ -305,6 +308,22 @@ pub struct Waveform {
 }
 
 /// Scan angle can be stored as a i8 (rank) or i16 (scaled).
+///
+/// Ranks are whole degrees between -90 and 90, and are used by point formats zero through five.
+/// Scaled angles are increments of 0.006 degrees between -180 and 180, and are used by the
+/// extended point formats.
+///
+/// ```
+/// use las::raw::point::{OutOfRange, Rounding, ScanAngle};
+///
+/// let scan_angle = ScanAngle::scaled(45.3, Rounding::Nearest, OutOfRange::Error).unwrap();
+/// assert_eq!(45, scan_angle.to_rank(Rounding::Nearest, OutOfRange::Error).unwrap());
+/// assert!(ScanAngle::rank(91., Rounding::Nearest, OutOfRange::Error).is_err());
+/// assert_eq!(
+///     ScanAngle::Rank(90),
+///     ScanAngle::rank(91., Rounding::Nearest, OutOfRange::Clamp).unwrap()
+/// );
+/// ```
 #[derive(Clone, Copy, Debug)]
 #[allow(missing_docs)]
 pub enum ScanAngle {
@@ -312,6 +331,28 @@ pub enum ScanAngle {
     Scaled(i16),
 }
 
+/// How scan angles are rounded to their integer representations.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Rounding {
+    /// Round to the nearest integer, with halves rounded away from zero.
+    #[default]
+    Nearest,
+
+    /// Round toward zero.
+    TowardZero,
+}
+
+/// What to do with scan angles outside of a representation's range.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum OutOfRange {
+    /// Return an error.
+    #[default]
+    Error,
+
+    /// Clamp the angle to the nearest end of the range.
+    Clamp,
+}
+
 /// These flags hold information about point classification, return number, and more.
 ///
 /// In point formats zero through five, two bytes are used to hold all of the information. Point
@@ -389,13 +430,13 @@ impl Point {
         } else {
             None
         };
-        point.waveform = if format.has_waveform {
-            Some(Waveform::read_from(&mut read)?)
+        point.nir = if format.has_nir {
+            utils::some_or_none_if_zero(read.read_u16::<LittleEndian>()?)
         } else {
             None
         };
-        point.nir = if format.has_nir {
-            utils::some_or_none_if_zero(read.read_u16::<LittleEndian>()?)
+        point.waveform = if format.has_waveform {
+            Some(Waveform::read_from(&mut read)?)
         } else {
             None
         };
@@ -406,6 +447,10 @@ impl Point {
 
     /// Writes a raw pont.
     ///
+    /// A scan angle that's already stored the way the format stores it is written unchanged, even
+    /// if it's outside of the spec's range. Otherwise it's converted to the nearest rank or scaled
+    /// angle, and an angle that doesn't fit is an error.
+    ///
     /// # Examples
     ///
     /// `Write` implements `WriteRawPoint`.
@@ -438,9 +483,17 @@ impl Point {
         }
         if format.is_extended {
             write.write_u8(self.user_data)?;
-            write.write_i16::<LittleEndian>(self.scan_angle.into())?;
+            let scaled = match self.scan_angle {
+                ScanAngle::Scaled(n) => n,
+                rank => rank.to_scaled(Rounding::Nearest, OutOfRange::Error)?,
+            };
+            write.write_i16::<LittleEndian>(scaled)?;
         } else {
-            write.write_i8(self.scan_angle.into())?;
+            let rank = match self.scan_angle {
+                ScanAngle::Rank(n) => n,
+                scaled => scaled.to_rank(Rounding::Nearest, OutOfRange::Error)?,
+            };
+            write.write_i8(rank)?;
             write.write_u8(self.user_data)?;
         }
         write.write_u16::<LittleEndian>(self.point_source_id)?;
@@ -605,7 +658,7 @@ impl Flags {
     /// ```
     pub fn is_overlap(&self) -> bool {
         match *self {
-            Flags::TwoByte(_, b) => b & 0b1111 == OVERLAP_CLASSIFICATION_CODE,
+            Flags::TwoByte(_, b) => b & 0b1_1111 == OVERLAP_CLASSIFICATION_CODE,
             Flags::ThreeByte(_, b, _) => b & 8 == 8,
         }
     }
@@ -646,7 +699,9 @@ impl Flags {
     /// Converts these flags into two bytes.
     ///
     /// If these are two byte flags, no problem. However, if these are three byte flags,
-    /// information could be lost — in that case, we error.
+    /// information could be lost — in that case, we error. Classification 12 is an ordinary class
+    /// in three byte flags but means overlap in two byte flags, so it's an error too; use the
+    /// overlap bit instead.
     ///
     /// # Example
     ///
@@ -654,6 +709,8 @@ impl Flags {
     /// use las::raw::point::Flags;
     /// assert_eq!((1, 2), Flags::TwoByte(1, 2).to_two_bytes().unwrap());
     /// assert!(Flags::ThreeByte(0b00001000, 0, 0).to_two_bytes().is_err());
+    /// assert!(Flags::ThreeByte(0, 0, 12).to_two_bytes().is_err());
+    /// assert_eq!((0, 12), Flags::ThreeByte(0, 0b1000, 1).to_two_bytes().unwrap());
     /// ```
     pub fn to_two_bytes(&self) -> Result<(u8, u8)> {
         match *self {
@@ -673,6 +730,8 @@ impl Flags {
                     .into())
                 } else if c > 31 {
                     Err(Error::Classification(c).into())
+                } else if c == OVERLAP_CLASSIFICATION_CODE {
+                    Err(Error::OverlapClassification.into())
                 } else if self.scanner_channel() > 0 {
                     Err(Error::ScannerChannel(self.scanner_channel()).into())
                 } else {
@@ -705,9 +764,9 @@ impl Flags {
 
     /// Converts these flags to a classification.
     ///
-    /// Throws an error of the classifiction is 12 (overlap points), because we don't have an
-    /// overlap points class in this library. See the `las::point::Classification` documentation
-    /// for more information.
+    /// Throws an error if two byte flags have classification 12 (overlap points), since that's
+    /// handled by `las::Point::is_overlap`. In three byte flags, 12 is an ordinary class. See the
+    /// `las::point::Classification` documentation for more information.
     ///
     /// # Examples
     ///
@@ -717,17 +776,22 @@ impl Flags {
     /// assert_eq!(Classification::Ground, Flags::TwoByte(0, 2).to_classification().unwrap());
     /// assert_eq!(Classification::Ground, Flags::ThreeByte(0, 0, 2).to_classification().unwrap());
     /// assert!(Flags::TwoByte(0, 12).to_classification().is_err());
-    /// assert!(Flags::ThreeByte(0, 0, 12).to_classification().is_err());
+    /// assert_eq!(
+    ///     Classification::OverlapPoints,
+    ///     Flags::ThreeByte(0, 0, 12).to_classification().unwrap()
+    /// );
     /// ```
     pub fn to_classification(&self) -> Result<Classification> {
         match *self {
             Flags::TwoByte(_, b) => Classification::new(b & 0b0001_1111),
-            Flags::ThreeByte(_, _, c) => Classification::new(c),
+            Flags::ThreeByte(_, _, c) => Ok(Classification::extended(c)),
         }
     }
 
     /// Clears any overlap classes in these flags.
     ///
+    /// Only two byte flags use a class for overlap, so three byte flags are left alone.
+    ///
     /// # Examples
     ///
     /// ```
@@ -739,7 +803,7 @@ impl Flags {
     ///
     /// let mut flags = Flags::ThreeByte(0, 0, 12);
     /// flags.clear_overlap_class();
-    /// assert_eq!(Flags::ThreeByte(0, 8, 1), flags);
+    /// assert_eq!(Flags::ThreeByte(0, 0, 12), flags);
     /// ```
     pub fn clear_overlap_class(&mut self) {
         match *self {
@@ -748,12 +812,7 @@ impl Flags {
                     *b = (*b & 0b1110_0000) + u8::from(Classification::Unclassified);
                 }
             }
-            Flags::ThreeByte(_, ref mut b, ref mut c) => {
-                if *c == OVERLAP_CLASSIFICATION_CODE {
-                    *b |= 8;
-                    *c = u8::from(Classification::Unclassified);
-                }
-            }
+            Flags::ThreeByte(..) => {}
         }
     }
 }
@@ -786,27 +845,116 @@ impl PartialEq for Flags {
     }
 }
 
+impl ScanAngle {
+    /// Creates a rank from degrees.
+    ///
+    /// Not-a-number is always an error.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::raw::point::{OutOfRange, Rounding, ScanAngle};
+    /// let scan_angle = ScanAngle::rank(-12.7, Rounding::TowardZero, OutOfRange::Error).unwrap();
+    /// assert_eq!(ScanAngle::Rank(-12), scan_angle);
+    /// ```
+    pub fn rank(degrees: f32, rounding: Rounding, out_of_range: OutOfRange) -> Result<ScanAngle> {
+        let n = to_integer(degrees, 1., MAX_RANK, rounding, out_of_range)?;
+        Ok(ScanAngle::Rank(n as i8))
+    }
+
+    /// Creates a scaled angle from degrees.
+    ///
+    /// Not-a-number is always an error.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::raw::point::{OutOfRange, Rounding, ScanAngle};
+    /// let scan_angle = ScanAngle::scaled(-0.01, Rounding::Nearest, OutOfRange::Error).unwrap();
+    /// assert_eq!(ScanAngle::Scaled(-2), scan_angle);
+    /// ```
+    pub fn scaled(degrees: f32, rounding: Rounding, out_of_range: OutOfRange) -> Result<ScanAngle> {
+        let n = to_integer(
+            degrees,
+            SCAN_ANGLE_SCALE_FACTOR,
+            MAX_SCALED,
+            rounding,
+            out_of_range,
+        )?;
+        Ok(ScanAngle::Scaled(n as i16))
+    }
+
+    /// Returns this angle in degrees.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::raw::point::ScanAngle;
+    /// assert_eq!(0.6, ScanAngle::Scaled(100).degrees());
+    /// ```
+    pub fn degrees(self) -> f32 {
+        self.into()
+    }
+
+    /// Returns this angle as a rank.
+    ///
+    /// Ranks between -90 and 90 are returned as they are.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::raw::point::{OutOfRange, Rounding, ScanAngle};
+    /// let scan_angle = ScanAngle::Scaled(-15_083); // -90.498 degrees
+    /// assert_eq!(-90, scan_angle.to_rank(Rounding::Nearest, OutOfRange::Error).unwrap());
+    /// assert!(ScanAngle::Rank(100).to_rank(Rounding::Nearest, OutOfRange::Error).is_err());
+    /// ```
+    pub fn to_rank(self, rounding: Rounding, out_of_range: OutOfRange) -> Result<i8> {
+        match self {
+            ScanAngle::Rank(n) if f64::from(n).abs() <= MAX_RANK => Ok(n),
+            _ => ScanAngle::rank(self.degrees(), rounding, out_of_range).map(i8::from),
+        }
+    }
+
+    /// Returns this angle as a scaled integer, in increments of 0.006 degrees.
+    ///
+    /// Scaled angles between -30,000 and 30,000 are returned as they are.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::raw::point::{OutOfRange, Rounding, ScanAngle};
+    /// let scan_angle = ScanAngle::Rank(-90);
+    /// assert_eq!(-15_000, scan_angle.to_scaled(Rounding::Nearest, OutOfRange::Error).unwrap());
+    /// ```
+    pub fn to_scaled(self, rounding: Rounding, out_of_range: OutOfRange) -> Result<i16> {
+        match self {
+            ScanAngle::Scaled(n) if f64::from(n).abs() <= MAX_SCALED => Ok(n),
+            _ => ScanAngle::scaled(self.degrees(), rounding, out_of_range).map(i16::from),
+        }
+    }
+}
+
 impl Default for ScanAngle {
     fn default() -> ScanAngle {
         ScanAngle::Rank(0)
     }
 }
 
+/// Rounds to the nearest rank, clamped to -90 and 90.
 impl From<ScanAngle> for i8 {
     fn from(scan_angle: ScanAngle) -> i8 {
-        match scan_angle {
-            ScanAngle::Rank(n) => n,
-            ScanAngle::Scaled(_) => f32::from(scan_angle).round() as i8,
-        }
+        scan_angle
+            .to_rank(Rounding::Nearest, OutOfRange::Clamp)
+            .unwrap_or_default()
     }
 }
 
+/// Rounds to the nearest scaled angle, clamped to -30,000 and 30,000.
 impl From<ScanAngle> for i16 {
     fn from(scan_angle: ScanAngle) -> i16 {
-        match scan_angle {
-            ScanAngle::Rank(n) => ScanAngle::from(f32::from(n)).into(),
-            ScanAngle::Scaled(n) => n,
-        }
+        scan_angle
+            .to_scaled(Rounding::Nearest, OutOfRange::Clamp)
+            .unwrap_or_default()
     }
 }
 
@@ -819,9 +967,12 @@ impl From<ScanAngle> for f32 {
     }
 }
 
+/// Rounds to the nearest scaled angle, clamped to -180 and 180 degrees.
+///
+/// Not-a-number becomes zero.
 impl From<f32> for ScanAngle {
     fn from(n: f32) -> ScanAngle {
-        ScanAngle::Scaled((n / SCAN_ANGLE_SCALE_FACTOR) as i16)
+        ScanAngle::scaled(n, Rounding::Nearest, OutOfRange::Clamp).unwrap_or_default()
     }
 }
 
@@ -831,6 +982,27 @@ impl PartialEq for ScanAngle {
     }
 }
 
+fn to_integer(
+    degrees: f32,
+    scale: f32,
+    max: f64,
+    rounding: Rounding,
+    out_of_range: OutOfRange,
+) -> Result<f64> {
+    let n = f64::from(degrees) / f64::from(scale);
+    let n = match rounding {
+        Rounding::Nearest => n.round(),
+        Rounding::TowardZero => n.trunc(),
+    };
+    if n.abs() <= max {
+        Ok(n)
+    } else if out_of_range == OutOfRange::Clamp && !n.is_nan() {
+        Ok(n.clamp(-max, max))
+    } else {
+        Err(Error::ScanAngle(degrees).into())
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -853,6 +1025,9 @@ mod tests {
                     if format.has_waveform {
                         point.waveform = Some(Waveform::default());
                     }
+                    if format.has_nir {
+                        point.nir = Some(42);
+                    }
                     let mut cursor = Cursor::new(Vec::new());
                     point.write_to(&mut cursor, &format).unwrap();
                     cursor.set_position(0);
@@ -910,6 +1085,84 @@ mod tests {
         assert_eq!(90i8, ScanAngle::Scaled(15_000).into());
         assert_eq!(-15_000i16, ScanAngle::Rank(-90).into());
         assert_eq!(15_000i16, ScanAngle::Rank(90).into());
+        assert_eq!(90i8, ScanAngle::Rank(127).into());
+        assert_eq!(-90i8, ScanAngle::Scaled(i16::MIN).into());
+        assert_eq!(30_000i16, ScanAngle::Scaled(i16::MAX).into());
+        assert_eq!(ScanAngle::Scaled(0), ScanAngle::from(f32::NAN));
+        assert_eq!(ScanAngle::Scaled(-30_000), ScanAngle::from(-1000.));
+    }
+
+    #[test]
+    fn scan_angle_policies() {
+        use super::OutOfRange::{Clamp, Error};
+        use super::Rounding::{Nearest, TowardZero};
+
+        assert_eq!(
+            ScanAngle::Rank(13),
+            ScanAngle::rank(12.5, Nearest, Error).unwrap()
+        );
+        assert_eq!(
+            ScanAngle::Rank(-13),
+            ScanAngle::rank(-12.5, Nearest, Error).unwrap()
+        );
+        assert_eq!(
+            ScanAngle::Rank(12),
+            ScanAngle::rank(12.9, TowardZero, Error).unwrap()
+        );
+        assert_eq!(
+            ScanAngle::Rank(90),
+            ScanAngle::rank(90.4, Nearest, Error).unwrap()
+        );
+        assert!(ScanAngle::rank(90.5, Nearest, Error).is_err());
+        assert_eq!(
+            ScanAngle::Rank(90),
+            ScanAngle::rank(90.9, TowardZero, Error).unwrap()
+        );
+        assert_eq!(
+            ScanAngle::Rank(-90),
+            ScanAngle::rank(-1e9, Nearest, Clamp).unwrap()
+        );
+        assert!(ScanAngle::rank(f32::NAN, Nearest, Clamp).is_err());
+        assert!(ScanAngle::scaled(180.01, Nearest, Error).is_err());
+        assert_eq!(
+            ScanAngle::Scaled(30_000),
+            ScanAngle::scaled(f32::INFINITY, Nearest, Clamp).unwrap()
+        );
+        assert_eq!(
+            21_167,
+            ScanAngle::Rank(127).to_scaled(Nearest, Error).unwrap()
+        );
+        assert!(ScanAngle::Scaled(30_001).to_scaled(Nearest, Error).is_err());
+        assert!(ScanAngle::Scaled(15_100).to_rank(Nearest, Error).is_err());
+        assert_eq!(
+            90,
+            ScanAngle::Scaled(15_100).to_rank(Nearest, Clamp).unwrap()
+        );
+        for n in -30_000..=30_000 {
+            assert_eq!(n, ScanAngle::Scaled(n).to_scaled(Nearest, Error).unwrap());
+            let degrees = ScanAngle::Scaled(n).degrees();
+            assert_eq!(
+                ScanAngle::Scaled(n),
+                ScanAngle::scaled(degrees, Nearest, Error).unwrap()
+            );
+        }
+    }
+
+    #[test]
+    fn write_out_of_range_rank() {
+        let format = Format::new(0).unwrap();
+        let point = Point {
+            scan_angle: ScanAngle::Scaled(16_000),
+            ..Default::default()
+        };
+        assert!(point.write_to(Vec::new(), &format).is_err());
+        let point = Point {
+            scan_angle: ScanAngle::Scaled(15_000),
+            ..Default::default()
+        };
+        let mut bytes = Vec::new();
+        point.write_to(&mut bytes, &format).unwrap();
+        assert_eq!(90, bytes[16] as i8);
     }
 
     #[test]
@@ -945,6 +1198,7 @@ mod tests {
         assert!(Flags::TwoByte(0, OVERLAP_CLASSIFICATION_CODE).is_overlap());
         assert!(!Flags::ThreeByte(0, 0, 0).is_overlap());
         assert!(Flags::ThreeByte(0, 8, 0).is_overlap());
+        assert!(!Flags::TwoByte(0, 28).is_overlap());
         assert_eq!(
             (0, OVERLAP_CLASSIFICATION_CODE),
             Flags::ThreeByte(0, 8, 0).to_two_bytes().unwrap()
@@ -48,7 +48,7 @@ use std::path::Path;
 #[cfg(feature = "laz")]
 use crate::compression::CompressedPointReader;
 
-use crate::{raw, Builder, Header, Point, Result, Vlr};
+use crate::{raw, Builder, Filter, Header, Point, Result, Vlr, Writer};
 use std::{cmp::Ordering, fmt::Debug};
 use thiserror::Error;
 
@@ -64,21 +64,16 @@ pub enum Error {
     OffsetToEvlrsTooSmall(u64),
 }
 
-#[inline]
-pub(crate) fn read_point_from<R: std::io::Read>(
-    mut source: &mut R,
-    header: &Header,
-) -> Result<Point> {
-    let point = raw::Point::read_from(&mut source, header.point_format())
-        .map(|raw_point| Point::new(raw_point, header.transforms()));
-    point
-}
-
 /// Trait to specify behaviour a a PointReader
 pub(crate) trait PointReader: Debug + Send {
-    fn read_next(&mut self) -> Option<Result<Point>>;
+    fn read_next_raw(&mut self) -> Option<Result<raw::Point>>;
     fn seek(&mut self, position: u64) -> Result<()>;
     fn header(&self) -> &Header;
+
+    fn read_next(&mut self) -> Option<Result<Point>> {
+        self.read_next_raw()
+            .map(|result| result.map(|raw_point| Point::new(raw_point, self.header().transforms())))
+    }
 }
 
 /// An iterator over of the points in a `Reader`.
@@ -97,6 +92,57 @@ impl<'a> Iterator for PointIterator<'a> {
     }
 }
 
+/// An iterator over the raw points in a `Read`.
+///
+/// This struct is generally created by calling `raw_points()` on `Reader`.
+pub struct RawPointIterator<'a> {
+    read_raw: Box<dyn FnMut() -> Option<Result<raw::Point>> + 'a>,
+}
+
+impl<'a> Iterator for RawPointIterator<'a> {
+    type Item = Result<raw::Point>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        (self.read_raw)()
+    }
+}
+
+impl<'a> Debug for RawPointIterator<'a> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("RawPointIterator").finish_non_exhaustive()
+    }
+}
+
+/// An iterator over the points in a `Reader` that pass a `Filter`.
+///
+/// This struct is generally created by calling `filtered_points()` on `Reader`.
+#[derive(Debug)]
+pub struct FilteredPointIterator<'a> {
+    point_reader: &'a mut dyn PointReader,
+    filter: Filter,
+    done: bool,
+}
+
+impl<'a> Iterator for FilteredPointIterator<'a> {
+    type Item = Result<Point>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while !self.done {
+            match self.point_reader.read_next_raw() {
+                Some(Ok(raw_point)) => {
+                    let transforms = self.point_reader.header().transforms();
+                    if self.filter.matches_raw(&raw_point, transforms) {
+                        return Some(Ok(Point::new(raw_point, transforms)));
+                    }
+                }
+                Some(Err(err)) => return Some(Err(err)),
+                None => self.done = true,
+            }
+        }
+        None
+    }
+}
+
 #[derive(Debug)]
 struct UncompressedPointReader<R: std::io::Read + Seek> {
     source: R,
@@ -107,10 +153,13 @@ struct UncompressedPointReader<R: std::io::Read + Seek> {
 }
 
 impl<R: std::io::Read + Seek + Debug + Send> PointReader for UncompressedPointReader<R> {
-    fn read_next(&mut self) -> Option<Result<Point>> {
+    fn read_next_raw(&mut self) -> Option<Result<raw::Point>> {
         if self.last_point_idx < self.header.number_of_points() {
             self.last_point_idx += 1;
-            Some(read_point_from(&mut self.source, &self.header))
+            Some(raw::Point::read_from(
+                &mut self.source,
+                self.header.point_format(),
+            ))
         } else {
             None
         }
@@ -153,6 +202,25 @@ pub trait Read {
     /// ```
     fn read(&mut self) -> Option<Result<Point>>;
 
+    /// Reads a raw point.
+    ///
+    /// `Reader` doesn't convert the record to a `Point`, so its integer coordinates and flag bytes
+    /// are exactly as they are stored, for both uncompressed and compressed data. The default
+    /// implementation converts the next `Point` back with the header's transforms.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use las::{Read, Reader};
+    /// let mut reader = Reader::from_path("tests/data/autzen.las").unwrap();
+    /// let point = reader.read_raw().unwrap().unwrap();
+    /// ```
+    fn read_raw(&mut self) -> Option<Result<raw::Point>> {
+        let transforms = *self.header().transforms();
+        self.read()
+            .map(|point| point.and_then(|point| point.into_raw(&transforms)))
+    }
+
     /// Seeks to the given point number, zero-indexed.
     ///
     /// Note that seeking on compressed (LAZ) data can be expensive as the reader
@@ -180,6 +248,21 @@ pub trait Read {
     /// let points = reader.points().collect::<Result<Vec<_>, _>>().unwrap();
     /// ```
     fn points(&mut self) -> PointIterator;
+
+    /// Returns an iterator over this reader's raw points.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use las::{Read, Reader};
+    /// let mut reader = Reader::from_path("tests/data/autzen.las").unwrap();
+    /// let points = reader.raw_points().collect::<Result<Vec<_>, _>>().unwrap();
+    /// ```
+    fn raw_points(&mut self) -> RawPointIterator<'_> {
+        RawPointIterator {
+            read_raw: Box::new(move || self.read_raw()),
+        }
+    }
 }
 
 /// Reads LAS data.
@@ -301,6 +384,11 @@ impl<'a> Read for Reader<'a> {
         self.point_reader.read_next()
     }
 
+    /// Reads a raw point.
+    fn read_raw(&mut self) -> Option<Result<raw::Point>> {
+        self.point_reader.read_next_raw()
+    }
+
     /// Seeks to the given point number, zero-indexed.
     fn seek(&mut self, position: u64) -> Result<()> {
         self.point_reader.seek(position)
@@ -315,6 +403,64 @@ impl<'a> Read for Reader<'a> {
 }
 
 impl<'a> Reader<'a> {
+    /// Returns an iterator over the points that pass this filter.
+    ///
+    /// Each record is tested before it is converted to a `Point`. If the filter can't match
+    /// anything inside the header's bounds, no records are read at all. Otherwise every record is
+    /// read, and decompressed for laz data, since spatial indexes and laz chunk tables aren't used
+    /// to skip records.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::point::Classification;
+    /// use las::{Filter, Reader};
+    /// let mut reader = Reader::from_path("tests/data/autzen.las").unwrap();
+    /// let filter = Filter::classification([Classification::Ground]);
+    /// for point in reader.filtered_points(filter) {
+    ///     assert_eq!(Classification::Ground, point.unwrap().classification);
+    /// }
+    /// ```
+    pub fn filtered_points(&mut self, filter: Filter) -> FilteredPointIterator<'_> {
+        let done = !filter.may_match(self.point_reader.header());
+        FilteredPointIterator {
+            point_reader: &mut *self.point_reader,
+            filter,
+            done,
+        }
+    }
+
+    /// Copies every remaining raw point record to a writer, returning the number copied.
+    ///
+    /// Records aren't converted to `Point`s, so a copy to a writer made with this reader's header
+    /// is byte-for-byte identical to a well-formed source file. The callback can change each
+    /// record before it's written.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::io::Cursor;
+    /// use las::{Read, Reader, Writer};
+    /// let mut reader = Reader::from_path("tests/data/autzen.las").unwrap();
+    /// let mut writer = Writer::new(Cursor::new(Vec::new()), reader.header().clone()).unwrap();
+    /// let n = reader.copy_raw(&mut writer, |point| point.user_data = 42).unwrap();
+    /// assert_eq!(106, n);
+    /// ```
+    pub fn copy_raw<W, F>(&mut self, writer: &mut Writer<W>, mut f: F) -> Result<u64>
+    where
+        W: 'static + std::io::Write + Seek + Debug + Send,
+        F: FnMut(&mut raw::Point),
+    {
+        let mut n = 0;
+        while let Some(point) = self.read_raw() {
+            let mut point = point?;
+            f(&mut point);
+            writer.write_raw(point)?;
+            n += 1;
+        }
+        Ok(n)
+    }
+
     /// Creates a new reader from a path.
     ///
     /// The underlying `File` is wrapped in a `BufReader` for performance reasons.
@@ -354,4 +500,95 @@ mod tests {
         assert_eq!(point, reader.read().unwrap().unwrap());
         assert!(reader.read().is_none());
     }
+
+    #[test]
+    fn raw_points() {
+        let mut reader = Reader::from_path("tests/data/autzen.las").unwrap();
+        let points = reader.points().collect::<Result<Vec<_>>>().unwrap();
+        reader.seek(0).unwrap();
+        let transforms = *reader.header().transforms();
+        let raw_points = reader.raw_points().collect::<Result<Vec<_>>>().unwrap();
+        assert_eq!(points.len(), raw_points.len());
+        for (point, raw_point) in points.into_iter().zip(raw_points) {
+            assert_eq!(point, Point::new(raw_point, &transforms));
+        }
+    }
+
+    #[test]
+    fn default_raw_points() {
+        #[derive(Debug)]
+        struct Points<'a>(Reader<'a>);
+
+        impl Read for Points<'_> {
+            fn header(&self) -> &Header {
+                self.0.header()
+            }
+            fn read(&mut self) -> Option<Result<Point>> {
+                self.0.read()
+            }
+            fn seek(&mut self, position: u64) -> Result<()> {
+                self.0.seek(position)
+            }
+            fn points(&mut self) -> PointIterator {
+                self.0.points()
+            }
+        }
+
+        let mut points = Points(Reader::from_path("tests/data/autzen.las").unwrap());
+        let raw_points = points.raw_points().collect::<Result<Vec<_>>>().unwrap();
+        let mut reader = Reader::from_path("tests/data/autzen.las").unwrap();
+        let expected = reader.raw_points().collect::<Result<Vec<_>>>().unwrap();
+        let transforms = *reader.header().transforms();
+        assert_eq!(expected.len(), raw_points.len());
+        for (expected, raw_point) in expected.into_iter().zip(raw_points) {
+            assert_eq!(
+                Point::new(expected, &transforms),
+                Point::new(raw_point, &transforms)
+            );
+        }
+    }
+
+    #[test]
+    fn copy_raw_out_of_spec_scan_angles() {
+        use crate::point::Format;
+        use crate::raw::point::ScanAngle;
+        use std::io::Cursor;
+
+        for (format, scan_angle) in [(0, ScanAngle::Rank(120)), (6, ScanAngle::Scaled(31_000))] {
+            let mut builder = Builder::from((1, 4));
+            builder.point_format = Format::new(format).unwrap();
+            let header = builder.into_header().unwrap();
+            let mut writer = Writer::new(Cursor::new(Vec::new()), header.clone()).unwrap();
+            writer
+                .write_raw(raw::Point {
+                    scan_angle,
+                    gps_time: Some(0.),
+                    ..Default::default()
+                })
+                .unwrap();
+            let original = writer.into_inner().unwrap().into_inner();
+            let mut reader = Reader::new(Cursor::new(original.clone())).unwrap();
+            let mut writer = Writer::new(Cursor::new(Vec::new()), header).unwrap();
+            assert_eq!(1, reader.copy_raw(&mut writer, |_| {}).unwrap());
+            assert!(original == writer.into_inner().unwrap().into_inner());
+        }
+    }
+
+    #[test]
+    fn filtered_points() {
+        let filter = Filter::first_return().and(!Filter::point_source_id(7326));
+        let mut reader = Reader::from_path("tests/data/autzen.las").unwrap();
+        let expected = reader
+            .points()
+            .map(|point| point.unwrap())
+            .filter(|point| filter.matches(point))
+            .collect::<Vec<_>>();
+        let mut reader = Reader::from_path("tests/data/autzen.las").unwrap();
+        let points = reader
+            .filtered_points(filter)
+            .collect::<Result<Vec<_>>>()
+            .unwrap();
+        assert!(!points.is_empty());
+        assert_eq!(expected, points);
+    }
 }
@@ -0,0 +1,337 @@
+//! Reorder point records along a space-filling curve or by time.
+//!
+//! Spatially coherent points compress better and make spatial queries faster. A `Sorter` reads raw
+//! records from a reader, orders them, and writes them to a writer without converting them to
+//! `Point`s:
+//!
+//! ```
+//! use std::io::Cursor;
+//! use las::sort::{Order, Sorter};
+//! use las::{Read, Reader, Writer};
+//!
+//! let mut reader = Reader::from_path("tests/data/autzen.las").unwrap();
+//! let mut writer = Writer::new(Cursor::new(Vec::new()), reader.header().clone()).unwrap();
+//! Sorter::new(Order::Hilbert).sort(&mut reader, &mut writer).unwrap();
+//! ```
+//!
+//! Inputs with more than `max_points_in_memory` points are sorted in runs that are spilled to
+//! temporary files and then merged.
+
+use crate::point::Format;
+use crate::{raw, Read, Result, Writer};
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+use std::fmt::Debug;
+use std::fs::{self, File};
+use std::io::{BufReader, BufWriter, Seek};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// The default number of points held in memory while sorting.
+pub const DEFAULT_MAX_POINTS_IN_MEMORY: usize = 1_000_000;
+
+static RUN_ID: AtomicUsize = AtomicUsize::new(0);
+
+/// The order to sort points in.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Order {
+    /// Morton (Z-order) curve over the integer x and y coordinates.
+    Morton,
+
+    /// Hilbert curve over the integer x and y coordinates.
+    Hilbert,
+
+    /// Ascending gps time, which restores acquisition order. Points without a time sort first.
+    GpsTime,
+}
+
+impl Order {
+    /// Returns the sort key of a raw point.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::raw;
+    /// use las::sort::Order;
+    /// let a = raw::Point { x: 0, y: 0, ..Default::default() };
+    /// let b = raw::Point { x: 1, y: 1, ..Default::default() };
+    /// assert!(Order::Morton.key(&a) < Order::Morton.key(&b));
+    /// ```
+    pub fn key(&self, point: &raw::Point) -> u64 {
+        match *self {
+            Order::Morton => morton(ordered(point.x), ordered(point.y)),
+            Order::Hilbert => hilbert(ordered(point.x), ordered(point.y)),
+            Order::GpsTime => match point.gps_time {
+                // Every time's key is above zero, except for negative NaNs with every bit set.
+                None => 0,
+                Some(gps_time) => {
+                    let bits = gps_time.to_bits();
+                    if bits >> 63 == 1 {
+                        !bits
+                    } else {
+                        bits | 1 << 63
+                    }
+                }
+            },
+        }
+    }
+}
+
+/// Sorts point records, spilling to temporary files when there are too many to hold in memory.
+#[derive(Clone, Debug)]
+pub struct Sorter {
+    /// The order to sort in.
+    pub order: Order,
+
+    /// The number of points held in memory at once.
+    ///
+    /// Each run of this many points is sorted in memory and, if there's more than one run, written
+    /// to a temporary file.
+    pub max_points_in_memory: usize,
+
+    /// The directory for temporary files.
+    pub temp_dir: PathBuf,
+}
+
+impl Sorter {
+    /// Creates a sorter with the default memory limit and the system's temporary directory.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::sort::{Order, Sorter};
+    /// let sorter = Sorter::new(Order::Morton);
+    /// ```
+    pub fn new(order: Order) -> Sorter {
+        Sorter {
+            order,
+            max_points_in_memory: DEFAULT_MAX_POINTS_IN_MEMORY,
+            temp_dir: std::env::temp_dir(),
+        }
+    }
+
+    /// Reads all remaining points from the reader and writes them, sorted, to the writer.
+    ///
+    /// Returns the number of points written. Points with equal keys keep their input order.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::io::Cursor;
+    /// use las::sort::{Order, Sorter};
+    /// use las::{Read, Reader, Writer};
+    ///
+    /// let mut reader = Reader::from_path("tests/data/autzen.las").unwrap();
+    /// let mut writer = Writer::new(Cursor::new(Vec::new()), reader.header().clone()).unwrap();
+    /// let n = Sorter::new(Order::GpsTime).sort(&mut reader, &mut writer).unwrap();
+    /// assert_eq!(106, n);
+    /// ```
+    pub fn sort<R, W>(&self, reader: &mut R, writer: &mut Writer<W>) -> Result<u64>
+    where
+        R: Read,
+        W: 'static + std::io::Write + Seek + Debug + Send,
+    {
+        let format = *reader.header().point_format();
+        let max_points_in_memory = self.max_points_in_memory.max(1);
+        let mut runs = Vec::new();
+        let mut points = Vec::new();
+        let result = (|| {
+            for point in reader.raw_points() {
+                points.push(point?);
+                if points.len() == max_points_in_memory {
+                    runs.push(self.spill(&mut points, &format)?);
+                }
+            }
+            if runs.is_empty() {
+                self.sort_run(&mut points);
+                let n = points.len() as u64;
+                for point in points.drain(..) {
+                    writer.write_raw(point)?;
+                }
+                Ok(n)
+            } else {
+                if !points.is_empty() {
+                    runs.push(self.spill(&mut points, &format)?);
+                }
+                self.merge(&runs, &format, writer)
+            }
+        })();
+        for run in runs {
+            let _ = fs::remove_file(run);
+        }
+        result
+    }
+
+    fn sort_run(&self, points: &mut [raw::Point]) {
+        points.sort_by_cached_key(|point| self.order.key(point));
+    }
+
+    fn spill(&self, points: &mut Vec<raw::Point>, format: &Format) -> Result<PathBuf> {
+        self.sort_run(points);
+        let path = self.temp_dir.join(format!(
+            "las-sort-{}-{}.tmp",
+            std::process::id(),
+            RUN_ID.fetch_add(1, Ordering::Relaxed)
+        ));
+        let mut write = BufWriter::new(File::create(&path)?);
+        for point in points.drain(..) {
+            point.write_to(&mut write, format)?;
+        }
+        std::io::Write::flush(&mut write)?;
+        Ok(path)
+    }
+
+    fn merge<W>(&self, runs: &[PathBuf], format: &Format, writer: &mut Writer<W>) -> Result<u64>
+    where
+        W: 'static + std::io::Write + Seek + Debug + Send,
+    {
+        let mut lens = Vec::with_capacity(runs.len());
+        let mut reads = Vec::with_capacity(runs.len());
+        for run in runs {
+            lens.push(fs::metadata(run)?.len() / u64::from(format.len()));
+            reads.push(BufReader::new(File::open(run)?));
+        }
+        let mut heap = BinaryHeap::new();
+        let mut heads = Vec::with_capacity(runs.len());
+        for (i, read) in reads.iter_mut().enumerate() {
+            let head = if lens[i] > 0 {
+                lens[i] -= 1;
+                let point = raw::Point::read_from(read, format)?;
+                heap.push(Reverse((self.order.key(&point), i)));
+                Some(point)
+            } else {
+                None
+            };
+            heads.push(head);
+        }
+        let mut n = 0;
+        while let Some(Reverse((_, i))) = heap.pop() {
+            let point = heads[i].take().expect("every run in the heap has a head");
+            writer.write_raw(point)?;
+            n += 1;
+            if lens[i] > 0 {
+                lens[i] -= 1;
+                let point = raw::Point::read_from(&mut reads[i], format)?;
+                heap.push(Reverse((self.order.key(&point), i)));
+                heads[i] = Some(point);
+            }
+        }
+        Ok(n)
+    }
+}
+
+impl Default for Sorter {
+    fn default() -> Sorter {
+        Sorter::new(Order::Morton)
+    }
+}
+
+/// Maps an i32 to a u32 with the same ordering.
+fn ordered(n: i32) -> u32 {
+    (n as u32) ^ (1 << 31)
+}
+
+fn morton(x: u32, y: u32) -> u64 {
+    spread(x) | spread(y) << 1
+}
+
+/// Spreads the bits of n out so there's a zero between each of them.
+fn spread(n: u32) -> u64 {
+    let mut n = u64::from(n);
+    n = (n | n << 16) & 0x0000_ffff_0000_ffff;
+    n = (n | n << 8) & 0x00ff_00ff_00ff_00ff;
+    n = (n | n << 4) & 0x0f0f_0f0f_0f0f_0f0f;
+    n = (n | n << 2) & 0x3333_3333_3333_3333;
+    (n | n << 1) & 0x5555_5555_5555_5555
+}
+
+fn hilbert(mut x: u32, mut y: u32) -> u64 {
+    let mut d = 0;
+    let mut s = 1u32 << 31;
+    while s > 0 {
+        let rx = x & s > 0;
+        let ry = y & s > 0;
+        d += u64::from(s) * u64::from(s) * ((3 * u64::from(rx)) ^ u64::from(ry));
+        if !ry {
+            if rx {
+                x = !x;
+                y = !y;
+            }
+            std::mem::swap(&mut x, &mut y);
+        }
+        s >>= 1;
+    }
+    d
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Reader;
+    use std::io::Cursor;
+
+    fn sorted(order: Order, max_points_in_memory: usize) -> Vec<raw::Point> {
+        let mut reader = Reader::from_path("tests/data/autzen.las").unwrap();
+        let mut writer = Writer::new(Cursor::new(Vec::new()), reader.header().clone()).unwrap();
+        let sorter = Sorter {
+            max_points_in_memory,
+            ..Sorter::new(order)
+        };
+        assert_eq!(106, sorter.sort(&mut reader, &mut writer).unwrap());
+        let mut reader = Reader::new(writer.into_inner().unwrap()).unwrap();
+        reader.raw_points().collect::<Result<Vec<_>>>().unwrap()
+    }
+
+    #[test]
+    fn morton_bits() {
+        assert_eq!(0b11, morton(1, 1));
+        assert_eq!(0b10, morton(0, 1));
+        assert_eq!(u64::MAX, morton(u32::MAX, u32::MAX));
+    }
+
+    #[test]
+    fn hilbert_visits_neighbors() {
+        let n = 8;
+        let mut cells = (0..n)
+            .flat_map(|x| (0..n).map(move |y| (x, y)))
+            .collect::<Vec<_>>();
+        cells.sort_by_key(|&(x, y)| hilbert(x, y));
+        for pair in cells.windows(2) {
+            let (a, b) = (pair[0], pair[1]);
+            assert_eq!(1, a.0.abs_diff(b.0) + a.1.abs_diff(b.1), "{:?}", pair);
+        }
+    }
+
+    #[test]
+    fn ordered_keeps_order() {
+        assert!(ordered(-1) < ordered(0));
+        assert!(ordered(i32::MIN) < ordered(i32::MAX));
+    }
+
+    #[test]
+    fn external_matches_in_memory() {
+        for &order in &[Order::Morton, Order::Hilbert, Order::GpsTime] {
+            let in_memory = sorted(order, DEFAULT_MAX_POINTS_IN_MEMORY);
+            let keys = in_memory
+                .iter()
+                .map(|point| order.key(point))
+                .collect::<Vec<_>>();
+            assert!(keys.windows(2).all(|pair| pair[0] <= pair[1]));
+            assert_eq!(in_memory, sorted(order, 10));
+        }
+    }
+
+    #[test]
+    fn gps_time_key() {
+        let point = |gps_time| raw::Point {
+            gps_time,
+            ..Default::default()
+        };
+        let key = |gps_time| Order::GpsTime.key(&point(gps_time));
+        assert!(key(None) < key(Some(f64::NEG_INFINITY)));
+        assert!(key(Some(f64::NEG_INFINITY)) < key(Some(-1e9)));
+        assert!(key(Some(-1.)) < key(Some(0.)));
+        assert!(key(Some(0.)) < key(Some(1.)));
+        assert!(key(Some(1.)) < key(Some(2.)));
+    }
+}
@@ -0,0 +1,399 @@
+//! Per-attribute point statistics.
+//!
+//! `Stats` streams points and keeps summaries, histograms, and counts that are useful for quality
+//! control:
+//!
+//! ```
+//! use las::stats::Stats;
+//! use las::Reader;
+//!
+//! let mut reader = Reader::from_path("tests/data/autzen.las").unwrap();
+//! let stats = Stats::from_reader(&mut reader).unwrap();
+//! assert_eq!(106, stats.count);
+//! assert!(stats.z.stddev().unwrap() > 0.);
+//! ```
+//!
+//! With the `serde` feature, `Stats` can be serialized, e.g. to JSON for a report.
+
+use crate::{Point, Read, Result};
+use std::collections::BTreeMap;
+
+/// Running count, min, max, mean, and standard deviation of one attribute.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Summary {
+    count: u64,
+    min: f64,
+    max: f64,
+    mean: f64,
+    m2: f64,
+}
+
+impl Summary {
+    /// Adds a value.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::stats::Summary;
+    /// let mut summary = Summary::default();
+    /// summary.add(1.);
+    /// summary.add(3.);
+    /// assert_eq!(Some(2.), summary.mean());
+    /// ```
+    pub fn add(&mut self, value: f64) {
+        if self.count == 0 {
+            self.min = value;
+            self.max = value;
+        } else {
+            self.min = self.min.min(value);
+            self.max = self.max.max(value);
+        }
+        self.count += 1;
+        let delta = value - self.mean;
+        self.mean += delta / self.count as f64;
+        self.m2 += delta * (value - self.mean);
+    }
+
+    /// Returns the number of values.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::stats::Summary;
+    /// assert_eq!(0, Summary::default().count());
+    /// ```
+    pub fn count(&self) -> u64 {
+        self.count
+    }
+
+    /// Returns the smallest value, or `None` if there are no values.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::stats::Summary;
+    /// let mut summary = Summary::default();
+    /// assert_eq!(None, summary.min());
+    /// summary.add(1.);
+    /// assert_eq!(Some(1.), summary.min());
+    /// ```
+    pub fn min(&self) -> Option<f64> {
+        self.value(self.min)
+    }
+
+    /// Returns the largest value, or `None` if there are no values.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::stats::Summary;
+    /// let mut summary = Summary::default();
+    /// summary.add(1.);
+    /// summary.add(2.);
+    /// assert_eq!(Some(2.), summary.max());
+    /// ```
+    pub fn max(&self) -> Option<f64> {
+        self.value(self.max)
+    }
+
+    /// Returns the mean, or `None` if there are no values.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::stats::Summary;
+    /// let mut summary = Summary::default();
+    /// summary.add(1.);
+    /// summary.add(2.);
+    /// assert_eq!(Some(1.5), summary.mean());
+    /// ```
+    pub fn mean(&self) -> Option<f64> {
+        self.value(self.mean)
+    }
+
+    /// Returns the population standard deviation, or `None` if there are no values.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::stats::Summary;
+    /// let mut summary = Summary::default();
+    /// summary.add(1.);
+    /// summary.add(3.);
+    /// assert_eq!(Some(1.), summary.stddev());
+    /// ```
+    pub fn stddev(&self) -> Option<f64> {
+        self.value((self.m2 / self.count as f64).sqrt())
+    }
+
+    fn value(&self, value: f64) -> Option<f64> {
+        if self.count == 0 {
+            None
+        } else {
+            Some(value)
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl serde::Serialize for Summary {
+    fn serialize<S: serde::Serializer>(
+        &self,
+        serializer: S,
+    ) -> std::result::Result<S::Ok, S::Error> {
+        use serde::ser::SerializeStruct;
+        if self.count == 0 {
+            return serializer.serialize_none();
+        }
+        let mut state = serializer.serialize_struct("Summary", 5)?;
+        state.serialize_field("count", &self.count)?;
+        state.serialize_field("min", &self.min)?;
+        state.serialize_field("max", &self.max)?;
+        state.serialize_field("mean", &self.mean)?;
+        state.serialize_field("stddev", &self.stddev())?;
+        state.end()
+    }
+}
+
+/// A histogram with equal-width bins.
+///
+/// Values below the first bin are counted in the first bin, and values above the last bin are
+/// counted in the last.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct Histogram {
+    /// The lower edge of the first bin.
+    pub min: f64,
+
+    /// The width of each bin.
+    pub width: f64,
+
+    /// The number of values in each bin.
+    pub counts: Vec<u64>,
+}
+
+impl Histogram {
+    /// Creates an empty histogram.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::stats::Histogram;
+    /// let histogram = Histogram::new(0., 10., 5);
+    /// assert_eq!(vec![0; 5], histogram.counts);
+    /// ```
+    pub fn new(min: f64, width: f64, bins: usize) -> Histogram {
+        Histogram {
+            min,
+            width,
+            counts: vec![0; bins],
+        }
+    }
+
+    /// Adds a value.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::stats::Histogram;
+    /// let mut histogram = Histogram::new(0., 10., 5);
+    /// histogram.add(15.);
+    /// histogram.add(1000.);
+    /// assert_eq!(vec![0, 1, 0, 0, 1], histogram.counts);
+    /// ```
+    pub fn add(&mut self, value: f64) {
+        if self.counts.is_empty() {
+            return;
+        }
+        let bin = ((value - self.min) / self.width).floor();
+        let bin = if bin > 0. { bin as usize } else { 0 };
+        let last = self.counts.len() - 1;
+        self.counts[bin.min(last)] += 1;
+    }
+}
+
+/// Statistics over a set of points.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct Stats {
+    /// The number of points.
+    pub count: u64,
+
+    /// The x coordinates.
+    pub x: Summary,
+
+    /// The y coordinates.
+    pub y: Summary,
+
+    /// The z coordinates.
+    pub z: Summary,
+
+    /// The intensities.
+    pub intensity: Summary,
+
+    /// The scan angles, in degrees.
+    pub scan_angle: Summary,
+
+    /// The user data.
+    pub user_data: Summary,
+
+    /// The gps times, for the points that have them.
+    pub gps_time: Summary,
+
+    /// The red channel, for the points that have color.
+    pub red: Summary,
+
+    /// The green channel, for the points that have color.
+    pub green: Summary,
+
+    /// The blue channel, for the points that have color.
+    pub blue: Summary,
+
+    /// The near infrared channel, for the points that have it.
+    pub nir: Summary,
+
+    /// Intensities in 256 bins that cover the full `u16` range.
+    pub intensity_histogram: Histogram,
+
+    /// Scan angles in one degree bins from -90 to 90 degrees.
+    pub scan_angle_histogram: Histogram,
+
+    /// The number of points in each classification.
+    pub classifications: BTreeMap<u8, u64>,
+
+    /// The number of points from each point source.
+    pub point_source_ids: BTreeMap<u16, u64>,
+
+    /// The number of points by return number and number of returns, indexed by
+    /// `returns[return_number][number_of_returns]`.
+    pub returns: [[u64; 16]; 16],
+}
+
+impl Stats {
+    /// Computes statistics over the remaining points in a reader.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::stats::Stats;
+    /// use las::Reader;
+    /// let mut reader = Reader::from_path("tests/data/autzen.las").unwrap();
+    /// let stats = Stats::from_reader(&mut reader).unwrap();
+    /// assert_eq!(106, stats.classifications.values().sum::<u64>());
+    /// ```
+    pub fn from_reader<R: Read>(reader: &mut R) -> Result<Stats> {
+        let mut stats = Stats::default();
+        for point in reader.points() {
+            stats.add(&point?);
+        }
+        Ok(stats)
+    }
+
+    /// Adds a point.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::stats::Stats;
+    /// use las::Point;
+    /// let mut stats = Stats::default();
+    /// stats.add(&Point { return_number: 1, number_of_returns: 2, ..Default::default() });
+    /// assert_eq!(1, stats.returns[1][2]);
+    /// assert_eq!(None, stats.gps_time.min());
+    /// ```
+    pub fn add(&mut self, point: &Point) {
+        self.count += 1;
+        self.x.add(point.x);
+        self.y.add(point.y);
+        self.z.add(point.z);
+        self.intensity.add(f64::from(point.intensity));
+        self.scan_angle.add(f64::from(point.scan_angle));
+        self.user_data.add(f64::from(point.user_data));
+        if let Some(gps_time) = point.gps_time {
+            self.gps_time.add(gps_time);
+        }
+        if let Some(color) = point.color {
+            self.red.add(f64::from(color.red));
+            self.green.add(f64::from(color.green));
+            self.blue.add(f64::from(color.blue));
+        }
+        if let Some(nir) = point.nir {
+            self.nir.add(f64::from(nir));
+        }
+        self.intensity_histogram.add(f64::from(point.intensity));
+        self.scan_angle_histogram.add(f64::from(point.scan_angle));
+        *self
+            .classifications
+            .entry(u8::from(point.classification))
+            .or_default() += 1;
+        *self
+            .point_source_ids
+            .entry(point.point_source_id)
+            .or_default() += 1;
+        self.returns[usize::from(point.return_number & 15)]
+            [usize::from(point.number_of_returns & 15)] += 1;
+    }
+}
+
+impl Default for Stats {
+    fn default() -> Stats {
+        Stats {
+            count: 0,
+            x: Summary::default(),
+            y: Summary::default(),
+            z: Summary::default(),
+            intensity: Summary::default(),
+            scan_angle: Summary::default(),
+            user_data: Summary::default(),
+            gps_time: Summary::default(),
+            red: Summary::default(),
+            green: Summary::default(),
+            blue: Summary::default(),
+            nir: Summary::default(),
+            intensity_histogram: Histogram::new(0., 256., 256),
+            scan_angle_histogram: Histogram::new(-90., 1., 180),
+            classifications: BTreeMap::new(),
+            point_source_ids: BTreeMap::new(),
+            returns: [[0; 16]; 16],
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Reader;
+
+    #[test]
+    fn summary_matches_two_pass() {
+        let values = [2., 4., 4., 4., 5., 5., 7., 9.];
+        let mut summary = Summary::default();
+        for &value in &values {
+            summary.add(value);
+        }
+        assert_eq!(Some(5.), summary.mean());
+        assert_eq!(Some(2.), summary.stddev());
+        assert_eq!(Some(2.), summary.min());
+        assert_eq!(Some(9.), summary.max());
+    }
+
+    #[test]
+    fn autzen() {
+        let mut reader = Reader::from_path("tests/data/autzen.las").unwrap();
+        let stats = Stats::from_reader(&mut reader).unwrap();
+        let header = reader.header();
+        assert_eq!(header.number_of_points(), stats.count);
+        assert_eq!(Some(header.bounds().min.z), stats.z.min());
+        assert_eq!(Some(header.bounds().max.x), stats.x.max());
+        assert_eq!(106, stats.intensity_histogram.counts.iter().sum::<u64>());
+        assert_eq!(106, stats.scan_angle_histogram.counts.iter().sum::<u64>());
+        assert_eq!(106, stats.point_source_ids.values().sum::<u64>());
+        for n in 1..=5 {
+            assert_eq!(
+                header.number_of_points_by_return(n).unwrap_or(0),
+                stats.returns[n as usize].iter().sum::<u64>()
+            );
+        }
+        assert_eq!(0, stats.red.count());
+    }
+}
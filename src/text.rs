@@ -0,0 +1,826 @@
+//! Read and write points as delimited text.
+//!
+//! Columns are described with the single letters used by txt2las, e.g. `"xyzirnc"`, or by name.
+//! A `TextReader` parses each line into a `Point` and can build a matching `Header`:
+//!
+//! ```
+//! use las::text::{self, TextReader};
+//!
+//! let text = "1.25 2.5 3.0 100 2\n4.0 5.0 6.125 200 9\n";
+//! let reader = TextReader::new(text.as_bytes(), text::columns("xyzic").unwrap());
+//! let (header, points) = reader.read_to_end().unwrap();
+//! assert_eq!(2, points.len());
+//! assert_eq!(0.001, header.transforms().z.scale);
+//! ```
+//!
+//! A `TextWriter` does the reverse, writing coordinates with as many decimals as the header's
+//! scales allow:
+//!
+//! ```
+//! use las::text::{self, TextWriter};
+//! use las::{Read, Reader, Write};
+//!
+//! let mut reader = Reader::from_path("tests/data/autzen.las").unwrap();
+//! let columns = text::columns("xyzi").unwrap();
+//! let mut writer = TextWriter::new(Vec::new(), reader.header().clone(), columns);
+//! writer.write_column_names().unwrap();
+//! for point in reader.points() {
+//!     writer.write(point.unwrap()).unwrap();
+//! }
+//! ```
+
+use crate::point::{Classification, Format, ScanDirection};
+use crate::{Bounds, Builder, Color, Header, Point, Result, Transform, Write};
+use std::io::BufRead;
+use thiserror::Error;
+
+/// The most decimals used when choosing scales from text.
+const MAX_DECIMALS: usize = 8;
+
+/// Text errors.
+#[derive(Error, Debug)]
+pub enum Error {
+    /// The column letter isn't recognized.
+    #[error("unknown column letter: {0}")]
+    ColumnLetter(char),
+
+    /// The column name isn't recognized.
+    #[error("unknown column name: {0}")]
+    ColumnName(String),
+
+    /// The text has no column names.
+    #[error("the text has no column names")]
+    NoColumnNames,
+
+    /// A line doesn't have a field for every column.
+    #[error("line {line} has {found} fields, but {expected} columns were expected")]
+    #[allow(missing_docs)]
+    FieldCount {
+        line: usize,
+        expected: usize,
+        found: usize,
+    },
+
+    /// A field couldn't be parsed as its column's value.
+    #[error("line {line}: invalid {column} value: {field}")]
+    #[allow(missing_docs)]
+    Field {
+        line: usize,
+        column: &'static str,
+        field: String,
+    },
+}
+
+/// A column of delimited point text.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Column {
+    /// The x coordinate, `x`.
+    X,
+    /// The y coordinate, `y`.
+    Y,
+    /// The z coordinate, `z`.
+    Z,
+    /// The gps time, `t`.
+    GpsTime,
+    /// The intensity, `i`.
+    Intensity,
+    /// The scan angle, `a`.
+    ScanAngle,
+    /// The return number, `r`.
+    ReturnNumber,
+    /// The number of returns, `n`.
+    NumberOfReturns,
+    /// The classification, `c`.
+    Classification,
+    /// The user data, `u`.
+    UserData,
+    /// The point source id, `p`.
+    PointSourceId,
+    /// The edge of flight line flag, `e`.
+    EdgeOfFlightLine,
+    /// The scan direction flag, `d`.
+    ScanDirection,
+    /// The red channel, `R`.
+    Red,
+    /// The green channel, `G`.
+    Green,
+    /// The blue channel, `B`.
+    Blue,
+    /// The near infrared channel, `I`.
+    Nir,
+    /// A column to ignore, `s`.
+    Skip,
+}
+
+impl Column {
+    /// Returns the column for a txt2las letter.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::text::Column;
+    /// assert_eq!(Column::Red, Column::from_letter('R').unwrap());
+    /// assert!(Column::from_letter('?').is_err());
+    /// ```
+    pub fn from_letter(letter: char) -> Result<Column> {
+        Ok(match letter {
+            'x' => Column::X,
+            'y' => Column::Y,
+            'z' => Column::Z,
+            't' => Column::GpsTime,
+            'i' => Column::Intensity,
+            'a' => Column::ScanAngle,
+            'r' => Column::ReturnNumber,
+            'n' => Column::NumberOfReturns,
+            'c' => Column::Classification,
+            'u' => Column::UserData,
+            'p' => Column::PointSourceId,
+            'e' => Column::EdgeOfFlightLine,
+            'd' => Column::ScanDirection,
+            'R' => Column::Red,
+            'G' => Column::Green,
+            'B' => Column::Blue,
+            'I' => Column::Nir,
+            's' => Column::Skip,
+            _ => return Err(Error::ColumnLetter(letter).into()),
+        })
+    }
+
+    /// Returns the column with this name, ignoring case.
+    ///
+    /// Single letters are also accepted, as long as they aren't ambiguous without case.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::text::Column;
+    /// assert_eq!(Column::GpsTime, Column::from_name("GPS_Time").unwrap());
+    /// assert_eq!(Column::X, Column::from_name("X").unwrap());
+    /// assert_eq!(Column::GpsTime, Column::from_name("t").unwrap());
+    /// assert!(Column::from_name("i").is_err()); // intensity or nir?
+    /// ```
+    pub fn from_name(name: &str) -> Result<Column> {
+        let lowercase = name.trim().to_lowercase();
+        ALL.iter()
+            .find(|column| column.name() == lowercase)
+            .copied()
+            .or_else(|| {
+                let mut chars = lowercase.chars();
+                match (chars.next(), chars.next()) {
+                    (Some(letter), None) if !"rgbis".contains(letter) => {
+                        Column::from_letter(letter).ok()
+                    }
+                    _ => None,
+                }
+            })
+            .ok_or_else(|| Error::ColumnName(name.to_string()).into())
+    }
+
+    /// Returns this column's name.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::text::Column;
+    /// assert_eq!("point_source_id", Column::PointSourceId.name());
+    /// ```
+    pub fn name(&self) -> &'static str {
+        match *self {
+            Column::X => "x",
+            Column::Y => "y",
+            Column::Z => "z",
+            Column::GpsTime => "gps_time",
+            Column::Intensity => "intensity",
+            Column::ScanAngle => "scan_angle",
+            Column::ReturnNumber => "return_number",
+            Column::NumberOfReturns => "number_of_returns",
+            Column::Classification => "classification",
+            Column::UserData => "user_data",
+            Column::PointSourceId => "point_source_id",
+            Column::EdgeOfFlightLine => "edge_of_flight_line",
+            Column::ScanDirection => "scan_direction",
+            Column::Red => "red",
+            Column::Green => "green",
+            Column::Blue => "blue",
+            Column::Nir => "nir",
+            Column::Skip => "skip",
+        }
+    }
+}
+
+const ALL: [Column; 18] = [
+    Column::X,
+    Column::Y,
+    Column::Z,
+    Column::GpsTime,
+    Column::Intensity,
+    Column::ScanAngle,
+    Column::ReturnNumber,
+    Column::NumberOfReturns,
+    Column::Classification,
+    Column::UserData,
+    Column::PointSourceId,
+    Column::EdgeOfFlightLine,
+    Column::ScanDirection,
+    Column::Red,
+    Column::Green,
+    Column::Blue,
+    Column::Nir,
+    Column::Skip,
+];
+
+/// Parses a txt2las column spec, one letter per column.
+///
+/// # Examples
+///
+/// ```
+/// use las::text::{self, Column};
+/// let columns = text::columns("xyzi").unwrap();
+/// assert_eq!(vec![Column::X, Column::Y, Column::Z, Column::Intensity], columns);
+/// ```
+pub fn columns(spec: &str) -> Result<Vec<Column>> {
+    spec.chars().map(Column::from_letter).collect()
+}
+
+/// Splits a line into fields on commas, semicolons, tabs, or spaces.
+fn fields(line: &str) -> Vec<&str> {
+    line.split(|c: char| c == ',' || c == ';' || c.is_whitespace())
+        .filter(|field| !field.is_empty())
+        .collect()
+}
+
+/// Reads points from delimited text.
+///
+/// Blank lines and lines that start with `#` are skipped. Classification 12 is read as it is in
+/// the legacy point formats, as an unclassified overlap point; `read_to_end` turns those into
+/// `Classification::OverlapPoints` if it picks an extended format.
+#[derive(Debug)]
+pub struct TextReader<R: BufRead> {
+    read: R,
+    columns: Vec<Column>,
+    line: usize,
+    decimals: [usize; 3],
+}
+
+impl<R: BufRead> TextReader<R> {
+    /// Creates a reader with the given columns.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::text::{self, TextReader};
+    /// let mut reader = TextReader::new("1,2,3".as_bytes(), text::columns("xyz").unwrap());
+    /// assert_eq!(3., reader.next().unwrap().unwrap().z);
+    /// ```
+    pub fn new(read: R, columns: Vec<Column>) -> TextReader<R> {
+        TextReader {
+            read,
+            columns,
+            line: 0,
+            decimals: [0; 3],
+        }
+    }
+
+    /// Creates a reader whose columns are named by the first line of text.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::text::{Column, TextReader};
+    /// let text = "X,Y,Z,Classification\n1,2,3,2\n";
+    /// let reader = TextReader::from_column_names(text.as_bytes()).unwrap();
+    /// assert_eq!(Column::Classification, reader.columns()[3]);
+    /// ```
+    pub fn from_column_names(mut read: R) -> Result<TextReader<R>> {
+        let mut line = String::new();
+        if read.read_line(&mut line)? == 0 {
+            return Err(Error::NoColumnNames.into());
+        }
+        let columns = fields(line.trim_start_matches('#'))
+            .into_iter()
+            .map(|name| Column::from_name(name.trim_matches('"')))
+            .collect::<Result<Vec<_>>>()?;
+        let mut reader = TextReader::new(read, columns);
+        reader.line = 1;
+        Ok(reader)
+    }
+
+    /// Returns the columns.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::text::{self, Column, TextReader};
+    /// let reader = TextReader::new("".as_bytes(), text::columns("xyz").unwrap());
+    /// assert_eq!(&[Column::X, Column::Y, Column::Z], reader.columns());
+    /// ```
+    pub fn columns(&self) -> &[Column] {
+        &self.columns
+    }
+
+    /// Reads every remaining point and builds a header for them.
+    ///
+    /// The point format is the smallest one that holds every column, and each coordinate's scale
+    /// is set by the most decimals seen in its column, counting any exponent, so `1.5e-3` has four.
+    /// Return numbers above seven, classifications above 31, and scan angles beyond ±90° need an
+    /// extended format.
+    /// Points that need an extended format get a gps time of zero if there's no time column.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::text::{self, TextReader};
+    /// let reader = TextReader::new("1 2 3 4\n".as_bytes(), text::columns("xyzt").unwrap());
+    /// let (header, _) = reader.read_to_end().unwrap();
+    /// assert!(header.point_format().has_gps_time);
+    /// ```
+    pub fn read_to_end(mut self) -> Result<(Header, Vec<Point>)> {
+        let mut points = self.by_ref().collect::<Result<Vec<_>>>()?;
+        let mut bounds = Bounds::default();
+        for point in &points {
+            bounds.grow(point);
+        }
+        let is_extended = points.iter().any(|point| {
+            point.return_number > 7
+                || point.number_of_returns > 7
+                || u8::from(point.classification) > 31
+                || point.scan_angle.abs() > 90.
+        });
+        let has = |columns: &[Column]| self.columns.iter().any(|c| columns.contains(c));
+        let has_gps_time = has(&[Column::GpsTime]);
+        let has_color = has(&[Column::Red, Column::Green, Column::Blue]);
+        let has_nir = has(&[Column::Nir]);
+        let mut builder = Builder::from((1, if is_extended || has_nir { 4 } else { 2 }));
+        builder.point_format = Format::new(if has_nir {
+            8
+        } else if is_extended {
+            if has_color {
+                7
+            } else {
+                6
+            }
+        } else {
+            match (has_gps_time, has_color) {
+                (false, false) => 0,
+                (true, false) => 1,
+                (false, true) => 2,
+                (true, true) => 3,
+            }
+        })?;
+        if is_extended {
+            // Overlap only comes from classification 12, which the extended formats keep as is.
+            for point in points.iter_mut().filter(|point| point.is_overlap) {
+                point.is_overlap = false;
+                point.classification = Classification::OverlapPoints;
+            }
+        }
+        if builder.point_format.has_gps_time && !has_gps_time {
+            // The extended formats always have gps time, so points without one get zero.
+            for point in &mut points {
+                point.gps_time = Some(0.);
+            }
+        }
+        if !points.is_empty() {
+            let scale = |decimals: usize| 10f64.powi(-(decimals as i32));
+            builder.transforms.x =
+                Transform::fit(bounds.min.x, bounds.max.x, scale(self.decimals[0]));
+            builder.transforms.y =
+                Transform::fit(bounds.min.y, bounds.max.y, scale(self.decimals[1]));
+            builder.transforms.z =
+                Transform::fit(bounds.min.z, bounds.max.z, scale(self.decimals[2]));
+            builder.bounds = bounds;
+        }
+        Ok((builder.into_header()?, points))
+    }
+
+    fn parse(&mut self, line: &str) -> Result<Point> {
+        let fields = fields(line);
+        if fields.len() < self.columns.len() {
+            return Err(Error::FieldCount {
+                line: self.line,
+                expected: self.columns.len(),
+                found: fields.len(),
+            }
+            .into());
+        }
+        let line = self.line;
+        let mut point = Point::default();
+        for (&column, &field) in self.columns.iter().zip(&fields) {
+            let error = || Error::Field {
+                line,
+                column: column.name(),
+                field: field.to_string(),
+            };
+            let float = || field.parse::<f64>().map_err(|_| error());
+            let integer = |max: f64| {
+                let n = float()?;
+                if n.fract() == 0. && (0. ..=max).contains(&n) {
+                    Ok(n)
+                } else {
+                    Err(error())
+                }
+            };
+            match column {
+                Column::X | Column::Y | Column::Z => {
+                    let value = float()?;
+                    let axis = match column {
+                        Column::X => 0,
+                        Column::Y => 1,
+                        _ => 2,
+                    };
+                    let decimals = decimals(field);
+                    self.decimals[axis] = self.decimals[axis].max(decimals);
+                    match axis {
+                        0 => point.x = value,
+                        1 => point.y = value,
+                        _ => point.z = value,
+                    }
+                }
+                Column::GpsTime => point.gps_time = Some(float()?),
+                Column::Intensity => point.intensity = integer(f64::from(u16::MAX))? as u16,
+                Column::ScanAngle => point.scan_angle = float()? as f32,
+                Column::ReturnNumber => point.return_number = integer(15.)? as u8,
+                Column::NumberOfReturns => point.number_of_returns = integer(15.)? as u8,
+                Column::Classification => {
+                    let n = integer(255.)? as u8;
+                    if n == 12 {
+                        point.is_overlap = true;
+                        point.classification = Classification::Unclassified;
+                    } else {
+                        point.classification = Classification::new(n)?;
+                    }
+                }
+                Column::UserData => point.user_data = integer(255.)? as u8,
+                Column::PointSourceId => {
+                    point.point_source_id = integer(f64::from(u16::MAX))? as u16
+                }
+                Column::EdgeOfFlightLine => point.is_edge_of_flight_line = integer(1.)? == 1.,
+                Column::ScanDirection => {
+                    point.scan_direction = if integer(1.)? == 1. {
+                        ScanDirection::LeftToRight
+                    } else {
+                        ScanDirection::RightToLeft
+                    }
+                }
+                Column::Red | Column::Green | Column::Blue => {
+                    let value = integer(f64::from(u16::MAX))? as u16;
+                    let color = point.color.get_or_insert_with(Color::default);
+                    match column {
+                        Column::Red => color.red = value,
+                        Column::Green => color.green = value,
+                        _ => color.blue = value,
+                    }
+                }
+                Column::Nir => point.nir = Some(integer(f64::from(u16::MAX))? as u16),
+                Column::Skip => {}
+            }
+        }
+        Ok(point)
+    }
+}
+
+/// Returns the number of decimal places written in a number, including any exponent.
+fn decimals(field: &str) -> usize {
+    let (mantissa, exponent) = match field.split_once(['e', 'E']) {
+        Some((mantissa, exponent)) => (mantissa, exponent.parse::<i64>().unwrap_or(0)),
+        None => (field, 0),
+    };
+    let fraction = mantissa
+        .split_once('.')
+        .map_or(0, |(_, fraction)| fraction.len());
+    (fraction as i64 - exponent).clamp(0, MAX_DECIMALS as i64) as usize
+}
+
+impl<R: BufRead> Iterator for TextReader<R> {
+    type Item = Result<Point>;
+
+    fn next(&mut self) -> Option<Result<Point>> {
+        let mut line = String::new();
+        loop {
+            line.clear();
+            match self.read.read_line(&mut line) {
+                Ok(0) => return None,
+                Ok(_) => {
+                    self.line += 1;
+                    let trimmed = line.trim();
+                    if !trimmed.is_empty() && !trimmed.starts_with('#') {
+                        return Some(self.parse(trimmed));
+                    }
+                }
+                Err(err) => return Some(Err(err.into())),
+            }
+        }
+    }
+}
+
+/// Writes points as delimited text.
+#[derive(Debug)]
+pub struct TextWriter<W: std::io::Write> {
+    write: W,
+    header: Header,
+    columns: Vec<Column>,
+    delimiter: char,
+    decimals: [usize; 3],
+}
+
+impl<W: std::io::Write> TextWriter<W> {
+    /// Creates a text writer that writes comma-delimited columns.
+    ///
+    /// Coordinates get as many decimals as the header's scales need, e.g. three for a scale of
+    /// 0.001.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::text::{self, TextWriter};
+    /// use las::{Header, Point, Write};
+    /// let mut writer = TextWriter::new(Vec::new(), Header::default(), text::columns("xyz").unwrap());
+    /// writer.write(Point { x: 1., y: 2., z: 3., ..Default::default() }).unwrap();
+    /// assert_eq!(b"1.000,2.000,3.000\n", writer.into_inner().as_slice());
+    /// ```
+    pub fn new(write: W, header: Header, columns: Vec<Column>) -> TextWriter<W> {
+        let decimals = |scale: f64| {
+            let decimals = -scale.log10();
+            if decimals > 0. {
+                (decimals - 1e-9).ceil().min(MAX_DECIMALS as f64) as usize
+            } else {
+                0
+            }
+        };
+        let transforms = header.transforms();
+        let decimals = [
+            decimals(transforms.x.scale),
+            decimals(transforms.y.scale),
+            decimals(transforms.z.scale),
+        ];
+        TextWriter {
+            write,
+            header,
+            columns,
+            delimiter: ',',
+            decimals,
+        }
+    }
+
+    /// Sets the delimiter between fields.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::text::{self, TextWriter};
+    /// use las::Header;
+    /// let mut writer = TextWriter::new(Vec::new(), Header::default(), text::columns("xy").unwrap());
+    /// writer.set_delimiter(' ');
+    /// writer.write_column_names().unwrap();
+    /// assert_eq!(b"x y\n", writer.into_inner().as_slice());
+    /// ```
+    pub fn set_delimiter(&mut self, delimiter: char) {
+        self.delimiter = delimiter;
+    }
+
+    /// Writes a line with the column names.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::text::{self, TextWriter};
+    /// use las::Header;
+    /// let mut writer = TextWriter::new(Vec::new(), Header::default(), text::columns("xt").unwrap());
+    /// writer.write_column_names().unwrap();
+    /// assert_eq!(b"x,gps_time\n", writer.into_inner().as_slice());
+    /// ```
+    pub fn write_column_names(&mut self) -> Result<()> {
+        let names = self
+            .columns
+            .iter()
+            .map(|column| column.name())
+            .collect::<Vec<_>>();
+        writeln!(self.write, "{}", names.join(&self.delimiter.to_string()))?;
+        Ok(())
+    }
+
+    /// Returns the underlying writer.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::text::TextWriter;
+    /// use las::Header;
+    /// let writer = TextWriter::new(Vec::new(), Header::default(), Vec::new());
+    /// assert!(writer.into_inner().is_empty());
+    /// ```
+    pub fn into_inner(self) -> W {
+        self.write
+    }
+
+    fn field(&self, column: Column, point: &Point) -> String {
+        let color = point.color.unwrap_or_default();
+        match column {
+            Column::X => format!("{:.*}", self.decimals[0], point.x),
+            Column::Y => format!("{:.*}", self.decimals[1], point.y),
+            Column::Z => format!("{:.*}", self.decimals[2], point.z),
+            Column::GpsTime => format!("{:.6}", point.gps_time.unwrap_or(0.)),
+            Column::Intensity => point.intensity.to_string(),
+            Column::ScanAngle => point.scan_angle.to_string(),
+            Column::ReturnNumber => point.return_number.to_string(),
+            Column::NumberOfReturns => point.number_of_returns.to_string(),
+            Column::Classification => {
+                if !self.header.point_format().is_extended
+                    && point.is_overlap
+                    && point.classification == Classification::Unclassified
+                {
+                    "12".to_string()
+                } else {
+                    u8::from(point.classification).to_string()
+                }
+            }
+            Column::UserData => point.user_data.to_string(),
+            Column::PointSourceId => point.point_source_id.to_string(),
+            Column::EdgeOfFlightLine => u8::from(point.is_edge_of_flight_line).to_string(),
+            Column::ScanDirection => {
+                u8::from(point.scan_direction == ScanDirection::LeftToRight).to_string()
+            }
+            Column::Red => color.red.to_string(),
+            Column::Green => color.green.to_string(),
+            Column::Blue => color.blue.to_string(),
+            Column::Nir => point.nir.unwrap_or(0).to_string(),
+            Column::Skip => "0".to_string(),
+        }
+    }
+}
+
+impl<W: std::io::Write> Write for TextWriter<W> {
+    fn header(&self) -> &Header {
+        &self.header
+    }
+
+    fn write(&mut self, point: Point) -> Result<()> {
+        let fields = self
+            .columns
+            .iter()
+            .map(|&column| self.field(column, &point))
+            .collect::<Vec<_>>();
+        writeln!(self.write, "{}", fields.join(&self.delimiter.to_string()))?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Read, Reader, Writer};
+    use std::io::Cursor;
+
+    #[test]
+    fn letters_roundtrip_names() {
+        for column in ALL {
+            assert_eq!(column, Column::from_name(column.name()).unwrap());
+        }
+        assert!(Column::from_name("R").is_err());
+        assert_eq!(Column::Red, Column::from_letter('R').unwrap());
+    }
+
+    #[test]
+    fn field_count() {
+        let mut reader = TextReader::new("1 2\n".as_bytes(), columns("xyz").unwrap());
+        assert!(reader.next().unwrap().is_err());
+    }
+
+    #[test]
+    fn invalid_field() {
+        let mut reader = TextReader::new("1 2 3 -4\n".as_bytes(), columns("xyzi").unwrap());
+        assert!(reader.next().unwrap().is_err());
+    }
+
+    #[test]
+    fn skips_comments_and_blank_lines() {
+        let text = "# a comment\n\n1;2;3\n";
+        let reader = TextReader::new(text.as_bytes(), columns("xyz").unwrap());
+        assert_eq!(1, reader.count());
+    }
+
+    #[test]
+    fn extended_and_color() {
+        let text = "1 2 3 9 40 1 2 3\n";
+        let reader = TextReader::new(text.as_bytes(), columns("xyzrcRGB").unwrap());
+        let (header, points) = reader.read_to_end().unwrap();
+        assert_eq!(Format::new(7).unwrap(), *header.point_format());
+        assert_eq!(Some(Color::new(1, 2, 3)), points[0].color);
+    }
+
+    #[test]
+    fn extended_number_of_returns() {
+        let text = "1 2 3 1 8\n";
+        let reader = TextReader::new(text.as_bytes(), columns("xyzrn").unwrap());
+        let (header, points) = reader.read_to_end().unwrap();
+        assert_eq!(Format::new(6).unwrap(), *header.point_format());
+        let mut writer = Writer::new(Cursor::new(Vec::new()), header).unwrap();
+        writer.write(points[0].clone()).unwrap();
+    }
+
+    #[test]
+    fn extended_scan_angle() {
+        let reader = TextReader::new("1 2 3 -90\n".as_bytes(), columns("xyza").unwrap());
+        let (header, _) = reader.read_to_end().unwrap();
+        assert_eq!(Format::new(0).unwrap(), *header.point_format());
+
+        let reader = TextReader::new(
+            "1 2 3 -90\n1 2 3 120\n".as_bytes(),
+            columns("xyza").unwrap(),
+        );
+        let (header, points) = reader.read_to_end().unwrap();
+        assert_eq!(Format::new(6).unwrap(), *header.point_format());
+        let mut writer = Writer::new(Cursor::new(Vec::new()), header).unwrap();
+        for point in points {
+            writer.write(point).unwrap();
+        }
+        let mut reader = Reader::new(writer.into_inner().unwrap()).unwrap();
+        let scan_angles = reader
+            .points()
+            .map(|point| point.unwrap().scan_angle)
+            .collect::<Vec<_>>();
+        assert_eq!(vec![-90., 120.], scan_angles);
+    }
+
+    #[test]
+    fn scientific_notation_decimals() {
+        assert_eq!(3, decimals("1e-3"));
+        assert_eq!(5, decimals("1.25E-3"));
+        assert_eq!(0, decimals("1.5e2"));
+        assert_eq!(1, decimals("1.25e1"));
+        let text = "1e-3 2.5e-4 3\n";
+        let reader = TextReader::new(text.as_bytes(), columns("xyz").unwrap());
+        let (header, _) = reader.read_to_end().unwrap();
+        assert_eq!(0.001, header.transforms().x.scale);
+        assert_eq!(0.00001, header.transforms().y.scale);
+        assert_eq!(1., header.transforms().z.scale);
+    }
+
+    #[test]
+    fn overlap() {
+        let reader = TextReader::new("1 2 3 12\n".as_bytes(), columns("xyzc").unwrap());
+        let (header, points) = reader.read_to_end().unwrap();
+        assert!(points[0].is_overlap);
+        let mut writer = TextWriter::new(Vec::new(), header, columns("c").unwrap());
+        writer.write(points[0].clone()).unwrap();
+        assert_eq!(b"12\n", writer.into_inner().as_slice());
+    }
+
+    #[test]
+    fn extended_overlap() {
+        let reader = TextReader::new("1 2 3 12\n1 2 3 40\n".as_bytes(), columns("xyzc").unwrap());
+        let (header, points) = reader.read_to_end().unwrap();
+        assert!(header.point_format().is_extended);
+        assert!(!points[0].is_overlap);
+        assert_eq!(Classification::OverlapPoints, points[0].classification);
+        let mut writer = TextWriter::new(Vec::new(), header, columns("c").unwrap());
+        writer.write(points[0].clone()).unwrap();
+        writer
+            .write(Point {
+                is_overlap: true,
+                ..Default::default()
+            })
+            .unwrap();
+        assert_eq!(b"12\n0\n", writer.into_inner().as_slice());
+    }
+
+    #[test]
+    fn autzen_roundtrip() {
+        let mut reader = Reader::from_path("tests/data/autzen.las").unwrap();
+        let columns = columns("xyztiarncupedRGB").unwrap();
+        let mut writer = TextWriter::new(Vec::new(), reader.header().clone(), columns.clone());
+        writer.write_column_names().unwrap();
+        let expected = reader
+            .points()
+            .map(|point| point.unwrap())
+            .collect::<Vec<_>>();
+        for point in &expected {
+            writer.write(point.clone()).unwrap();
+        }
+        let text = writer.into_inner();
+        let reader = TextReader::from_column_names(text.as_slice()).unwrap();
+        assert_eq!(columns, reader.columns());
+        let (header, points) = reader.read_to_end().unwrap();
+        assert_eq!(
+            reader_header_scales(&Reader::from_path("tests/data/autzen.las").unwrap()),
+            (
+                header.transforms().x.scale,
+                header.transforms().y.scale,
+                header.transforms().z.scale
+            )
+        );
+        assert_eq!(expected.len(), points.len());
+        for (point, expected) in points.iter().zip(&expected) {
+            assert!((point.x - expected.x).abs() < 1e-9);
+            assert!((point.gps_time.unwrap() - expected.gps_time.unwrap()).abs() < 1e-6);
+            assert_eq!(expected.intensity, point.intensity);
+            assert_eq!(expected.classification, point.classification);
+            assert_eq!(expected.return_number, point.return_number);
+        }
+    }
+
+    fn reader_header_scales(reader: &Reader<'_>) -> (f64, f64, f64) {
+        let transforms = reader.header().transforms();
+        (transforms.x.scale, transforms.y.scale, transforms.z.scale)
+    }
+}
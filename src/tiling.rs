@@ -0,0 +1,497 @@
+//! Write points into a grid of square tiles.
+//!
+//! A `TilingWriter` sends each point to the file for the tile that contains it, creating files as
+//! needed:
+//!
+//! ```
+//! use las::{Read, Reader, TilingWriter, Write};
+//!
+//! let mut reader = Reader::from_path("tests/data/autzen.las").unwrap();
+//! let directory = std::env::temp_dir().join("las-tiling-module-example");
+//! std::fs::create_dir_all(&directory).unwrap();
+//! let mut writer =
+//!     TilingWriter::new(&directory, reader.header().clone(), 500., (0., 0.)).unwrap();
+//! for point in reader.points() {
+//!     writer.write(point.unwrap()).unwrap();
+//! }
+//! writer.close().unwrap();
+//! # std::fs::remove_dir_all(directory).unwrap();
+//! ```
+
+use crate::{raw, Builder, Header, Point, Result, Write, Writer};
+use std::collections::HashMap;
+use std::ffi::OsString;
+use std::fs::{self, File, OpenOptions};
+use std::io::{BufReader, BufWriter, Write as _};
+use std::path::{Path, PathBuf};
+use thiserror::Error;
+
+/// The number of tile files that are kept open at once, by default.
+pub const DEFAULT_MAX_OPEN_FILES: usize = 64;
+
+/// Tiling errors.
+#[derive(Error, Clone, Copy, Debug)]
+pub enum Error {
+    /// The tile size must be positive and finite.
+    #[error("invalid tile size: {0}")]
+    TileSize(f64),
+
+    /// At least one file must be kept open.
+    #[error("the maximum number of open files must be at least one")]
+    MaxOpenFiles,
+
+    /// The tiling writer is closed.
+    #[error("the tiling writer is closed")]
+    Closed,
+}
+
+/// Writes points into a grid of square tiles, one file per tile.
+///
+/// Tiles are aligned to an origin, and each tile file is named after the tile's column and row
+/// counted from the origin, e.g. `3_-2.las` holds the points with
+/// `origin.0 + 3 * tile_size <= x < origin.0 + 4 * tile_size` and
+/// `origin.1 - 2 * tile_size <= y < origin.1 - tile_size`. Every tile gets a copy of the source
+/// header, so the tiles inherit its vlrs, point format, and transforms; the counts and bounds are
+/// recomputed when the tile is closed. Tiles are compressed if the header's point format is
+/// compressed and the `laz` feature is enabled.
+///
+/// A las writer can't be closed and reopened to append more points, and keeping one open per tile
+/// would run out of file handles on big grids. Instead, points are appended to a raw point file
+/// next to each tile, e.g. `3_-2.las.points`, and the tile files are written from those when the
+/// writer is closed. At most `max_open_files` of the point files are open at once; when another
+/// is needed, the least recently used one is closed, and it's reopened for appending if a point
+/// for that tile arrives later. Until the writer is closed the points take up about the size of
+/// the uncompressed output in point files, and while closing, a tile and its point file exist
+/// side by side.
+///
+/// If closing fails, the point files are left in place. If the writer is dropped without being
+/// closed, it tries to close and then removes the point files, ignoring any errors.
+#[derive(Debug)]
+pub struct TilingWriter {
+    directory: PathBuf,
+    header: Header,
+    extension: &'static str,
+    tile_size: f64,
+    origin: (f64, f64),
+    prefix: String,
+    max_open_files: usize,
+    open: HashMap<(i64, i64), OpenTile>,
+    tiles: HashMap<(i64, i64), Tile>,
+    clock: u64,
+    closed: bool,
+}
+
+#[derive(Debug)]
+struct Tile {
+    path: PathBuf,
+    points: PathBuf,
+    number_of_points: u64,
+}
+
+#[derive(Debug)]
+struct OpenTile {
+    file: BufWriter<File>,
+    last_used: u64,
+}
+
+impl TilingWriter {
+    /// Creates a new tiling writer that writes tiles into a directory.
+    ///
+    /// The directory must already exist. `origin` is the xy location of a tile corner.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::{Header, TilingWriter};
+    /// let writer = TilingWriter::new(std::env::temp_dir(), Header::default(), 100., (0., 0.));
+    /// assert!(writer.is_ok());
+    /// let writer = TilingWriter::new(std::env::temp_dir(), Header::default(), 0., (0., 0.));
+    /// assert!(writer.is_err());
+    /// ```
+    pub fn new<P: AsRef<Path>>(
+        directory: P,
+        header: Header,
+        tile_size: f64,
+        origin: (f64, f64),
+    ) -> Result<TilingWriter> {
+        if !(tile_size > 0. && tile_size.is_finite()) {
+            return Err(Error::TileSize(tile_size).into());
+        }
+        let compress = cfg!(feature = "laz") && header.point_format().is_compressed;
+        let mut builder = Builder::from(header);
+        if !compress {
+            builder.vlrs.retain(|vlr| !vlr.is_laszip());
+        }
+        Ok(TilingWriter {
+            directory: directory.as_ref().to_path_buf(),
+            header: builder.into_header()?,
+            extension: if compress { "laz" } else { "las" },
+            tile_size,
+            origin,
+            prefix: String::new(),
+            max_open_files: DEFAULT_MAX_OPEN_FILES,
+            open: HashMap::new(),
+            tiles: HashMap::new(),
+            clock: 0,
+            closed: false,
+        })
+    }
+
+    /// Sets the maximum number of tile files that are open at once.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::{Header, TilingWriter};
+    /// let mut writer =
+    ///     TilingWriter::new(std::env::temp_dir(), Header::default(), 100., (0., 0.)).unwrap();
+    /// writer.set_max_open_files(8).unwrap();
+    /// assert!(writer.set_max_open_files(0).is_err());
+    /// ```
+    pub fn set_max_open_files(&mut self, max_open_files: usize) -> Result<()> {
+        if max_open_files == 0 {
+            return Err(Error::MaxOpenFiles.into());
+        }
+        self.max_open_files = max_open_files;
+        while self.open.len() > self.max_open_files {
+            self.evict()?;
+        }
+        Ok(())
+    }
+
+    /// Sets a prefix for the tile file names, e.g. `autzen_` for `autzen_3_-2.las`.
+    ///
+    /// Tiles that already have points keep their names.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::{Header, TilingWriter};
+    /// let mut writer =
+    ///     TilingWriter::new(std::env::temp_dir(), Header::default(), 100., (0., 0.)).unwrap();
+    /// writer.set_prefix("autzen_");
+    /// ```
+    pub fn set_prefix<S: Into<String>>(&mut self, prefix: S) {
+        self.prefix = prefix.into();
+    }
+
+    /// Returns the paths of all tiles written so far, sorted.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::{Header, TilingWriter};
+    /// let writer =
+    ///     TilingWriter::new(std::env::temp_dir(), Header::default(), 100., (0., 0.)).unwrap();
+    /// assert!(writer.paths().is_empty());
+    /// ```
+    pub fn paths(&self) -> Vec<PathBuf> {
+        let mut paths = self
+            .tiles
+            .values()
+            .map(|tile| tile.path.clone())
+            .collect::<Vec<_>>();
+        paths.sort();
+        paths
+    }
+
+    /// Writes every tile file and removes the point files.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::{Header, TilingWriter};
+    /// let mut writer =
+    ///     TilingWriter::new(std::env::temp_dir(), Header::default(), 100., (0., 0.)).unwrap();
+    /// writer.close().unwrap();
+    /// assert!(writer.close().is_err());
+    /// ```
+    pub fn close(&mut self) -> Result<()> {
+        if self.closed {
+            return Err(Error::Closed.into());
+        }
+        for (_, mut open_tile) in self.open.drain() {
+            open_tile.file.flush()?;
+        }
+        for tile in self.tiles.values() {
+            let mut writer = Writer::from_path(&tile.path, self.header.clone())?;
+            let mut points = BufReader::new(File::open(&tile.points)?);
+            if let Err(err) = (0..tile.number_of_points)
+                .try_for_each(|_| {
+                    writer.write_raw(raw::Point::read_from(
+                        &mut points,
+                        self.header.point_format(),
+                    )?)
+                })
+                .and_then(|()| writer.close())
+            {
+                // Don't let the writer retry, and panic, when it's dropped.
+                writer.closed = true;
+                return Err(err);
+            }
+            fs::remove_file(&tile.points)?;
+        }
+        self.closed = true;
+        Ok(())
+    }
+
+    fn tile(&self, point: &Point) -> (i64, i64) {
+        (
+            ((point.x - self.origin.0) / self.tile_size).floor() as i64,
+            ((point.y - self.origin.1) / self.tile_size).floor() as i64,
+        )
+    }
+
+    fn path(&self, tile: (i64, i64)) -> PathBuf {
+        self.directory.join(format!(
+            "{}{}_{}.{}",
+            self.prefix, tile.0, tile.1, self.extension
+        ))
+    }
+
+    fn evict(&mut self) -> Result<()> {
+        if let Some(tile) = self
+            .open
+            .iter()
+            .min_by_key(|(_, open_tile)| open_tile.last_used)
+            .map(|(tile, _)| *tile)
+        {
+            if let Some(mut open_tile) = self.open.remove(&tile) {
+                open_tile.file.flush()?;
+            }
+        }
+        Ok(())
+    }
+
+    fn open(&mut self, tile: (i64, i64)) -> Result<BufWriter<File>> {
+        if self.open.len() >= self.max_open_files {
+            self.evict()?;
+        }
+        if let Some(existing) = self.tiles.get(&tile) {
+            let file = OpenOptions::new().append(true).open(&existing.points)?;
+            Ok(BufWriter::new(file))
+        } else {
+            let path = self.path(tile);
+            let mut points = OsString::from(path.clone());
+            points.push(".points");
+            let points = PathBuf::from(points);
+            let file = File::create(&points)?;
+            self.tiles.insert(
+                tile,
+                Tile {
+                    path,
+                    points,
+                    number_of_points: 0,
+                },
+            );
+            Ok(BufWriter::new(file))
+        }
+    }
+}
+
+impl Drop for TilingWriter {
+    fn drop(&mut self) {
+        if !self.closed {
+            let _ = self.close();
+            for tile in self.tiles.values() {
+                let _ = fs::remove_file(&tile.points);
+            }
+        }
+    }
+}
+
+impl Write for TilingWriter {
+    /// Returns the header that every tile starts from.
+    fn header(&self) -> &Header {
+        &self.header
+    }
+
+    /// Writes a point to the tile that contains it.
+    fn write(&mut self, point: Point) -> Result<()> {
+        if self.closed {
+            return Err(Error::Closed.into());
+        }
+        let format = *self.header.point_format();
+        if !point.matches(&format) {
+            return Err(crate::writer::Error::PointAttributes { format, point }.into());
+        }
+        let tile = self.tile(&point);
+        let point = point.into_raw(self.header.transforms())?;
+        self.clock += 1;
+        if !self.open.contains_key(&tile) {
+            let file = self.open(tile)?;
+            self.open.insert(
+                tile,
+                OpenTile {
+                    file,
+                    last_used: self.clock,
+                },
+            );
+        }
+        let open_tile = self.open.get_mut(&tile).expect("the tile was just opened");
+        open_tile.last_used = self.clock;
+        point.write_to(&mut open_tile.file, &format)?;
+        self.tiles
+            .get_mut(&tile)
+            .expect("every open tile is recorded")
+            .number_of_points += 1;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Bounds, Read, Reader};
+
+    fn tile(name: &str, max_open_files: usize) -> Vec<(PathBuf, Vec<Point>)> {
+        let directory =
+            std::env::temp_dir().join(format!("las-tiling-{}-{}", std::process::id(), name));
+        fs::create_dir_all(&directory).unwrap();
+        let mut reader = Reader::from_path("tests/data/autzen.las").unwrap();
+        let mut writer =
+            TilingWriter::new(&directory, reader.header().clone(), 200., (0., 0.)).unwrap();
+        writer.set_max_open_files(max_open_files).unwrap();
+        for point in reader.points() {
+            writer.write(point.unwrap()).unwrap();
+        }
+        writer.close().unwrap();
+        for entry in fs::read_dir(&directory).unwrap() {
+            assert_eq!("las", entry.unwrap().path().extension().unwrap());
+        }
+        let tiles = writer
+            .paths()
+            .into_iter()
+            .map(|path| {
+                let points = Reader::from_path(&path)
+                    .unwrap()
+                    .points()
+                    .map(|point| point.unwrap())
+                    .collect();
+                (path.file_name().unwrap().into(), points)
+            })
+            .collect();
+        fs::remove_dir_all(directory).unwrap();
+        tiles
+    }
+
+    #[test]
+    fn tiles() {
+        let tiles = tile("tiles", DEFAULT_MAX_OPEN_FILES);
+        assert!(tiles.len() > 1);
+        assert_eq!(
+            106,
+            tiles.iter().map(|(_, points)| points.len()).sum::<usize>()
+        );
+        for (path, points) in &tiles {
+            let name = path.file_stem().unwrap().to_str().unwrap();
+            let (x, y) = name.split_once('_').unwrap();
+            let x = x.parse::<i64>().unwrap() as f64 * 200.;
+            let y = y.parse::<i64>().unwrap() as f64 * 200.;
+            for point in points {
+                assert!(x <= point.x && point.x < x + 200.);
+                assert!(y <= point.y && point.y < y + 200.);
+            }
+        }
+    }
+
+    #[test]
+    fn evicted_tiles_are_reopened() {
+        assert_eq!(
+            tile("all-open", DEFAULT_MAX_OPEN_FILES),
+            tile("one-open", 1)
+        );
+    }
+
+    #[test]
+    fn names_are_tile_indices() {
+        let directory =
+            std::env::temp_dir().join(format!("las-tiling-{}-names", std::process::id()));
+        fs::create_dir_all(&directory).unwrap();
+        let mut writer = TilingWriter::new(&directory, Header::default(), 0.1, (0., 0.)).unwrap();
+        writer.set_prefix("points_");
+        for (x, y) in [(0.35, -0.05), (0.05, 0.05)] {
+            writer
+                .write(Point {
+                    x,
+                    y,
+                    ..Default::default()
+                })
+                .unwrap();
+        }
+        writer.close().unwrap();
+        assert_eq!(
+            vec![
+                directory.join("points_0_0.las"),
+                directory.join("points_3_-1.las")
+            ],
+            writer.paths()
+        );
+        fs::remove_dir_all(directory).unwrap();
+    }
+
+    #[test]
+    fn drop_closes() {
+        let directory =
+            std::env::temp_dir().join(format!("las-tiling-{}-drop", std::process::id()));
+        fs::create_dir_all(&directory).unwrap();
+        let mut writer = TilingWriter::new(&directory, Header::default(), 1., (0., 0.)).unwrap();
+        writer.write(Default::default()).unwrap();
+        drop(writer);
+        let names = fs::read_dir(&directory)
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name())
+            .collect::<Vec<_>>();
+        assert_eq!(vec![OsString::from("0_0.las")], names);
+        assert_eq!(
+            1,
+            Reader::from_path(directory.join("0_0.las"))
+                .unwrap()
+                .header()
+                .number_of_points()
+        );
+        fs::remove_dir_all(directory).unwrap();
+    }
+
+    #[test]
+    fn headers() {
+        let directory =
+            std::env::temp_dir().join(format!("las-tiling-{}-headers", std::process::id()));
+        fs::create_dir_all(&directory).unwrap();
+        let source = Reader::from_path("tests/data/autzen.las").unwrap();
+        let mut writer =
+            TilingWriter::new(&directory, source.header().clone(), 1000., (0., 0.)).unwrap();
+        let point = Point {
+            x: 1.5,
+            y: 2.5,
+            z: 3.5,
+            gps_time: Some(1.),
+            ..Default::default()
+        };
+        writer.write(point).unwrap();
+        writer.close().unwrap();
+        let paths = writer.paths();
+        assert_eq!(vec![directory.join("0_0.las")], paths);
+        let reader = Reader::from_path(&paths[0]).unwrap();
+        let header = reader.header();
+        assert_eq!(1, header.number_of_points());
+        assert_eq!(source.header().transforms(), header.transforms());
+        assert_eq!(source.header().vlrs(), header.vlrs());
+        assert_eq!(
+            Bounds {
+                min: crate::Vector {
+                    x: 1.5,
+                    y: 2.5,
+                    z: 3.5
+                },
+                max: crate::Vector {
+                    x: 1.5,
+                    y: 2.5,
+                    z: 3.5
+                },
+            },
+            header.bounds()
+        );
+        fs::remove_dir_all(directory).unwrap();
+    }
+}
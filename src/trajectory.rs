@@ -0,0 +1,621 @@
+//! Sensor trajectories, interpolated at each point's gps time.
+//!
+//! A `Trajectory` is a time-ordered list of sensor `Pose`s, each with a position and roll, pitch,
+//! and yaw in degrees. Trajectories are read from text, with one `time x y z roll pitch yaw` record
+//! per line, or from binary files of seven little-endian doubles per record in the same order.
+//! Positions must be in the same coordinate system as the points.
+//!
+//! ```
+//! use las::trajectory::Trajectory;
+//! use las::Point;
+//!
+//! let trajectory = Trajectory::from_text("0 0 0 100 0 0 0\n10 100 0 100 0 0 0".as_bytes()).unwrap();
+//! let point = Point { x: 50., y: 0., z: 0., gps_time: Some(5.), ..Default::default() };
+//! assert_eq!(Some(100.), trajectory.range(&point));
+//! assert_eq!(Some(0.), trajectory.incidence_angle(&point));
+//! ```
+//!
+//! Use a `TrajectoryWriter` to store each point's range and incidence angle in typed extra bytes
+//! on the way into a writer.
+
+use crate::extra_bytes::{self, DataType, Descriptor};
+use crate::{Builder, Header, Point, Result, Vector, Write};
+use byteorder::{ByteOrder, LittleEndian};
+use std::io::{self, BufRead};
+use thiserror::Error;
+
+/// The name of the range extra bytes attribute.
+pub const RANGE: &str = "range";
+
+/// The name of the incidence angle extra bytes attribute.
+pub const INCIDENCE_ANGLE: &str = "incidence_angle";
+
+const BINARY_RECORD_LENGTH: usize = 56;
+
+/// Trajectory errors.
+#[derive(Error, Clone, Debug)]
+pub enum Error {
+    /// A line of a text trajectory isn't a pose.
+    #[error("invalid trajectory line {line}: {text}")]
+    #[allow(missing_docs)]
+    Line { line: usize, text: String },
+
+    /// A binary trajectory's length isn't a whole number of records.
+    #[error("binary trajectory length {0} is not a multiple of 56")]
+    Length(usize),
+
+    /// The trajectory has no poses.
+    #[error("the trajectory has no poses")]
+    Empty,
+
+    /// A pose's time isn't finite.
+    #[error("pose time {0} is not finite")]
+    Time(f64),
+
+    /// The point has no gps time.
+    #[error("the point has no gps time")]
+    NoGpsTime,
+
+    /// The point's gps time is outside of the trajectory.
+    #[error("gps time {0} is outside of the trajectory")]
+    OutsideTrajectory(f64),
+
+    /// The writer's header has neither a range nor an incidence angle attribute.
+    #[error("the header has no {RANGE} or {INCIDENCE_ANGLE} extra bytes attribute")]
+    NoAttributes,
+}
+
+/// A sensor position and attitude at a gps time.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Pose {
+    /// The gps time.
+    pub gps_time: f64,
+
+    /// The sensor position.
+    pub position: Vector<f64>,
+
+    /// The roll, in degrees.
+    pub roll: f64,
+
+    /// The pitch, in degrees.
+    pub pitch: f64,
+
+    /// The yaw, in degrees.
+    pub yaw: f64,
+}
+
+/// A time-ordered list of poses.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Trajectory {
+    poses: Vec<Pose>,
+}
+
+impl Trajectory {
+    /// Creates a trajectory, sorting the poses by time.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::trajectory::{Pose, Trajectory};
+    /// let trajectory = Trajectory::new(vec![Pose::default()]).unwrap();
+    /// assert!(Trajectory::new(Vec::new()).is_err());
+    /// ```
+    pub fn new(mut poses: Vec<Pose>) -> Result<Trajectory> {
+        if poses.is_empty() {
+            return Err(Error::Empty.into());
+        }
+        if let Some(pose) = poses.iter().find(|pose| !pose.gps_time.is_finite()) {
+            return Err(Error::Time(pose.gps_time).into());
+        }
+        poses.sort_by(|a, b| a.gps_time.total_cmp(&b.gps_time));
+        Ok(Trajectory { poses })
+    }
+
+    /// Reads a text trajectory.
+    ///
+    /// Each line is a `time x y z roll pitch yaw` record, separated by whitespace or commas.
+    /// Blank lines and anything after a `#` are ignored.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::trajectory::Trajectory;
+    /// let trajectory = Trajectory::from_text("# time x y z roll pitch yaw\n1,2,3,4,0,0,90".as_bytes()).unwrap();
+    /// assert_eq!(90., trajectory.poses()[0].yaw);
+    /// ```
+    pub fn from_text<R: BufRead>(read: R) -> Result<Trajectory> {
+        let mut poses = Vec::new();
+        for (i, line) in read.lines().enumerate() {
+            let line = line?;
+            let text = line.split('#').next().unwrap_or_default();
+            let values = text
+                .split(|c: char| c.is_whitespace() || c == ',')
+                .filter(|s| !s.is_empty())
+                .map(|s| s.parse::<f64>())
+                .collect::<std::result::Result<Vec<_>, _>>();
+            match values.as_deref() {
+                Ok([]) => {}
+                Ok(&[gps_time, x, y, z, roll, pitch, yaw]) => poses.push(Pose {
+                    gps_time,
+                    position: Vector { x, y, z },
+                    roll,
+                    pitch,
+                    yaw,
+                }),
+                _ => {
+                    return Err(Error::Line {
+                        line: i + 1,
+                        text: line,
+                    }
+                    .into())
+                }
+            }
+        }
+        Trajectory::new(poses)
+    }
+
+    /// Reads a binary trajectory of seven little-endian doubles per record.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::trajectory::Trajectory;
+    /// let bytes: Vec<u8> = [1., 2., 3., 4., 0., 0., 90.]
+    ///     .iter()
+    ///     .flat_map(|n: &f64| n.to_le_bytes())
+    ///     .collect();
+    /// let trajectory = Trajectory::from_binary(&bytes[..]).unwrap();
+    /// assert_eq!(4., trajectory.poses()[0].position.z);
+    /// ```
+    pub fn from_binary<R: io::Read>(mut read: R) -> Result<Trajectory> {
+        let mut bytes = Vec::new();
+        let _ = read.read_to_end(&mut bytes)?;
+        if !bytes.len().is_multiple_of(BINARY_RECORD_LENGTH) {
+            return Err(Error::Length(bytes.len()).into());
+        }
+        let poses = bytes
+            .chunks(BINARY_RECORD_LENGTH)
+            .map(|record| {
+                let value = |i: usize| LittleEndian::read_f64(&record[i * 8..]);
+                Pose {
+                    gps_time: value(0),
+                    position: Vector {
+                        x: value(1),
+                        y: value(2),
+                        z: value(3),
+                    },
+                    roll: value(4),
+                    pitch: value(5),
+                    yaw: value(6),
+                }
+            })
+            .collect();
+        Trajectory::new(poses)
+    }
+
+    /// Returns the poses, sorted by time.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::trajectory::Trajectory;
+    /// let trajectory = Trajectory::from_text("2 0 0 0 0 0 0\n1 0 0 0 0 0 0".as_bytes()).unwrap();
+    /// assert_eq!(1., trajectory.poses()[0].gps_time);
+    /// ```
+    pub fn poses(&self) -> &[Pose] {
+        &self.poses
+    }
+
+    /// Returns the pose at a gps time, linearly interpolated between the surrounding poses.
+    ///
+    /// Angles are interpolated the short way around the circle. Returns `None` if the time is
+    /// before the first pose or after the last one.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::trajectory::Trajectory;
+    /// let trajectory = Trajectory::from_text("0 0 0 0 0 0 350\n1 10 0 0 0 0 10".as_bytes()).unwrap();
+    /// let pose = trajectory.interpolate(0.25).unwrap();
+    /// assert_eq!(2.5, pose.position.x);
+    /// assert_eq!(355., pose.yaw);
+    /// assert!(trajectory.interpolate(2.).is_none());
+    /// ```
+    pub fn interpolate(&self, gps_time: f64) -> Option<Pose> {
+        let first = self.poses.first()?;
+        let last = self.poses.last()?;
+        if !(first.gps_time..=last.gps_time).contains(&gps_time) {
+            return None;
+        }
+        let i = self
+            .poses
+            .partition_point(|pose| pose.gps_time <= gps_time)
+            .min(self.poses.len() - 1);
+        if i == 0 {
+            return Some(*first);
+        }
+        let (a, b) = (self.poses[i - 1], self.poses[i]);
+        let dt = b.gps_time - a.gps_time;
+        if dt == 0. {
+            return Some(a);
+        }
+        let t = (gps_time - a.gps_time) / dt;
+        let lerp = |a: f64, b: f64| a + (b - a) * t;
+        Some(Pose {
+            gps_time,
+            position: Vector {
+                x: lerp(a.position.x, b.position.x),
+                y: lerp(a.position.y, b.position.y),
+                z: lerp(a.position.z, b.position.z),
+            },
+            roll: lerp_angle(a.roll, b.roll, t),
+            pitch: lerp_angle(a.pitch, b.pitch, t),
+            yaw: lerp_angle(a.yaw, b.yaw, t),
+        })
+    }
+
+    /// Returns the distance from the sensor to the point.
+    ///
+    /// Returns `None` if the point has no gps time or its time is outside of the trajectory.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::trajectory::Trajectory;
+    /// use las::Point;
+    /// let trajectory = Trajectory::from_text("0 0 0 5 0 0 0".as_bytes()).unwrap();
+    /// let point = Point { x: 3., y: 0., z: 1., gps_time: Some(0.), ..Default::default() };
+    /// assert_eq!(Some(5.), trajectory.range(&point));
+    /// ```
+    pub fn range(&self, point: &Point) -> Option<f64> {
+        let beam = self.beam(point)?;
+        Some((beam.x * beam.x + beam.y * beam.y + beam.z * beam.z).sqrt())
+    }
+
+    /// Returns the angle, in degrees, between the beam and the vertical.
+    ///
+    /// This is the incidence angle on a horizontal surface. Use
+    /// `Trajectory::incidence_angle_with_normal` when the surface normal is known.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::trajectory::Trajectory;
+    /// use las::Point;
+    /// let trajectory = Trajectory::from_text("0 0 0 10 0 0 0".as_bytes()).unwrap();
+    /// let point = Point { x: 10., y: 0., z: 0., gps_time: Some(0.), ..Default::default() };
+    /// assert!((45. - trajectory.incidence_angle(&point).unwrap()).abs() < 1e-9);
+    /// ```
+    pub fn incidence_angle(&self, point: &Point) -> Option<f64> {
+        self.incidence_angle_with_normal(
+            point,
+            Vector {
+                x: 0.,
+                y: 0.,
+                z: 1.,
+            },
+        )
+    }
+
+    /// Returns the angle, in degrees, between the beam and a surface normal.
+    ///
+    /// The normal's direction doesn't matter, so the angle is between 0 and 90 degrees. Returns
+    /// `None` if the point is at the sensor or the normal is zero.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::trajectory::Trajectory;
+    /// use las::{Point, Vector};
+    /// let trajectory = Trajectory::from_text("0 0 0 10 0 0 0".as_bytes()).unwrap();
+    /// let point = Point { x: 10., y: 0., z: 0., gps_time: Some(0.), ..Default::default() };
+    /// let normal = Vector { x: -1., y: 0., z: 1. };
+    /// assert!(trajectory.incidence_angle_with_normal(&point, normal).unwrap() < 1e-3);
+    /// ```
+    pub fn incidence_angle_with_normal(&self, point: &Point, normal: Vector<f64>) -> Option<f64> {
+        let beam = self.beam(point)?;
+        let range = (beam.x * beam.x + beam.y * beam.y + beam.z * beam.z).sqrt();
+        let length = (normal.x * normal.x + normal.y * normal.y + normal.z * normal.z).sqrt();
+        if range == 0. || length == 0. {
+            return None;
+        }
+        let cos = (beam.x * normal.x + beam.y * normal.y + beam.z * normal.z) / (range * length);
+        Some(cos.abs().min(1.).acos().to_degrees())
+    }
+
+    fn beam(&self, point: &Point) -> Option<Vector<f64>> {
+        let pose = self.interpolate(point.gps_time?)?;
+        Some(Vector {
+            x: point.x - pose.position.x,
+            y: point.y - pose.position.y,
+            z: point.z - pose.position.z,
+        })
+    }
+}
+
+/// Returns the range and incidence angle extra bytes descriptors, as `f32`s.
+///
+/// # Examples
+///
+/// ```
+/// use las::trajectory;
+/// assert_eq!(trajectory::RANGE, trajectory::descriptors()[0].name);
+/// ```
+pub fn descriptors() -> Vec<Descriptor> {
+    vec![
+        Descriptor::new(RANGE, DataType::F32),
+        Descriptor::new(INCIDENCE_ANGLE, DataType::F32),
+    ]
+}
+
+/// Adds the range and incidence angle attributes to a builder's extra bytes.
+///
+/// The attributes are appended to any existing extra bytes descriptors, and the point format's
+/// extra bytes grow to match.
+///
+/// # Examples
+///
+/// ```
+/// use las::{extra_bytes, trajectory, Builder};
+/// let mut builder = Builder::from((1, 4));
+/// trajectory::add_descriptors(&mut builder).unwrap();
+/// assert_eq!(8, builder.point_format.extra_bytes);
+/// let header = builder.into_header().unwrap();
+/// assert_eq!(2, extra_bytes::descriptors(&header).unwrap().len());
+/// ```
+pub fn add_descriptors(builder: &mut Builder) -> Result<()> {
+    let is_extra_bytes = |vlr: &crate::Vlr| {
+        vlr.user_id == extra_bytes::USER_ID && vlr.record_id == extra_bytes::RECORD_ID
+    };
+    let mut existing = match builder.vlrs.iter().position(is_extra_bytes) {
+        Some(i) => extra_bytes::descriptors_from_vlr(&builder.vlrs.remove(i))?,
+        None => Vec::new(),
+    };
+    let added = descriptors();
+    builder.point_format.extra_bytes += added
+        .iter()
+        .map(|descriptor| descriptor.data_type.size() as u16)
+        .sum::<u16>();
+    existing.extend(added);
+    builder.vlrs.push(extra_bytes::vlr(&existing)?);
+    Ok(())
+}
+
+/// Stores each point's range and incidence angle in its extra bytes on the way into a writer.
+///
+/// The attributes are found by name in the writer header's extra bytes descriptors, e.g. after
+/// `add_descriptors`. Points without a gps time, or outside of the trajectory, are errors.
+///
+/// # Examples
+///
+/// ```
+/// use las::trajectory::{self, Trajectory, TrajectoryWriter};
+/// use las::{extra_bytes, Builder, Point, Write, Writer};
+/// use std::io::Cursor;
+///
+/// let mut builder = Builder::from((1, 4));
+/// builder.point_format.has_gps_time = true;
+/// trajectory::add_descriptors(&mut builder).unwrap();
+/// let writer = Writer::new(Cursor::new(Vec::new()), builder.into_header().unwrap()).unwrap();
+/// let trajectory = Trajectory::from_text("0 0 0 10 0 0 0".as_bytes()).unwrap();
+/// let mut writer = TrajectoryWriter::new(writer, trajectory).unwrap();
+/// writer.write(Point { gps_time: Some(0.), ..Default::default() }).unwrap();
+/// ```
+#[derive(Debug)]
+pub struct TrajectoryWriter<W: Write> {
+    inner: W,
+    trajectory: Trajectory,
+    range: Option<(usize, Descriptor)>,
+    incidence_angle: Option<(usize, Descriptor)>,
+}
+
+impl<W: Write> TrajectoryWriter<W> {
+    /// Wraps a writer, finding the range and incidence angle attributes in its header.
+    ///
+    /// Errors if the header has neither attribute.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::trajectory::{Trajectory, TrajectoryWriter};
+    /// use las::Writer;
+    /// use std::io::Cursor;
+    /// let writer = Writer::new(Cursor::new(Vec::new()), Default::default()).unwrap();
+    /// let trajectory = Trajectory::from_text("0 0 0 10 0 0 0".as_bytes()).unwrap();
+    /// assert!(TrajectoryWriter::new(writer, trajectory).is_err());
+    /// ```
+    pub fn new(inner: W, trajectory: Trajectory) -> Result<TrajectoryWriter<W>> {
+        let mut range = None;
+        let mut incidence_angle = None;
+        let mut offset = 0;
+        for descriptor in extra_bytes::descriptors(inner.header())? {
+            let size = descriptor.data_type.size();
+            if descriptor.name == RANGE {
+                range = Some((offset, descriptor));
+            } else if descriptor.name == INCIDENCE_ANGLE {
+                incidence_angle = Some((offset, descriptor));
+            }
+            offset += size;
+        }
+        if range.is_none() && incidence_angle.is_none() {
+            return Err(Error::NoAttributes.into());
+        }
+        Ok(TrajectoryWriter {
+            inner,
+            trajectory,
+            range,
+            incidence_angle,
+        })
+    }
+
+    /// Returns the trajectory.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::trajectory::{self, Trajectory, TrajectoryWriter};
+    /// use las::{Builder, Writer};
+    /// use std::io::Cursor;
+    /// let mut builder = Builder::from((1, 4));
+    /// trajectory::add_descriptors(&mut builder).unwrap();
+    /// let writer = Writer::new(Cursor::new(Vec::new()), builder.into_header().unwrap()).unwrap();
+    /// let trajectory = Trajectory::from_text("0 0 0 10 0 0 0".as_bytes()).unwrap();
+    /// let writer = TrajectoryWriter::new(writer, trajectory).unwrap();
+    /// assert_eq!(1, writer.trajectory().poses().len());
+    /// ```
+    pub fn trajectory(&self) -> &Trajectory {
+        &self.trajectory
+    }
+
+    /// Returns the wrapped writer.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::trajectory::{self, Trajectory, TrajectoryWriter};
+    /// use las::{Builder, Writer};
+    /// use std::io::Cursor;
+    /// let mut builder = Builder::from((1, 4));
+    /// trajectory::add_descriptors(&mut builder).unwrap();
+    /// let writer = Writer::new(Cursor::new(Vec::new()), builder.into_header().unwrap()).unwrap();
+    /// let trajectory = Trajectory::from_text("0 0 0 10 0 0 0".as_bytes()).unwrap();
+    /// let writer = TrajectoryWriter::new(writer, trajectory).unwrap().into_inner();
+    /// ```
+    pub fn into_inner(self) -> W {
+        self.inner
+    }
+}
+
+impl<W: Write> Write for TrajectoryWriter<W> {
+    fn header(&self) -> &Header {
+        self.inner.header()
+    }
+
+    fn write(&mut self, mut point: Point) -> Result<()> {
+        let gps_time = point.gps_time.ok_or(Error::NoGpsTime)?;
+        let outside = || Error::OutsideTrajectory(gps_time);
+        let len = self.inner.header().point_format().extra_bytes as usize;
+        if point.extra_bytes.len() < len {
+            point.extra_bytes.resize(len, 0);
+        }
+        if let Some((offset, descriptor)) = &self.range {
+            let range = self.trajectory.range(&point).ok_or_else(outside)?;
+            descriptor.set(&mut point.extra_bytes[*offset..], range)?;
+        }
+        if let Some((offset, descriptor)) = &self.incidence_angle {
+            // A point at the sensor has no incidence angle, so it's stored as zero.
+            let incidence_angle = match self.trajectory.incidence_angle(&point) {
+                Some(incidence_angle) => incidence_angle,
+                None if self.trajectory.interpolate(gps_time).is_some() => 0.,
+                None => return Err(outside().into()),
+            };
+            descriptor.set(&mut point.extra_bytes[*offset..], incidence_angle)?;
+        }
+        self.inner.write(point)
+    }
+}
+
+fn lerp_angle(a: f64, b: f64, t: f64) -> f64 {
+    let delta = (b - a + 180.).rem_euclid(360.) - 180.;
+    let angle = a + delta * t;
+    if a < 0. || b < 0. {
+        180. - (180. - angle).rem_euclid(360.)
+    } else {
+        angle.rem_euclid(360.)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Read, Reader, Writer};
+    use std::io::Cursor;
+
+    #[test]
+    fn parse_errors() {
+        assert!(Trajectory::from_text("1 2 3".as_bytes()).is_err());
+        assert!(Trajectory::from_text("a 0 0 0 0 0 0".as_bytes()).is_err());
+        assert!(Trajectory::from_text("# nothing".as_bytes()).is_err());
+        assert!(Trajectory::from_text("nan 0 0 0 0 0 0".as_bytes()).is_err());
+        assert!(Trajectory::from_binary(&[0; 57][..]).is_err());
+    }
+
+    #[test]
+    fn interpolate() {
+        let trajectory = Trajectory::from_text(
+            "0 0 0 0 -10 0 0\n1 10 20 30 10 0 0\n1 10 20 30 10 0 0\n3 10 20 50 0 0 0".as_bytes(),
+        )
+        .unwrap();
+        let pose = trajectory.interpolate(0.5).unwrap();
+        assert_eq!(
+            Vector {
+                x: 5.,
+                y: 10.,
+                z: 15.
+            },
+            pose.position
+        );
+        assert_eq!(0., pose.roll);
+        assert_eq!(30., trajectory.interpolate(1.).unwrap().position.z);
+        assert_eq!(40., trajectory.interpolate(2.).unwrap().position.z);
+        assert_eq!(50., trajectory.interpolate(3.).unwrap().position.z);
+        assert!(trajectory.interpolate(-0.1).is_none());
+        assert!(trajectory.interpolate(f64::NAN).is_none());
+    }
+
+    #[test]
+    fn write_extra_bytes() {
+        let mut builder = Builder::from((1, 4));
+        builder.point_format.has_gps_time = true;
+        trajectory_descriptors_with_existing(&mut builder);
+        let writer = Writer::new(Cursor::new(Vec::new()), builder.into_header().unwrap()).unwrap();
+        let trajectory =
+            Trajectory::from_text("0 0 0 10 0 0 0\n10 0 0 10 0 0 0".as_bytes()).unwrap();
+        let mut writer = TrajectoryWriter::new(writer, trajectory).unwrap();
+        writer
+            .write(Point {
+                x: 10.,
+                gps_time: Some(5.),
+                extra_bytes: vec![42, 0],
+                ..Default::default()
+            })
+            .unwrap();
+        writer
+            .write(Point {
+                z: 10.,
+                gps_time: Some(5.),
+                ..Default::default()
+            })
+            .unwrap();
+        assert!(writer
+            .write(Point {
+                gps_time: Some(11.),
+                ..Default::default()
+            })
+            .is_err());
+        assert!(writer.write(Point::default()).is_err());
+
+        let mut writer = writer.into_inner();
+        writer.close().unwrap();
+        let mut reader = Reader::new(writer.into_inner().unwrap()).unwrap();
+        let descriptors = extra_bytes::descriptors(reader.header()).unwrap();
+        assert_eq!(3, descriptors.len());
+        let points = reader.points().collect::<Result<Vec<_>>>().unwrap();
+        assert_eq!(2, points.len());
+        assert_eq!(42, points[0].extra_bytes[0]);
+        let range = descriptors[1].get(&points[0].extra_bytes[2..]).unwrap();
+        assert!((range - 200f64.sqrt()).abs() < 1e-4);
+        let incidence_angle = descriptors[2].get(&points[0].extra_bytes[6..]).unwrap();
+        assert!((incidence_angle - 45.).abs() < 1e-4);
+        assert_eq!(Some(0.), descriptors[1].get(&points[1].extra_bytes[2..]));
+        assert_eq!(Some(0.), descriptors[2].get(&points[1].extra_bytes[6..]));
+    }
+
+    fn trajectory_descriptors_with_existing(builder: &mut Builder) {
+        let existing = vec![Descriptor::new("amplitude", DataType::U16)];
+        builder.point_format.extra_bytes = 2;
+        builder.vlrs.push(extra_bytes::vlr(&existing).unwrap());
+        add_descriptors(builder).unwrap();
+        assert_eq!(10, builder.point_format.extra_bytes);
+    }
+}
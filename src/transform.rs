@@ -3,6 +3,7 @@ use crate::Result;
 
 /// A scale and an offset that transforms xyz coordinates.
 #[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Transform {
     /// The scale.
     pub scale: f64,
@@ -49,6 +50,39 @@ impl Transform {
             Ok(n as i32)
         }
     }
+
+    /// Returns a transform with this scale that can represent every value from min to max.
+    ///
+    /// A zero offset is kept if it works, otherwise the offset is moved to the middle of the range
+    /// (rounded to a multiple of the scale). If the range is still too large to fit in an i32, the
+    /// scale is multiplied by ten until it does.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::Transform;
+    /// let transform = Transform::fit(4_000_000., 4_000_100., 0.001);
+    /// assert_eq!(0.001, transform.scale);
+    /// assert!(transform.inverse(4_000_000.).is_ok());
+    /// assert!(transform.inverse(4_000_100.).is_ok());
+    /// let transform = Transform::fit(-1e7, 1e7, 0.001);
+    /// assert_eq!(0.01, transform.scale);
+    /// ```
+    pub fn fit(min: f64, max: f64, scale: f64) -> Transform {
+        let limit = f64::from(i32::MAX) - 1.;
+        if !(min <= max && min.is_finite() && max.is_finite()) {
+            return Transform { scale, offset: 0. };
+        }
+        if min / scale >= -limit && max / scale <= limit {
+            return Transform { scale, offset: 0. };
+        }
+        let mut scale = scale;
+        while (max - min) / 2. / scale > limit {
+            scale *= 10.;
+        }
+        let offset = ((min + max) / 2. / scale).round() * scale;
+        Transform { scale, offset }
+    }
 }
 
 impl Default for Transform {
@@ -78,6 +112,20 @@ mod tests {
         assert!(transform.inverse(n).is_err());
     }
 
+    #[test]
+    fn fit_keeps_values_in_range() {
+        for &(min, max, scale) in &[
+            (0., 1., 0.01),
+            (4e6, 4.1e6, 0.0001),
+            (-3e9, 5e9, 0.001),
+            (1e12, 1e12 + 1., 0.001),
+        ] {
+            let transform = Transform::fit(min, max, scale);
+            assert!(transform.inverse(min).is_ok(), "{} {}", min, transform);
+            assert!(transform.inverse(max).is_ok(), "{} {}", max, transform);
+        }
+    }
+
     #[test]
     fn too_small() {
         let transform = Transform::default();
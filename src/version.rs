@@ -7,6 +7,7 @@ use crate::{Error, Result};
 ///
 /// Defaults to 1.2.
 #[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Version {
     /// The major version.
     ///
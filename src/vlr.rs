@@ -32,6 +32,8 @@
 use thiserror::Error;
 use crate::{raw, Result};
 
+const LASZIP_USER_ID: &str = "laszip encoded";
+const LASZIP_RECORD_ID: u16 = 22204;
 const REGULAR_HEADER_SIZE: usize = 54;
 const EXTENDED_HEADER_SIZE: usize = 60;
 
@@ -45,6 +47,7 @@ pub enum Error {
 
 /// A variable length record.
 #[derive(Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Vlr {
     /// The user that created this record.
     ///
@@ -161,6 +164,22 @@ impl Vlr {
         self.data.len() > u16::MAX as usize
     }
 
+    /// Returns true if this is the laszip vlr, which describes how the points are compressed.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::Vlr;
+    /// let mut vlr = Vlr::default();
+    /// assert!(!vlr.is_laszip());
+    /// vlr.user_id = "laszip encoded".to_string();
+    /// vlr.record_id = 22204;
+    /// assert!(vlr.is_laszip());
+    /// ```
+    pub fn is_laszip(&self) -> bool {
+        self.user_id == LASZIP_USER_ID && self.record_id == LASZIP_RECORD_ID
+    }
+
     fn record_length_after_header(&self, is_extended: bool) -> Result<raw::vlr::RecordLength> {
         if is_extended {
             Ok(raw::vlr::RecordLength::Evlr(self.data.len() as u64))
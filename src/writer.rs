@@ -40,7 +40,7 @@ use std::path::Path;
 use crate::compression::CompressedPointWriter;
 
 use crate::point::Format;
-use crate::{Header, Point, Result};
+use crate::{raw, Bounds, Builder, Header, Point, Result};
 use thiserror::Error;
 
 /// Writer errors.
@@ -50,11 +50,24 @@ pub enum Error {
     #[error("the writer is closed")]
     Closed,
 
+    /// Closing the writer failed, and the destination went with the failed write.
+    #[error("the writer failed to close, so it has no destination")]
+    CloseFailed,
+
     /// The attributes of the point format and point do not match.
     #[error("the attributes of the point format ({format}) do not match the point: {point:?}")]
     #[allow(missing_docs)]
     PointAttributes { format: Format, point: Point },
 
+    /// The raw point's extra bytes don't match the point format.
+    #[error("the point format ({format}) has {} extra bytes, but the raw point has {len}", format.extra_bytes)]
+    #[allow(missing_docs)]
+    RawPointExtraBytes { format: Format, len: usize },
+
+    /// The precision must be positive and finite.
+    #[error("invalid precision: {0}")]
+    Precision(f64),
+
     /// Wrapper around `std::io::Error`.
     #[error(transparent)]
     Io(#[from] std::io::Error),
@@ -74,6 +87,7 @@ pub(crate) fn write_point_to<W: std::io::Write>(
 /// Trait that defines a PointWriter, s
 pub(crate) trait PointWriter<W: std::io::Write>: Debug + Send {
     fn write_next(&mut self, point: Point) -> Result<()>;
+    fn write_next_raw(&mut self, point: raw::Point) -> Result<()>;
     //https://users.rust-lang.org/t/is-there-a-way-to-move-a-trait-object/707
     fn into_inner(self: Box<Self>) -> W;
     fn get_mut(&mut self) -> &mut W;
@@ -92,6 +106,10 @@ impl<W: std::io::Write> PointWriter<W> for UnreachablePointWriter {
         unreachable!()
     }
 
+    fn write_next_raw(&mut self, _point: raw::Point) -> Result<()> {
+        unreachable!()
+    }
+
     fn into_inner(self: Box<Self>) -> W {
         unreachable!()
     }
@@ -122,6 +140,11 @@ impl<W: std::io::Write + Debug + Send> PointWriter<W> for UncompressedPointWrite
         Ok(())
     }
 
+    fn write_next_raw(&mut self, point: raw::Point) -> Result<()> {
+        self.header.add_raw_point(&point);
+        point.write_to(&mut self.dest, self.header.point_format())
+    }
+
     fn into_inner(self: Box<Self>) -> W {
         self.dest
     }
@@ -205,7 +228,7 @@ pub trait Write {
 /// ```
 #[derive(Debug)]
 pub struct Writer<W: 'static + std::io::Write + Seek + Debug + Send> {
-    closed: bool,
+    pub(crate) closed: bool,
     start: u64,
     point_writer: Box<dyn PointWriter<W> + Send>,
 }
@@ -275,7 +298,7 @@ impl<W: 'static + std::io::Write + Seek + Debug + Send> Writer<W> {
 
         let point_padding = self.header().point_padding().clone();
         self.point_writer.get_mut().write_all(&point_padding)?;
-        let raw_evlrs: Vec<Result<crate::raw::Vlr>> = {
+        let raw_evlrs: Vec<Result<raw::Vlr>> = {
             self.point_writer
                 .header()
                 .evlrs()
@@ -325,6 +348,36 @@ impl<W: 'static + std::io::Write + Seek + Debug + Send> Write for Writer<W> {
     }
 }
 
+impl<W: 'static + std::io::Write + Seek + Debug + Send> Writer<W> {
+    /// Writes a raw point record as-is.
+    ///
+    /// The record isn't converted to a `Point`, so its flag bits, scan angle, and other fields are
+    /// written exactly as they are. The header's point counts and bounds are still updated.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use las::{raw, Write, Writer};
+    /// let mut writer = Writer::default();
+    /// writer.write_raw(raw::Point::default()).unwrap();
+    /// assert_eq!(1, writer.header().number_of_points());
+    /// ```
+    pub fn write_raw(&mut self, point: raw::Point) -> Result<()> {
+        if self.closed {
+            return Err(Error::Closed.into());
+        }
+        let format = *self.header().point_format();
+        if point.extra_bytes.len() != usize::from(format.extra_bytes) {
+            return Err(Error::RawPointExtraBytes {
+                format,
+                len: point.extra_bytes.len(),
+            }
+            .into());
+        }
+        self.point_writer.write_next_raw(point)
+    }
+}
+
 impl<W: 'static + std::io::Write + Seek + Debug + Send> Writer<W> {
     /// Closes this writer and returns its inner `Write`, seeked to the beginning of the las data.
     ///
@@ -390,6 +443,153 @@ impl Writer<BufWriter<File>> {
     }
 }
 
+/// Writes LAS data, choosing the transforms from the points themselves.
+///
+/// Points are buffered in memory until the writer is closed. Then the transforms are fit to the
+/// bounds of the buffered points with `Builder::fit_transforms`, and everything is written in a
+/// second pass. If the points don't fit in memory, scan them once to grow a `Bounds`, call
+/// `Builder::fit_transforms` yourself, and use a regular `Writer`.
+///
+/// Like `Writer`, the data are written when the writer is dropped if `close` hasn't been called.
+///
+/// ```
+/// use std::io::Cursor;
+/// use las::{Point, Read, Reader, Write};
+/// use las::writer::AutoTransformWriter;
+///
+/// let mut writer = AutoTransformWriter::new(Cursor::new(Vec::new()), Default::default(), 0.001).unwrap();
+/// writer.write(Point { x: 500_000., y: 5_000_000., ..Default::default() }).unwrap();
+/// let reader = Reader::new(writer.into_inner().unwrap()).unwrap();
+/// assert!(reader.header().transforms().y.offset > 0.);
+/// ```
+#[derive(Debug)]
+pub struct AutoTransformWriter<W: 'static + std::io::Write + Seek + Debug + Send> {
+    dest: Option<W>,
+    header: Header,
+    precision: f64,
+    bounds: Bounds,
+    points: Vec<Point>,
+    writer: Option<Writer<W>>,
+}
+
+impl<W: 'static + std::io::Write + Seek + Debug + Send> AutoTransformWriter<W> {
+    /// Creates a new writer that picks transforms with this precision.
+    ///
+    /// The header's transforms are replaced when the writer is closed.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::io::Cursor;
+    /// use las::writer::AutoTransformWriter;
+    /// let writer = AutoTransformWriter::new(Cursor::new(Vec::new()), Default::default(), 0.01);
+    /// assert!(writer.is_ok());
+    /// let writer = AutoTransformWriter::new(Cursor::new(Vec::new()), Default::default(), 0.);
+    /// assert!(writer.is_err());
+    /// ```
+    pub fn new(dest: W, header: Header, precision: f64) -> Result<Self> {
+        if !(precision > 0. && precision.is_finite()) {
+            return Err(Error::Precision(precision).into());
+        }
+        Ok(AutoTransformWriter {
+            dest: Some(dest),
+            header,
+            precision,
+            bounds: Bounds::default(),
+            points: Vec::new(),
+            writer: None,
+        })
+    }
+
+    /// Fits the transforms and writes all of the buffered points.
+    ///
+    /// The header is built before the destination is used, so a bad header leaves the writer
+    /// open. If writing fails, the destination is gone and `into_inner` returns an error.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::io::Cursor;
+    /// use las::writer::AutoTransformWriter;
+    /// let mut writer = AutoTransformWriter::new(Cursor::new(Vec::new()), Default::default(), 0.01).unwrap();
+    /// writer.close().unwrap();
+    /// assert!(writer.close().is_err());
+    /// ```
+    pub fn close(&mut self) -> Result<()> {
+        if self.dest.is_none() {
+            return Err(Error::Closed.into());
+        }
+        let mut builder = Builder::from(self.header.clone());
+        builder.fit_transforms(&self.bounds, self.precision);
+        let header = builder.into_header()?;
+        let dest = self.dest.take().expect("the destination was just checked");
+        let mut writer = Writer::new(dest, header)?;
+        if let Err(err) = self
+            .points
+            .drain(..)
+            .try_for_each(|point| writer.write(point))
+            .and_then(|()| writer.close())
+        {
+            // Don't let the writer retry, and panic, when it's dropped.
+            writer.closed = true;
+            return Err(err);
+        }
+        self.header = writer.header().clone();
+        self.writer = Some(writer);
+        Ok(())
+    }
+
+    /// Closes this writer, if needed, and returns the inner `Write`, seeked to the start of the
+    /// las data.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::io::Cursor;
+    /// use las::writer::AutoTransformWriter;
+    /// let writer = AutoTransformWriter::new(Cursor::new(Vec::new()), Default::default(), 0.01).unwrap();
+    /// let cursor = writer.into_inner().unwrap();
+    /// ```
+    pub fn into_inner(mut self) -> Result<W> {
+        if self.dest.is_some() {
+            self.close()?;
+        }
+        self.writer.take().ok_or(Error::CloseFailed)?.into_inner()
+    }
+}
+
+impl<W: 'static + std::io::Write + Seek + Debug + Send> Write for AutoTransformWriter<W> {
+    /// Returns the header, with the fitted transforms once the writer is closed.
+    fn header(&self) -> &Header {
+        &self.header
+    }
+
+    /// Buffers a point.
+    fn write(&mut self, point: Point) -> Result<()> {
+        if self.dest.is_none() {
+            return Err(Error::Closed.into());
+        }
+        if !point.matches(self.header.point_format()) {
+            return Err(Error::PointAttributes {
+                format: *self.header.point_format(),
+                point,
+            }
+            .into());
+        }
+        self.bounds.grow(&point);
+        self.points.push(point);
+        Ok(())
+    }
+}
+
+impl<W: 'static + std::io::Write + Seek + Debug + Send> Drop for AutoTransformWriter<W> {
+    fn drop(&mut self) {
+        if self.dest.is_some() {
+            self.close().expect("Error when dropping the writer");
+        }
+    }
+}
+
 impl Default for Writer<Cursor<Vec<u8>>> {
     fn default() -> Writer<Cursor<Vec<u8>>> {
         Writer::new(Cursor::new(Vec::new()), Header::default()).unwrap()
@@ -410,7 +610,7 @@ mod tests {
 
     use crate::header::Builder;
     use crate::point::Format;
-    use crate::Version;
+    use crate::{Read, Reader, Version};
 
     use super::*;
 
@@ -429,6 +629,109 @@ mod tests {
         assert!(writer.write(Default::default()).is_err());
     }
 
+    #[test]
+    fn auto_transform_utm() {
+        let points = [
+            (500_000., 5_000_000., 10.),
+            (501_000.25, 5_001_000.5, 12.75),
+        ]
+        .iter()
+        .map(|&(x, y, z)| Point {
+            x,
+            y,
+            z,
+            ..Default::default()
+        })
+        .collect::<Vec<_>>();
+        assert!(points[1].clone().into_raw(&Default::default()).is_err());
+
+        let mut writer =
+            AutoTransformWriter::new(Cursor::new(Vec::new()), Default::default(), 0.001).unwrap();
+        for point in &points {
+            writer.write(point.clone()).unwrap();
+        }
+        let mut reader = Reader::new(writer.into_inner().unwrap()).unwrap();
+        assert_eq!(0.001, reader.header().transforms().y.scale);
+        assert_eq!(2, reader.header().number_of_points());
+        let read = reader.points().collect::<Result<Vec<_>>>().unwrap();
+        assert_eq!(points, read);
+    }
+
+    #[test]
+    fn auto_transform_already_closed() {
+        let mut writer =
+            AutoTransformWriter::new(Cursor::new(Vec::new()), Default::default(), 0.001).unwrap();
+        writer.close().unwrap();
+        assert!(writer.close().is_err());
+        assert!(writer.write(Default::default()).is_err());
+    }
+
+    #[test]
+    fn auto_transform_close_failed() {
+        #[derive(Debug)]
+        struct Broken;
+
+        impl std::io::Write for Broken {
+            fn write(&mut self, _: &[u8]) -> std::io::Result<usize> {
+                Err(std::io::Error::other("broken"))
+            }
+            fn flush(&mut self) -> std::io::Result<()> {
+                Ok(())
+            }
+        }
+
+        impl Seek for Broken {
+            fn seek(&mut self, _: SeekFrom) -> std::io::Result<u64> {
+                Ok(0)
+            }
+        }
+
+        let mut writer = AutoTransformWriter::new(Broken, Default::default(), 0.001).unwrap();
+        assert!(writer.close().is_err());
+        assert!(writer.close().is_err());
+        assert!(writer.into_inner().is_err());
+    }
+
+    #[test]
+    fn auto_transform_close_failed_after_header() {
+        /// Fails every write once one goes past 300 bytes.
+        #[derive(Debug)]
+        struct Full(Cursor<Vec<u8>>, bool);
+
+        impl std::io::Write for Full {
+            fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+                self.1 |= self.0.position() + buf.len() as u64 > 300;
+                if self.1 {
+                    Err(std::io::Error::other("full"))
+                } else {
+                    self.0.write(buf)
+                }
+            }
+            fn flush(&mut self) -> std::io::Result<()> {
+                Ok(())
+            }
+        }
+
+        impl Seek for Full {
+            fn seek(&mut self, position: SeekFrom) -> std::io::Result<u64> {
+                self.0.seek(position)
+            }
+        }
+
+        let mut writer = AutoTransformWriter::new(
+            Full(Cursor::new(Vec::new()), false),
+            Default::default(),
+            0.001,
+        )
+        .unwrap();
+        for _ in 0..10 {
+            writer.write(Default::default()).unwrap();
+        }
+        assert!(writer.close().is_err());
+        assert!(writer.close().is_err());
+        assert!(writer.into_inner().is_err());
+    }
+
     #[test]
     fn missing_extra_bytes() {
         let format = Format {
@@ -439,6 +742,21 @@ mod tests {
         assert!(writer.write(Default::default()).is_err());
     }
 
+    #[test]
+    fn overlap_points_in_legacy_format() {
+        let mut writer = writer(Format::new(0).unwrap(), Version::new(1, 2));
+        let point = Point {
+            classification: crate::point::Classification::OverlapPoints,
+            ..Default::default()
+        };
+        assert!(writer.write(point).is_err());
+        let point = Point {
+            is_overlap: true,
+            ..Default::default()
+        };
+        writer.write(point).unwrap();
+    }
+
     #[test]
     fn missing_gps_time() {
         let format = Format::new(1).unwrap();
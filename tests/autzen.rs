@@ -90,3 +90,61 @@ fn test_seek_0_works_on_las() {
 fn test_seek_0_works_on_laz() {
     test_seek_0_works_on("tests/data/autzen.laz");
 }
+
+fn test_copy_raw_is_byte_identical(path: &str) {
+    use las::{Read, Reader, Writer};
+    use std::io::Cursor;
+
+    let mut reader = Reader::from_path(path).unwrap();
+    let mut writer = Writer::new(Cursor::new(Vec::new()), reader.header().clone()).unwrap();
+    assert_eq!(106, reader.copy_raw(&mut writer, |_| {}).unwrap());
+    let copy = writer.into_inner().unwrap().into_inner();
+    assert!(std::fs::read(path).unwrap() == copy);
+}
+
+#[test]
+fn test_copy_raw_is_byte_identical_las() {
+    test_copy_raw_is_byte_identical("tests/data/autzen.las");
+}
+
+#[cfg(feature = "laz")]
+#[test]
+fn test_copy_raw_preserves_laz_records() {
+    use las::{Read, Reader, Writer};
+    use std::io::Cursor;
+
+    fn copy(mut reader: Reader, compress: bool) -> Vec<u8> {
+        let mut builder = las::Builder::from(reader.header().clone());
+        builder.point_format.is_compressed = compress;
+        builder.vlrs.retain(|vlr| vlr.user_id != "laszip encoded");
+        let mut writer =
+            Writer::new(Cursor::new(Vec::new()), builder.into_header().unwrap()).unwrap();
+        reader.copy_raw(&mut writer, |_| {}).unwrap();
+        writer.into_inner().unwrap().into_inner()
+    }
+
+    let once = copy(Reader::from_path("tests/data/autzen.laz").unwrap(), false);
+    let laz = copy(Reader::from_path("tests/data/autzen.laz").unwrap(), true);
+    let twice = copy(Reader::new(Cursor::new(laz)).unwrap(), false);
+    assert!(once == twice);
+}
+
+#[cfg(feature = "laz")]
+#[test]
+fn test_raw_points_match_between_las_and_laz() {
+    use las::{Read, Reader};
+    let las = Reader::from_path("tests/data/autzen.las")
+        .unwrap()
+        .raw_points()
+        .collect::<las::Result<Vec<_>>>()
+        .unwrap();
+    let laz = Reader::from_path("tests/data/autzen.laz")
+        .unwrap()
+        .raw_points()
+        .collect::<las::Result<Vec<_>>>()
+        .unwrap();
+    assert_eq!(las.len(), laz.len());
+    for (a, b) in las.iter().zip(&laz) {
+        assert_eq!((a.x, a.y, a.z), (b.x, b.y, b.z));
+    }
+}
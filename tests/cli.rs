@@ -0,0 +1,221 @@
+//! Run the `las` command-line tool against the test data.
+
+#![cfg(feature = "cli")]
+
+use las::{Read, Reader};
+use std::path::PathBuf;
+use std::process::Command;
+
+fn las(args: &[&str]) -> String {
+    let output = Command::new(env!("CARGO_BIN_EXE_las"))
+        .args(args)
+        .output()
+        .unwrap();
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    String::from_utf8(output.stdout).unwrap()
+}
+
+fn temp_path(name: &str) -> PathBuf {
+    std::env::temp_dir().join(format!("las-cli-{}-{}", std::process::id(), name))
+}
+
+fn read_all(path: &PathBuf) -> Vec<las::Point> {
+    Reader::from_path(path)
+        .unwrap()
+        .points()
+        .map(|point| point.unwrap())
+        .collect()
+}
+
+#[test]
+fn info() {
+    let output = las(&["info", "tests/data/autzen.las"]);
+    assert!(output.contains("number_of_points: 106"));
+    assert!(output.contains("GTCitationGeoKey"));
+}
+
+#[test]
+fn info_json_stats() {
+    let output = las(&["info", "--json", "--stats", "tests/data/autzen.las"]);
+    let info: serde_json::Value = serde_json::from_str(&output).unwrap();
+    assert_eq!(106, info["number_of_points"]);
+    assert_eq!("wkt", info["vlrs"][0]["kind"]);
+    assert_eq!(info["bounds"]["min"]["z"], info["stats"]["z"]["min"]);
+}
+
+#[test]
+fn info_extra_bytes() {
+    let output = las(&["info", "--json", "tests/data/extrabytes.laz"]);
+    let info: serde_json::Value = serde_json::from_str(&output).unwrap();
+    let vlr = info["vlrs"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|vlr| vlr["kind"] == "extra bytes")
+        .unwrap();
+    assert_eq!("Colors", vlr["content"][0]["name"]);
+    assert_eq!("deprecated (23)", vlr["content"][0]["data_type"]);
+    assert_eq!(7, vlr["content"][1]["size"]);
+}
+
+#[test]
+fn translate_las_laz_las() {
+    let laz = temp_path("translate.laz");
+    let roundtrip = temp_path("translate.las");
+    las(&["translate", "tests/data/autzen.las", laz.to_str().unwrap()]);
+    las(&[
+        "translate",
+        laz.to_str().unwrap(),
+        roundtrip.to_str().unwrap(),
+    ]);
+    assert!(
+        Reader::from_path(&laz)
+            .unwrap()
+            .header()
+            .point_format()
+            .is_compressed
+    );
+    assert_eq!(
+        read_all(&"tests/data/autzen.las".into()),
+        read_all(&roundtrip)
+    );
+    std::fs::remove_file(laz).unwrap();
+    std::fs::remove_file(roundtrip).unwrap();
+}
+
+#[test]
+fn translate_format_and_filter() {
+    let output = temp_path("translate-filter.las");
+    las(&[
+        "translate",
+        "tests/data/autzen.las",
+        output.to_str().unwrap(),
+        "--las-version",
+        "1.4",
+        "--point-format",
+        "7",
+        "--scale",
+        "0.001",
+        "--first-only",
+        "--thin",
+        "2",
+    ]);
+    let reader = Reader::from_path(&output).unwrap();
+    assert_eq!(7, reader.header().point_format().to_u8().unwrap());
+    assert_eq!(0.001, reader.header().transforms().y.scale);
+    assert_eq!(45, reader.header().number_of_points());
+    assert!(read_all(&output)
+        .iter()
+        .all(|point| point.return_number == 1 && point.color.is_some()));
+    std::fs::remove_file(output).unwrap();
+}
+
+#[test]
+fn translate_extended_classification() {
+    let output = temp_path("translate-classification.las");
+    las(&[
+        "translate",
+        "tests/data/autzen.las",
+        output.to_str().unwrap(),
+        "--las-version",
+        "1.4",
+        "--point-format",
+        "6",
+        "--drop-classification",
+        "12",
+    ]);
+    assert_eq!(106, read_all(&output).len());
+    std::fs::remove_file(output).unwrap();
+}
+
+#[test]
+fn translate_thin_zero() {
+    let output = Command::new(env!("CARGO_BIN_EXE_las"))
+        .args([
+            "translate",
+            "tests/data/autzen.las",
+            temp_path("translate-thin-zero.las").to_str().unwrap(),
+            "--thin",
+            "0",
+        ])
+        .output()
+        .unwrap();
+    assert!(!output.status.success());
+}
+
+#[test]
+fn merge() {
+    let output = temp_path("merge.laz");
+    las(&[
+        "merge",
+        "tests/data/autzen.las",
+        "tests/data/autzen.las",
+        "--output",
+        output.to_str().unwrap(),
+    ]);
+    let reader = Reader::from_path(&output).unwrap();
+    assert_eq!(212, reader.header().number_of_points());
+    assert_eq!("MERGE", reader.header().system_identifier());
+    assert_eq!(212, read_all(&output).len());
+    std::fs::remove_file(output).unwrap();
+}
+
+#[test]
+fn split_by_points() {
+    let dir = temp_path("split");
+    std::fs::create_dir_all(&dir).unwrap();
+    las(&[
+        "split",
+        "tests/data/autzen.las",
+        "--output-dir",
+        dir.to_str().unwrap(),
+        "--points",
+        "50",
+    ]);
+    let counts = ["autzen_0.las", "autzen_1.las", "autzen_2.las"]
+        .iter()
+        .map(|name| read_all(&dir.join(name)).len())
+        .collect::<Vec<_>>();
+    assert_eq!(vec![50, 50, 6], counts);
+    std::fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn split_by_tile() {
+    let dir = temp_path("split-tile");
+    std::fs::create_dir_all(&dir).unwrap();
+    las(&[
+        "split",
+        "tests/data/autzen.las",
+        "--output-dir",
+        dir.to_str().unwrap(),
+        "--tile-size",
+        "100",
+        "--extension",
+        "laz",
+    ]);
+    let mut total = 0;
+    for entry in std::fs::read_dir(&dir).unwrap() {
+        let path = entry.unwrap().path();
+        assert_eq!("laz", path.extension().unwrap());
+        let name = path.file_stem().unwrap().to_str().unwrap();
+        let (column, row) = name
+            .strip_prefix("autzen_")
+            .unwrap()
+            .split_once('_')
+            .unwrap();
+        let (column, row) = (column.parse::<i64>().unwrap(), row.parse::<i64>().unwrap());
+        let points = read_all(&path);
+        for point in &points {
+            assert_eq!(column, (point.x / 100.).floor() as i64);
+            assert_eq!(row, (point.y / 100.).floor() as i64);
+        }
+        total += points.len();
+    }
+    assert_eq!(106, total);
+    std::fs::remove_dir_all(dir).unwrap();
+}
@@ -0,0 +1,45 @@
+//! Round-trip headers and points through serde.
+
+#![cfg(feature = "serde")]
+
+use las::{Header, Point, Read, Reader};
+
+#[test]
+fn header() {
+    let reader = Reader::from_path("tests/data/autzen.las").unwrap();
+    let header = reader.header().clone();
+    let json = serde_json::to_string(&header).unwrap();
+    assert_eq!(header, serde_json::from_str::<Header>(&json).unwrap());
+}
+
+#[test]
+fn points() {
+    let mut reader = Reader::from_path("tests/data/autzen.las").unwrap();
+    let points = reader
+        .points()
+        .map(|point| point.unwrap())
+        .collect::<Vec<_>>();
+    let json = serde_json::to_string(&points).unwrap();
+    assert_eq!(points, serde_json::from_str::<Vec<Point>>(&json).unwrap());
+}
+
+#[test]
+fn invalid_header() {
+    // Point format 6 isn't supported by las 1.2, so the builder refuses it.
+    let mut json = serde_json::to_value(Header::default()).unwrap();
+    json["point_format"] = serde_json::to_value(las::point::Format::new(6).unwrap()).unwrap();
+    assert!(serde_json::from_value::<Header>(json).is_err());
+}
+
+#[test]
+fn class_map() {
+    let class_map: las::class_map::ClassMap =
+        serde_json::from_str(r#"{"7": 18, "64": 1}"#).unwrap();
+    assert_eq!(18, class_map.get(7));
+    assert_eq!(1, class_map.get(64));
+    assert_eq!(2, class_map.get(2));
+    assert_eq!(
+        r#"{"7":18,"64":1}"#,
+        serde_json::to_string(&class_map).unwrap()
+    );
+}